@@ -0,0 +1,15 @@
+let map = fn(arr, f) {
+  let accumulated = [];
+  let i = 0;
+
+  while (i < len(arr)) {
+    accumulated = push(accumulated, f(arr[i]));
+    i += 1;
+  }
+
+  accumulated
+};
+
+let a = [1, 2, 3, 4];
+let double = fn(x) { x * 2 };
+map(a, double);
@@ -1,4 +1,7 @@
-use crate::{parser::Parser, tokens::token::Token};
+use crate::{
+    parser::Parser,
+    tokens::{span::Span, token::Token},
+};
 
 use super::{AstNode, ExpressionNode, ParsableResult, ParsePrefix};
 
@@ -6,6 +9,7 @@ use super::{AstNode, ExpressionNode, ParsableResult, ParsePrefix};
 pub struct ArrayLiteral {
     pub token: Token,
     pub expressions: Vec<ExpressionNode>,
+    pub span: Span,
 }
 
 impl AstNode for ArrayLiteral {
@@ -13,6 +17,10 @@ impl AstNode for ArrayLiteral {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         format!(
             "[{}]",
@@ -28,11 +36,13 @@ impl AstNode for ArrayLiteral {
 impl ParsePrefix for ArrayLiteral {
     fn parse_prefix(parser: &mut Parser) -> ParsableResult<ExpressionNode> {
         let token = parser.current_token.clone();
+        let start = parser.current_span;
         let expressions = parser.parse_expression_list(Token::RBRACKET)?;
 
         Ok(ExpressionNode::ArrayLiteral(ArrayLiteral {
             token,
             expressions,
+            span: parser.span_from(start),
         }))
     }
 }
@@ -44,7 +54,7 @@ mod test {
             infix_expression::test::test_infix_expression, test::test_expression, ExpressionNode,
             StatementNode,
         },
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
         tokens::token::Token,
     };
 
@@ -54,7 +64,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -81,7 +91,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -0,0 +1,164 @@
+use crate::{
+    parser::{
+        parse_error::{ParseError, ParseErrorKind},
+        precedence::Precedence,
+        Parser,
+    },
+    tokens::{span::Span, token::Token},
+};
+
+use super::{AstNode, ExpressionNode, ParsableResult, ParseInfix};
+
+#[derive(Debug, Clone)]
+pub struct AssignExpression {
+    pub token: Token,
+    pub target: Box<ExpressionNode>,
+    pub value: Box<ExpressionNode>,
+    pub span: Span,
+}
+
+impl AssignExpression {
+    // the operator applied between the current and the new value for
+    // compound assignments like `x += 1`
+    pub fn operator(&self) -> Option<Token> {
+        match self.token {
+            Token::PLUS_ASSIGN => Some(Token::PLUS),
+            Token::MINUS_ASSIGN => Some(Token::MINUS),
+            Token::ASTERISK_ASSIGN => Some(Token::ASTERISK),
+            Token::SLASH_ASSIGN => Some(Token::SLASH),
+            Token::PERCENT_ASSIGN => Some(Token::PERCENT),
+            _ => None,
+        }
+    }
+
+    // the assigned variable and the chain of index expressions leading from
+    // it to the target, outermost first
+    pub fn path(&self) -> (&ExpressionNode, Vec<&ExpressionNode>) {
+        let mut target = self.target.as_ref();
+        let mut keys = vec![];
+
+        while let ExpressionNode::IndexExpresssion(index) = target {
+            keys.push(index.right.as_ref());
+            target = &index.left;
+        }
+
+        keys.reverse();
+        (target, keys)
+    }
+}
+
+impl AstNode for AssignExpression {
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn string(&self) -> String {
+        format!(
+            "({} {} {})",
+            self.target.string(),
+            self.token.literal(),
+            self.value.string()
+        )
+    }
+}
+
+impl ParseInfix for AssignExpression {
+    fn parse_infix(parser: &mut Parser, left: ExpressionNode) -> ParsableResult<ExpressionNode> {
+        let token = parser.current_token.clone();
+        let start = left.span();
+
+        let mut root = &left;
+        while let ExpressionNode::IndexExpresssion(index) = root {
+            root = &index.left;
+        }
+
+        if !matches!(root, ExpressionNode::Identifier(_)) {
+            return Err(ParseError::new(
+                ParseErrorKind::InvalidAssignmentTarget,
+                token,
+                start,
+            ));
+        }
+
+        parser.next_token();
+
+        // parsing the value at the lowest precedence makes `a = b = c`
+        // right-associative
+        let value = parser.parse_expression(Precedence::LOWEST)?;
+
+        Ok(ExpressionNode::AssignExpression(AssignExpression {
+            token,
+            target: Box::new(left),
+            value: Box::new(value),
+            span: parser.span_from(start),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::{
+        ast::{AstNode, ExpressionNode, StatementNode},
+        parser::{parse_error::ParseError, Parser},
+        tokens::token::Token,
+    };
+
+    #[rstest]
+    #[case("x = 5;", Token::ASSIGN, "(x = 5)")]
+    #[case("x += 1;", Token::PLUS_ASSIGN, "(x += 1)")]
+    #[case("x -= y * 2;", Token::MINUS_ASSIGN, "(x -= (y * 2))")]
+    #[case("x *= 2;", Token::ASTERISK_ASSIGN, "(x *= 2)")]
+    #[case("x /= 2;", Token::SLASH_ASSIGN, "(x /= 2)")]
+    #[case("x %= 2;", Token::PERCENT_ASSIGN, "(x %= 2)")]
+    #[case("a = b = 1 + 2;", Token::ASSIGN, "(a = (b = (1 + 2)))")]
+    #[case("arr[0] = 1;", Token::ASSIGN, "((arr[0]) = 1)")]
+    #[case(
+        "h[\"a\"][i + 1] += 2;",
+        Token::PLUS_ASSIGN,
+        "(((h[a])[(i + 1)]) += 2)"
+    )]
+    #[case("x = y || z;", Token::ASSIGN, "(x = (y || z))")]
+    fn test_assign_expression(#[case] input: &str, #[case] token: Token, #[case] expected: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        let empty: Vec<ParseError> = vec![];
+
+        assert_eq!(errors, empty);
+        assert_eq!(program.statements.len(), 1);
+
+        let node = program.statements.into_iter().next().unwrap();
+        let StatementNode::ExpressionStatement(expression) = node else {
+            panic!("expected ExpressionStatement for node, got {:?}", node);
+        };
+        let ExpressionNode::AssignExpression(assign) = &expression.expression else {
+            panic!(
+                "expected AssignExpression for expression, got {:?}",
+                expression.expression
+            );
+        };
+
+        assert_eq!(assign.token, token);
+        assert_eq!(assign.string(), expected);
+    }
+
+    #[rstest]
+    #[case("1 = 2;")]
+    #[case("f() = 2;")]
+    #[case("f()[0] = 2;")]
+    #[case("(a + b) += 2;")]
+    fn test_invalid_assignment_target(#[case] input: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (_, errors) = parser.parse_program();
+
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].message(), "invalid assignment target");
+    }
+}
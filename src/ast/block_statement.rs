@@ -1,4 +1,4 @@
-use crate::tokens::token::Token;
+use crate::tokens::{span::Span, token::Token};
 
 use super::{AstNode, StatementNode};
 
@@ -6,6 +6,7 @@ use super::{AstNode, StatementNode};
 pub struct BlockStatement {
     pub token: Token,
     pub statements: Vec<StatementNode>,
+    pub span: Span,
 }
 
 impl AstNode for BlockStatement {
@@ -13,6 +14,10 @@ impl AstNode for BlockStatement {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         self.statements
             .iter()
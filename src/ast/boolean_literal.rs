@@ -1,4 +1,4 @@
-use crate::tokens::token::Token;
+use crate::tokens::{span::Span, token::Token};
 
 use super::{AstNode, ParsePrefix};
 
@@ -6,6 +6,7 @@ use super::{AstNode, ParsePrefix};
 pub struct BooleanLiteral {
     pub token: Token,
     pub value: bool,
+    pub span: Span,
 }
 
 impl AstNode for BooleanLiteral {
@@ -13,6 +14,10 @@ impl AstNode for BooleanLiteral {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         format!("{}", self.value)
     }
@@ -25,6 +30,7 @@ impl ParsePrefix for BooleanLiteral {
         Ok(super::ExpressionNode::BooleanLiteral(BooleanLiteral {
             token: parser.current_token.clone(),
             value: parser.current_token.is(&Token::TRUE),
+            span: parser.current_span,
         }))
     }
 }
@@ -36,7 +42,7 @@ pub mod test {
 
     use crate::{
         ast::{ExpressionNode, StatementNode},
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
     };
 
     pub fn assert_boolean_literal(expression: &ExpressionNode, value: bool) {
@@ -56,7 +62,7 @@ pub mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
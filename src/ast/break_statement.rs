@@ -0,0 +1,46 @@
+use crate::{
+    parser::{parse_error::ParseErrorKind, Parser},
+    tokens::{span::Span, token::Token},
+};
+
+use super::{AstNode, ParsableResult, ParseStatement, StatementNode};
+
+#[derive(Debug, Clone)]
+pub struct BreakStatement {
+    pub token: Token,
+    pub span: Span,
+}
+
+impl AstNode for BreakStatement {
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn string(&self) -> String {
+        "break;".to_string()
+    }
+}
+
+impl ParseStatement for BreakStatement {
+    fn parse(parser: &mut Parser) -> ParsableResult<StatementNode> {
+        if parser.loop_depth == 0 {
+            return Err(parser.current_error(ParseErrorKind::OutsideLoop));
+        }
+
+        let token = parser.current_token.clone();
+        let span = parser.current_span;
+
+        if parser.peek_token.is(&Token::SEMICOLON) {
+            parser.next_token();
+        }
+
+        Ok(StatementNode::BreakStatement(BreakStatement {
+            token,
+            span,
+        }))
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{parser::Parser, tokens::token::Token};
+use crate::{
+    parser::Parser,
+    tokens::{span::Span, token::Token},
+};
 
 use super::{AstNode, ExpressionNode, ParsableResult, ParseInfix};
 
@@ -7,6 +10,7 @@ pub struct CallExpression {
     pub token: Token,
     pub function: Box<ExpressionNode>,
     pub arguments: Vec<ExpressionNode>,
+    pub span: Span,
 }
 
 impl AstNode for CallExpression {
@@ -14,6 +18,10 @@ impl AstNode for CallExpression {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         format!(
             "{}({})",
@@ -30,6 +38,7 @@ impl AstNode for CallExpression {
 impl ParseInfix for CallExpression {
     fn parse_infix(parser: &mut Parser, left: ExpressionNode) -> ParsableResult<ExpressionNode> {
         let token = parser.current_token.clone();
+        let start = left.span();
 
         let arguments = parser.parse_expression_list(Token::RPAREN)?;
 
@@ -37,6 +46,7 @@ impl ParseInfix for CallExpression {
             token,
             function: Box::new(left),
             arguments,
+            span: parser.span_from(start),
         }))
     }
 }
@@ -51,7 +61,7 @@ mod test {
             infix_expression::test::test_infix_expression, test::test_expression, AstNode,
             ExpressionNode, StatementNode,
         },
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
         tokens::token::Token,
     };
 
@@ -61,7 +71,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -104,7 +114,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -1,4 +1,7 @@
-use crate::{parser::precedence::Precedence, tokens::token::Token};
+use crate::{
+    parser::precedence::Precedence,
+    tokens::{span::Span, token::Token},
+};
 
 use super::{AstNode, ExpressionNode, ParseStatement};
 
@@ -6,6 +9,7 @@ use super::{AstNode, ExpressionNode, ParseStatement};
 pub struct ExpressionStatement {
     pub token: Token,
     pub expression: ExpressionNode,
+    pub span: Span,
 }
 
 impl AstNode for ExpressionStatement {
@@ -13,6 +17,10 @@ impl AstNode for ExpressionStatement {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         self.expression.string()
     }
@@ -21,15 +29,26 @@ impl AstNode for ExpressionStatement {
 impl ParseStatement for ExpressionStatement {
     fn parse(parser: &mut crate::parser::Parser) -> super::ParsableResult<super::StatementNode> {
         let token = parser.current_token.clone();
+        let start = parser.current_span;
 
-        let expression = parser.parse_expression(Precedence::LOWEST)?;
+        // a `for` starting the statement ends it the way a `while` does, so a
+        // `[` or `(` after it starts the next statement instead of indexing or
+        // calling the loop
+        let expression = match parser.current_token {
+            Token::FOR => parser.parse_prefix()?,
+            _ => parser.parse_expression(Precedence::LOWEST)?,
+        };
 
         if parser.peek_token.is(&Token::SEMICOLON) {
             parser.next_token();
         }
 
         Ok(super::StatementNode::ExpressionStatement(
-            ExpressionStatement { token, expression },
+            ExpressionStatement {
+                token,
+                expression,
+                span: parser.span_from(start),
+            },
         ))
     }
 }
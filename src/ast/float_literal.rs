@@ -0,0 +1,81 @@
+use crate::{
+    parser::parse_error::ParseErrorKind,
+    tokens::{span::Span, token::Token},
+};
+
+use super::{AstNode, ParsePrefix};
+
+#[derive(Debug, Clone)]
+pub struct FloatLiteral {
+    pub token: Token,
+    pub value: f64,
+    pub span: Span,
+}
+
+impl AstNode for FloatLiteral {
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn string(&self) -> String {
+        format!("{:?}", self.value)
+    }
+}
+
+impl ParsePrefix for FloatLiteral {
+    fn parse_prefix(
+        parser: &mut crate::parser::Parser,
+    ) -> super::ParsableResult<super::ExpressionNode> {
+        let Token::FLOAT(value) = parser.current_token.clone() else {
+            return Err(parser.current_error(ParseErrorKind::ExpectedExpression));
+        };
+
+        Ok(super::ExpressionNode::FloatLiteral(FloatLiteral {
+            token: Token::FLOAT(value),
+            value,
+            span: parser.current_span,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::{
+        ast::{ExpressionNode, StatementNode},
+        parser::{parse_error::ParseError, Parser},
+    };
+
+    #[rstest]
+    #[case("1.5;", 1.5)]
+    #[case("0.25;", 0.25)]
+    #[case("2e3;", 2000.0)]
+    #[case("1.5E-2;", 0.015)]
+    fn test_float_literal(#[case] input: &str, #[case] value: f64) {
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        let empty: Vec<ParseError> = vec![];
+
+        assert_eq!(errors, empty);
+        assert_eq!(program.statements.len(), 1);
+
+        let node = program.statements.into_iter().next().unwrap();
+        let StatementNode::ExpressionStatement(expression) = node else {
+            panic!("expected ExpressionStatement for node, got {:?}", node);
+        };
+        let ExpressionNode::FloatLiteral(float) = expression.expression else {
+            panic!(
+                "expected FloatLiteral for expression, got {:?}",
+                expression.expression
+            );
+        };
+
+        assert_eq!(float.value, value);
+    }
+}
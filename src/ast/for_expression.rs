@@ -0,0 +1,194 @@
+use crate::{
+    parser::{parse_error::ParseErrorKind, precedence::Precedence, Parser},
+    tokens::{span::Span, token::Token},
+};
+
+use super::{
+    block_statement::BlockStatement, identifier::Identifier, AstNode, ExpressionNode,
+    ParsableResult, ParsePrefix,
+};
+
+#[derive(Debug, Clone)]
+pub struct ForExpression {
+    pub token: Token,
+    // a single variable binds array items, characters, range values and hash
+    // keys, a second one binds the value while the first takes the index or key
+    pub variables: Vec<Identifier>,
+    pub iterable: Box<ExpressionNode>,
+    pub body: BlockStatement,
+    pub span: Span,
+}
+
+impl AstNode for ForExpression {
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn string(&self) -> String {
+        format!(
+            "for ({} in {}) {}",
+            self.variables
+                .iter()
+                .map(|v| v.string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.iterable.string(),
+            self.body.string()
+        )
+    }
+}
+
+impl ForExpression {
+    fn parse_variable(parser: &mut Parser) -> ParsableResult<Identifier> {
+        parser.next_token();
+
+        let ExpressionNode::Identifier(ident) = Identifier::parse_prefix(parser)? else {
+            return Err(parser.current_error(ParseErrorKind::ExpectedIdentifier));
+        };
+
+        Ok(ident)
+    }
+}
+
+impl ParsePrefix for ForExpression {
+    fn parse_prefix(parser: &mut Parser) -> ParsableResult<ExpressionNode> {
+        let token = parser.current_token.clone();
+        let start = parser.current_span;
+
+        parser.expect_token(Token::LPAREN)?;
+
+        let mut variables = vec![ForExpression::parse_variable(parser)?];
+        if parser.peek_token.is(&Token::COMMA) {
+            parser.next_token();
+            variables.push(ForExpression::parse_variable(parser)?);
+        }
+
+        parser.expect_token(Token::IN)?;
+        parser.next_token();
+
+        let iterable = parser.parse_expression(Precedence::LOWEST)?;
+
+        parser.expect_token(Token::RPAREN)?;
+        parser.expect_token(Token::LBRACE)?;
+
+        parser.loop_depth += 1;
+        let body = parser.parse_block();
+        parser.loop_depth -= 1;
+
+        Ok(ExpressionNode::ForExpression(ForExpression {
+            token,
+            variables,
+            iterable: Box::new(iterable),
+            body: body?,
+            span: parser.span_from(start),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::{
+        ast::{test::test_expression, AstNode, ExpressionNode, StatementNode},
+        parser::{parse_error::ParseError, Parser},
+    };
+
+    #[rstest]
+    #[case("for (x in xs) { puts(x) }", vec!["x"], "for (x in xs) puts(x)")]
+    #[case(
+        "for (k, v in {1: 2}) { k + v; break }",
+        vec!["k", "v"],
+        "for (k, v in {1:2}) (k + v)break;"
+    )]
+    fn test_for_expression(
+        #[case] input: &str,
+        #[case] variables: Vec<&str>,
+        #[case] expected: &str,
+    ) {
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        let empty: Vec<ParseError> = vec![];
+
+        assert_eq!(errors, empty);
+        assert_eq!(program.statements.len(), 1);
+
+        let node = program.statements.first().unwrap();
+        let StatementNode::ExpressionStatement(expression) = node else {
+            panic!("expected ExpressionStatement for node, got {:?}", node);
+        };
+
+        let ExpressionNode::ForExpression(for_expression) = &expression.expression else {
+            panic!("expected ForExpression for node, got {:?}", node);
+        };
+
+        assert_eq!(for_expression.variables.len(), variables.len());
+        for (variable, expected) in for_expression.variables.iter().zip(variables) {
+            assert_eq!(variable.value, expected);
+        }
+
+        assert_eq!(for_expression.string(), expected);
+    }
+
+    #[test]
+    fn test_for_iterable_expression() {
+        let input = "for (c in \"abc\") { c }";
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        assert_eq!(errors, Vec::<ParseError>::new());
+
+        let StatementNode::ExpressionStatement(expression) = &program.statements[0] else {
+            panic!(
+                "expected ExpressionStatement, got {:?}",
+                program.statements[0]
+            );
+        };
+        let ExpressionNode::ForExpression(for_expression) = &expression.expression else {
+            panic!("expected ForExpression, got {:?}", expression.expression);
+        };
+
+        test_expression(&for_expression.iterable, &"abc");
+    }
+
+    #[rstest]
+    #[case("for (x in xs) { x }\n[1, 2]", "[1, 2]")]
+    #[case("for (x in xs) { x }\n(1)", "1")]
+    #[case("for (x in xs) { x }; [1, 2]", "[1, 2]")]
+    fn test_for_statement_ends(#[case] input: &str, #[case] next: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        assert_eq!(errors, Vec::<ParseError>::new());
+        assert_eq!(program.statements.len(), 2);
+
+        let StatementNode::ExpressionStatement(expression) = &program.statements[0] else {
+            panic!(
+                "expected ExpressionStatement, got {:?}",
+                program.statements[0]
+            );
+        };
+        assert!(matches!(
+            expression.expression,
+            ExpressionNode::ForExpression(_)
+        ));
+        assert_eq!(program.statements[1].string(), next);
+    }
+
+    #[rstest]
+    #[case("for (x xs) { x }", "expected 'in', found identifier `xs` at 1:8")]
+    #[case("for (1 in xs) { x }", "expected identifier, found integer `1` at 1:6")]
+    #[case("for (a, b, c in xs) { x }", "expected 'in', found ',' at 1:10")]
+    fn test_for_expression_errors(#[case] input: &str, #[case] expected: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (_, errors) = parser.parse_program();
+
+        assert_eq!(errors.first().map(|e| e.to_string()), Some(expected.into()));
+    }
+}
@@ -1,16 +1,40 @@
-use crate::{parser::Parser, tokens::token::Token};
+use crate::{
+    parser::{parse_error::ParseErrorKind, precedence::Precedence, Parser},
+    tokens::{span::Span, token::Token},
+};
 
 use super::{
     block_statement::BlockStatement, identifier::Identifier, AstNode, ExpressionNode,
     ParsableResult, ParsePrefix,
 };
 
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub ident: Identifier,
+    // evaluated when a call leaves the parameter out
+    pub default: Option<ExpressionNode>,
+    // `...name`, collects the remaining arguments into an array
+    pub rest: bool,
+}
+
+impl Parameter {
+    pub fn string(&self) -> String {
+        match (&self.default, self.rest) {
+            (_, true) => format!("...{}", self.ident.string()),
+            (Some(default), _) => format!("{} = {}", self.ident.string(), default.string()),
+            (None, _) => self.ident.string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FunctionExpression {
     pub token: Token,
-    pub parameters: Vec<Identifier>,
+    // defaults can only follow other defaults and the rest parameter comes last
+    pub parameters: Vec<Parameter>,
     pub body: BlockStatement,
     pub name: Option<String>,
+    pub span: Span,
 }
 
 impl AstNode for FunctionExpression {
@@ -18,6 +42,10 @@ impl AstNode for FunctionExpression {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         format!(
             "fn ({}) {}",
@@ -32,28 +60,48 @@ impl AstNode for FunctionExpression {
 }
 
 impl FunctionExpression {
-    fn parse_parameters(parser: &mut Parser) -> ParsableResult<Vec<Identifier>> {
-        let mut idents = vec![];
+    fn parse_parameters(parser: &mut Parser) -> ParsableResult<Vec<Parameter>> {
+        let mut parameters: Vec<Parameter> = vec![];
 
         if parser.peek_token.is(&Token::RPAREN) {
             parser.next_token();
-            return Ok(idents);
+            return Ok(parameters);
         }
 
         parser.next_token();
 
         loop {
-            let node = Identifier::parse_prefix(parser)?;
-            let ExpressionNode::Identifier(ident) = node else {
-                return Err(format!(
-                    "Identifier::parse_prefix returned invalid type, got {:?}",
-                    node
-                ));
+            let rest = parser.current_token.is(&Token::ELLIPSIS);
+            if rest {
+                parser.next_token();
+            }
+
+            let ExpressionNode::Identifier(ident) = Identifier::parse_prefix(parser)? else {
+                return Err(parser.current_error(ParseErrorKind::ExpectedIdentifier));
+            };
+
+            let after_default = parameters.last().is_some_and(|p| p.default.is_some());
+            let default = match parser.peek_token {
+                Token::ASSIGN if !rest => {
+                    parser.next_token();
+                    parser.next_token();
+                    Some(parser.parse_expression(Precedence::LOWEST)?)
+                }
+                _ if after_default && !rest => {
+                    return Err(parser.peek_error(ParseErrorKind::UnexpectedToken {
+                        expected: Token::ASSIGN,
+                    }))
+                }
+                _ => None,
             };
 
-            idents.push(ident);
+            parameters.push(Parameter {
+                ident,
+                default,
+                rest,
+            });
 
-            if !parser.peek_token.is(&Token::COMMA) {
+            if rest || !parser.peek_token.is(&Token::COMMA) {
                 break;
             }
 
@@ -63,7 +111,7 @@ impl FunctionExpression {
 
         parser.expect_token(Token::RPAREN)?;
 
-        Ok(idents)
+        Ok(parameters)
     }
 }
 
@@ -72,19 +120,25 @@ impl ParsePrefix for FunctionExpression {
         parser: &mut crate::parser::Parser,
     ) -> super::ParsableResult<super::ExpressionNode> {
         let token = parser.current_token.clone();
+        let start = parser.current_span;
         parser.expect_token(Token::LPAREN)?;
 
         let parameters = FunctionExpression::parse_parameters(parser)?;
 
         parser.expect_token(Token::LBRACE)?;
 
-        let body = parser.parse_block()?;
+        // loops around the function body cannot be left from inside it
+        let loop_depth = std::mem::take(&mut parser.loop_depth);
+        let body = parser.parse_block();
+        parser.loop_depth = loop_depth;
+        let body = body?;
 
         Ok(ExpressionNode::FunctionExpression(FunctionExpression {
             token,
             parameters,
             body,
             name: None,
+            span: parser.span_from(start),
         }))
     }
 }
@@ -95,8 +149,10 @@ mod test {
     use rstest::rstest;
 
     use crate::{
-        ast::{infix_expression::test::test_infix_expression, ExpressionNode, StatementNode},
-        parser::Parser,
+        ast::{
+            infix_expression::test::test_infix_expression, AstNode, ExpressionNode, StatementNode,
+        },
+        parser::{parse_error::ParseError, Parser},
         tokens::token::Token,
     };
 
@@ -108,7 +164,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -130,7 +186,7 @@ mod test {
             fn_expression
                 .parameters
                 .iter()
-                .map(|p| &p.value)
+                .map(|p| &p.ident.value)
                 .collect::<Vec<_>>(),
             params
         );
@@ -152,7 +208,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -171,4 +227,32 @@ mod test {
 
         assert_eq!(fn_expression.name, Some("myFunction".into()))
     }
+
+    #[rstest]
+    #[case("fn(x, y = 10) { x }", "fn (x,y = 10) x")]
+    #[case("fn(x = 1, y = x * 2) { x }", "fn (x = 1,y = (x * 2)) x")]
+    #[case("fn(first, ...others) { first }", "fn (first,...others) first")]
+    #[case("fn(a, b = [], ...c) { a }", "fn (a,b = [],...c) a")]
+    #[case("fn(...all) { all }", "fn (...all) all")]
+    fn test_function_parameters(#[case] input: &str, #[case] expected: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+
+        assert_eq!(errors, Vec::<ParseError>::new());
+        assert_eq!(program.string(), expected);
+    }
+
+    #[rstest]
+    #[case("fn(x = 1, y) { x }", "expected '=', found ')' at 1:12")]
+    #[case("fn(...xs, y) { xs }", "expected ')', found ',' at 1:9")]
+    #[case("fn(...xs = 1) { xs }", "expected ')', found '=' at 1:10")]
+    #[case("fn(... ) { 1 }", "expected identifier, found ')' at 1:8")]
+    fn test_function_parameter_errors(#[case] input: &str, #[case] expected: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (_, errors) = parser.parse_program();
+
+        assert_eq!(errors.first().map(|e| e.to_string()), Some(expected.into()));
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
     parser::{precedence::Precedence, Parser},
-    tokens::token::Token,
+    tokens::{span::Span, token::Token},
 };
 
 use super::{AstNode, ExpressionNode, ParsableResult, ParsePrefix};
@@ -9,6 +9,7 @@ use super::{AstNode, ExpressionNode, ParsableResult, ParsePrefix};
 pub struct HashLiteral {
     pub token: Token,
     pub map: Vec<(ExpressionNode, ExpressionNode)>,
+    pub span: Span,
 }
 
 impl AstNode for HashLiteral {
@@ -16,6 +17,10 @@ impl AstNode for HashLiteral {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         let pairs = self
             .map
@@ -30,6 +35,7 @@ impl AstNode for HashLiteral {
 impl ParsePrefix for HashLiteral {
     fn parse_prefix(parser: &mut Parser) -> ParsableResult<ExpressionNode> {
         let token = parser.current_token.clone();
+        let start = parser.current_span;
         let mut v = vec![];
 
         while !parser.peek_token.is(&Token::RBRACE) {
@@ -51,7 +57,11 @@ impl ParsePrefix for HashLiteral {
 
         parser.expect_token(Token::RBRACE)?;
 
-        Ok(ExpressionNode::HashLiteral(HashLiteral { token, map: v }))
+        Ok(ExpressionNode::HashLiteral(HashLiteral {
+            token,
+            map: v,
+            span: parser.span_from(start),
+        }))
     }
 }
 
@@ -62,7 +72,7 @@ mod test {
             infix_expression::test::test_infix_expression, test::test_expression, ExpressionNode,
             StatementNode,
         },
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
         tokens::token::Token,
     };
 
@@ -72,7 +82,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -114,7 +124,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -152,7 +162,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -190,7 +200,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -218,7 +228,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -1,4 +1,7 @@
-use crate::tokens::token::Token;
+use crate::{
+    parser::parse_error::ParseErrorKind,
+    tokens::{span::Span, token::Token},
+};
 
 use super::{AstNode, ExpressionNode, ParsePrefix};
 
@@ -6,6 +9,7 @@ use super::{AstNode, ExpressionNode, ParsePrefix};
 pub struct Identifier {
     pub token: Token,
     pub value: String,
+    pub span: Span,
 }
 
 impl AstNode for Identifier {
@@ -13,6 +17,10 @@ impl AstNode for Identifier {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         self.value.clone()
     }
@@ -23,12 +31,13 @@ impl ParsePrefix for Identifier {
         parser: &mut crate::parser::Parser,
     ) -> super::ParsableResult<super::ExpressionNode> {
         let Token::IDENT(ident) = parser.current_token.clone() else {
-            return Err(format!("Invalid token {:?}", parser.current_token));
+            return Err(parser.current_error(ParseErrorKind::ExpectedIdentifier));
         };
 
         Ok(ExpressionNode::Identifier(Identifier {
             token: Token::IDENT(ident.clone()),
             value: ident,
+            span: parser.current_span,
         }))
     }
 }
@@ -39,7 +48,7 @@ mod test {
 
     use crate::{
         ast::{ExpressionNode, StatementNode},
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
     };
 
     #[test]
@@ -48,7 +57,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
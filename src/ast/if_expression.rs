@@ -1,6 +1,12 @@
-use crate::{parser::precedence::Precedence, tokens::token::Token};
+use crate::{
+    parser::precedence::Precedence,
+    tokens::{span::Span, token::Token},
+};
 
-use super::{block_statement::BlockStatement, AstNode, ExpressionNode, ParsePrefix};
+use super::{
+    block_statement::BlockStatement, expression_statement::ExpressionStatement, AstNode,
+    ExpressionNode, ParsePrefix, StatementNode,
+};
 
 #[derive(Debug, Clone)]
 pub struct IfExpression {
@@ -8,6 +14,7 @@ pub struct IfExpression {
     pub condition: Box<ExpressionNode>,
     pub concequence: BlockStatement,
     pub alternative: Option<BlockStatement>,
+    pub span: Span,
 }
 
 impl AstNode for IfExpression {
@@ -15,6 +22,10 @@ impl AstNode for IfExpression {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         let else_s = match &self.alternative {
             Some(statement) => format!("else {}", statement.string()),
@@ -32,6 +43,7 @@ impl AstNode for IfExpression {
 impl ParsePrefix for IfExpression {
     fn parse_prefix(parser: &mut crate::parser::Parser) -> super::ParsableResult<ExpressionNode> {
         let token = parser.current_token.clone();
+        let start = parser.current_span;
 
         parser.expect_token(Token::LPAREN)?;
 
@@ -45,22 +57,39 @@ impl ParsePrefix for IfExpression {
 
         let concequence = parser.parse_block()?;
 
-        let alternative = match parser.peek_token.is(&Token::ELSE) {
-            true => {
+        let mut alternative = None;
+        if parser.peek_token.is(&Token::ELSE) {
+            parser.next_token();
+
+            if parser.peek_token.is(&Token::IF) {
                 parser.next_token();
 
+                // `else if` is an else block holding just the nested if
+                let token = parser.current_token.clone();
+                let nested = IfExpression::parse_prefix(parser)?;
+
+                alternative = Some(BlockStatement {
+                    token: token.clone(),
+                    span: nested.span(),
+                    statements: vec![StatementNode::ExpressionStatement(ExpressionStatement {
+                        token,
+                        span: nested.span(),
+                        expression: nested,
+                    })],
+                });
+            } else {
                 parser.expect_token(Token::LBRACE)?;
 
-                Some(parser.parse_block()?)
+                alternative = Some(parser.parse_block()?);
             }
-            false => None,
-        };
+        }
 
         Ok(ExpressionNode::IfExpression(IfExpression {
             token,
             condition: Box::new(condition),
             concequence,
             alternative,
+            span: parser.span_from(start),
         }))
     }
 }
@@ -71,10 +100,10 @@ mod test {
 
     use crate::{
         ast::{
-            infix_expression::test::test_infix_expression, test::test_expression, ExpressionNode,
-            StatementNode,
+            infix_expression::test::test_infix_expression, test::test_expression, AstNode,
+            ExpressionNode, StatementNode,
         },
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
         tokens::token::Token,
     };
 
@@ -84,7 +113,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -115,7 +144,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -149,4 +178,15 @@ mod test {
 
         test_expression(&expression.expression, &"y");
     }
+
+    #[test]
+    fn test_else_if_statement() {
+        let input = "if (x) { 1 } else if (y) { 2 } else { 3 }";
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        assert_eq!(errors, Vec::<ParseError>::new());
+
+        assert_eq!(program.string(), "if x 1 else if y 2 else 3");
+    }
 }
@@ -1,15 +1,16 @@
 use crate::{
     parser::{precedence::Precedence, Parser},
-    tokens::token::Token,
+    tokens::{span::Span, token::Token},
 };
 
-use super::{AstNode, ExpressionNode, ParseInfix};
+use super::{slice_expression::SliceExpression, AstNode, ExpressionNode, ParseInfix};
 
 #[derive(Debug, Clone)]
 pub struct IndexExpression {
     pub token: Token,
     pub left: Box<ExpressionNode>,
     pub right: Box<ExpressionNode>,
+    pub span: Span,
 }
 
 impl AstNode for IndexExpression {
@@ -17,6 +18,10 @@ impl AstNode for IndexExpression {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         format!("({}[{}])", self.left.string(), self.right.string())
     }
@@ -28,16 +33,27 @@ impl ParseInfix for IndexExpression {
         left: ExpressionNode,
     ) -> super::ParsableResult<ExpressionNode> {
         let token = parser.current_token.clone();
+        let start = left.span();
         parser.next_token();
 
+        if parser.current_token.is(&Token::COLON) {
+            return SliceExpression::parse_slice(parser, token, left, None);
+        }
+
         let index = parser.parse_expression(Precedence::LOWEST)?;
 
+        if parser.peek_token.is(&Token::COLON) {
+            parser.next_token();
+            return SliceExpression::parse_slice(parser, token, left, Some(index));
+        }
+
         parser.expect_token(Token::RBRACKET)?;
 
         Ok(ExpressionNode::IndexExpresssion(IndexExpression {
             token,
             left: Box::new(left),
             right: Box::new(index),
+            span: parser.span_from(start),
         }))
     }
 }
@@ -49,7 +65,7 @@ mod test {
             infix_expression::test::test_infix_expression, test::test_expression, ExpressionNode,
             StatementNode,
         },
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
         tokens::token::Token,
     };
 
@@ -59,7 +75,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -1,6 +1,6 @@
 use crate::{
     parser::{precedence::IntoPrecedence, Parser},
-    tokens::token::Token,
+    tokens::{span::Span, token::Token},
 };
 
 use super::{AstNode, ExpressionNode, ParsableResult, ParseInfix};
@@ -11,6 +11,7 @@ pub struct InfixExpression {
     pub left: Box<ExpressionNode>,
     pub operator: Token,
     pub right: Box<ExpressionNode>,
+    pub span: Span,
 }
 
 impl AstNode for InfixExpression {
@@ -18,18 +19,31 @@ impl AstNode for InfixExpression {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         let operator = match self.operator {
             Token::PLUS => "+",
             Token::MINUS => "-",
             Token::ASTERISK => "*",
             Token::SLASH => "/",
+            Token::PERCENT => "%",
 
             Token::LT => "<",
             Token::GT => ">",
+            Token::LT_EQ => "<=",
+            Token::GT_EQ => ">=",
 
             Token::EQ => "==",
             Token::NOT_EQ => "!=",
+
+            Token::AND => "&&",
+            Token::OR => "||",
+
+            Token::DOT_DOT => "..",
+            Token::DOT_DOT_EQ => "..=",
             _ => panic!("Invalid operator on token, got {:?}", self.operator),
         };
 
@@ -46,6 +60,7 @@ impl ParseInfix for InfixExpression {
     fn parse_infix(parser: &mut Parser, left: ExpressionNode) -> ParsableResult<ExpressionNode> {
         let token = parser.current_token.clone();
         let operator = parser.current_token.clone();
+        let start = left.span();
 
         parser.next_token();
 
@@ -56,6 +71,7 @@ impl ParseInfix for InfixExpression {
             left: left.into(),
             operator,
             right: right.into(),
+            span: parser.span_from(start),
         }))
     }
 }
@@ -68,7 +84,7 @@ pub mod test {
 
     use crate::{
         ast::{test::test_expression, AstNode, ExpressionNode, StatementNode},
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
         tokens::token::Token,
     };
 
@@ -89,6 +105,13 @@ pub mod test {
     #[case("foobar < barfoo;", "foobar", Token::LT, "barfoo")]
     #[case("foobar == barfoo;", "foobar", Token::EQ, "barfoo")]
     #[case("foobar != barfoo;", "foobar", Token::NOT_EQ, "barfoo")]
+    #[case("foobar % barfoo;", "foobar", Token::PERCENT, "barfoo")]
+    #[case("foobar <= barfoo;", "foobar", Token::LT_EQ, "barfoo")]
+    #[case("foobar >= barfoo;", "foobar", Token::GT_EQ, "barfoo")]
+    #[case("foobar && barfoo;", "foobar", Token::AND, "barfoo")]
+    #[case("foobar || barfoo;", "foobar", Token::OR, "barfoo")]
+    #[case("foobar..barfoo;", "foobar", Token::DOT_DOT, "barfoo")]
+    #[case("foobar..=barfoo;", "foobar", Token::DOT_DOT_EQ, "barfoo")]
     #[case("true == true", true, Token::EQ, true)]
     #[case("true != false", true, Token::NOT_EQ, false)]
     #[case("false == false", false, Token::EQ, false)]
@@ -103,7 +126,7 @@ pub mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -158,6 +181,15 @@ pub mod test {
     #[case("2 / (5 + 5)", "(2 / (5 + 5))")]
     #[case("-(5 + 5)", "(-(5 + 5))")]
     #[case("!(true == true)", "(!(true == true))")]
+    #[case("a + b % c * d", "(a + ((b % c) * d))")]
+    #[case("a <= b == c >= d", "((a <= b) == (c >= d))")]
+    #[case("a || b && c", "(a || (b && c))")]
+    #[case("a && b || c && d", "((a && b) || (c && d))")]
+    #[case("a == b && !c || d < e", "(((a == b) && (!c)) || (d < e))")]
+    #[case("0..n + 1", "(0 .. (n + 1))")]
+    #[case("a..=b * 2 == c", "(a ..= ((b * 2) == c))")]
+    #[case("a || b..c", "((a || b) .. c)")]
+    #[case("x = 0..len(a)", "(x = (0 .. len(a)))")]
     #[case("a * [1, 2, 3, 4][b * c] * d", "((a * ([1, 2, 3, 4][(b * c)])) * d)")]
     #[case(
         "add(a * b[2], b[1], 2 * [1, 2][1])",
@@ -167,7 +199,7 @@ pub mod test {
         let mut parser = Parser::new(input.into());
         let (program, errors) = parser.parse_program();
 
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
 
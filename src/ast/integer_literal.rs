@@ -1,4 +1,7 @@
-use crate::tokens::token::Token;
+use crate::{
+    parser::parse_error::ParseErrorKind,
+    tokens::{span::Span, token::Token},
+};
 
 use super::{AstNode, ParsePrefix};
 
@@ -6,6 +9,7 @@ use super::{AstNode, ParsePrefix};
 pub struct IntegerLiteral {
     pub token: Token,
     pub value: i64,
+    pub span: Span,
 }
 
 impl AstNode for IntegerLiteral {
@@ -13,6 +17,10 @@ impl AstNode for IntegerLiteral {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         format!("{}", self.value)
     }
@@ -23,12 +31,13 @@ impl ParsePrefix for IntegerLiteral {
         parser: &mut crate::parser::Parser,
     ) -> super::ParsableResult<super::ExpressionNode> {
         let Token::INT(value) = parser.current_token.clone() else {
-            return Err(format!("Invalid token {:?}", parser.current_token));
+            return Err(parser.current_error(ParseErrorKind::ExpectedExpression));
         };
 
         Ok(super::ExpressionNode::IntegerLiteral(IntegerLiteral {
             token: Token::INT(value),
             value,
+            span: parser.current_span,
         }))
     }
 }
@@ -39,7 +48,7 @@ pub mod test {
 
     use crate::{
         ast::{ExpressionNode, StatementNode},
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
     };
 
     pub fn assert_integer_literal(expression: &ExpressionNode, value: i64) {
@@ -59,7 +68,7 @@ pub mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -1,14 +1,24 @@
-use crate::{parser::precedence::Precedence, tokens::token::Token};
+use crate::{
+    parser::{
+        parse_error::{ParseError, ParseErrorKind},
+        precedence::Precedence,
+    },
+    tokens::{span::Span, token::Token},
+};
 
 use super::{
-    identifier::Identifier, AstNode, ExpressionNode, ParsableResult, ParseStatement, StatementNode,
+    identifier::Identifier, pattern::Pattern, AstNode, ExpressionNode, ParsableResult,
+    ParseStatement, StatementNode,
 };
 
 #[derive(Debug, Clone)]
 pub struct LetStatement {
     pub token: Token,
-    pub identifier: Identifier,
+    // a plain identifier or an array or hash pattern to destructure the value
+    pub pattern: Pattern,
     pub value: ExpressionNode,
+    pub span: Span,
+    pub doc: Option<String>,
 }
 
 impl AstNode for LetStatement {
@@ -16,25 +26,47 @@ impl AstNode for LetStatement {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
-        format!(
-            "let {} = {};",
-            self.identifier.string(),
-            self.value.string()
-        )
+        format!("let {} = {};", self.pattern.string(), self.value.string())
     }
 }
 
 impl ParseStatement for LetStatement {
     fn parse(parser: &mut crate::parser::Parser) -> ParsableResult<StatementNode> {
         let token = parser.current_token.clone();
-        let Token::IDENT(ident) = parser.peek_token.clone() else {
-            return Err(format!(
-                "invalid token, expected 'Token::IDENT' got '{:?}'",
-                parser.peek_token
-            ));
+        let start = parser.current_span;
+        let doc = parser.current_doc.clone();
+
+        let pattern = match parser.peek_token.clone() {
+            Token::IDENT(ident) => {
+                parser.next_token();
+                Pattern::Binding(Identifier {
+                    token: Token::IDENT(ident.clone()),
+                    value: ident,
+                    span: parser.current_span,
+                })
+            }
+            Token::LBRACKET | Token::LBRACE => {
+                parser.next_token();
+                let pattern = Pattern::parse(parser)?;
+
+                // a let has nothing to fall back to, so it only binds
+                if let Some(literal) = first_literal(&pattern) {
+                    return Err(ParseError::new(
+                        ParseErrorKind::ExpectedIdentifier,
+                        literal.token().clone(),
+                        literal.span(),
+                    ));
+                }
+
+                pattern
+            }
+            _ => return Err(parser.peek_error(ParseErrorKind::ExpectedIdentifier)),
         };
-        parser.next_token();
 
         parser.expect_token(Token::ASSIGN)?;
 
@@ -42,8 +74,10 @@ impl ParseStatement for LetStatement {
 
         let mut expression = parser.parse_expression(Precedence::LOWEST)?;
 
-        if let ExpressionNode::FunctionExpression(ref mut fn_expression) = expression {
-            fn_expression.name = Some(ident.clone());
+        if let (ExpressionNode::FunctionExpression(fn_expression), Pattern::Binding(ident)) =
+            (&mut expression, &pattern)
+        {
+            fn_expression.name = Some(ident.value.clone());
         }
 
         if parser.peek_token.is(&Token::SEMICOLON) {
@@ -52,15 +86,23 @@ impl ParseStatement for LetStatement {
 
         Ok(StatementNode::LetStatement(LetStatement {
             token,
-            identifier: Identifier {
-                token: Token::IDENT(ident.clone()),
-                value: ident,
-            },
+            pattern,
             value: expression,
+            span: parser.span_from(start),
+            doc,
         }))
     }
 }
 
+fn first_literal(pattern: &Pattern) -> Option<&ExpressionNode> {
+    match pattern {
+        Pattern::Literal(literal) => Some(literal),
+        Pattern::Wildcard(_) | Pattern::Binding(_) => None,
+        Pattern::Array(elements, _, _) => elements.iter().find_map(first_literal),
+        Pattern::Hash(entries, _) => entries.iter().find_map(|(_, p)| first_literal(p)),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::any::Any;
@@ -68,8 +110,8 @@ mod test {
     use rstest::rstest;
 
     use crate::{
-        ast::{test::test_expression, AstNode, StatementNode},
-        parser::Parser,
+        ast::{pattern::Pattern, test::test_expression, AstNode, StatementNode},
+        parser::{parse_error::ParseError, Parser},
         tokens::token::Token,
     };
 
@@ -79,12 +121,16 @@ mod test {
         };
 
         assert_eq!(statement.token(), &Token::LET);
-        assert_eq!(statement.identifier.value, name);
 
-        let Token::IDENT(literal) = statement.identifier.token() else {
+        let Pattern::Binding(identifier) = &statement.pattern else {
+            panic!("expected Pattern::Binding, got {:?}", statement.pattern);
+        };
+        assert_eq!(identifier.value, name);
+
+        let Token::IDENT(literal) = identifier.token() else {
             panic!(
                 "expected Token::IDENT in statement.identier, got {:?}",
-                statement.identifier.token(),
+                identifier.token(),
             );
         };
         assert_eq!(literal, &name);
@@ -104,7 +150,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -112,4 +158,82 @@ mod test {
         let mut nodes = program.statements.into_iter();
         assert_let(nodes.next().unwrap(), name, &value);
     }
+
+    #[test]
+    fn test_let_doc_comment() {
+        let input = "/// The answer.\nlet x = 42; // not a doc\nlet y = x;";
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        let empty: Vec<ParseError> = vec![];
+
+        assert_eq!(errors, empty);
+
+        let docs: Vec<_> = program
+            .statements
+            .into_iter()
+            .map(|node| match node {
+                StatementNode::LetStatement(statement) => statement.doc,
+                node => panic!("invalid node, expected 'let' got {:?}", node),
+            })
+            .collect();
+        assert_eq!(docs, vec![Some("The answer.".to_string()), None]);
+    }
+
+    #[rstest]
+    #[case("let [a, b] = xs;", "let [a, b] = xs;")]
+    #[case("let [first, ...rest] = xs", "let [first, ...rest] = xs;")]
+    #[case("let [_, [x, y], ..._] = xs;", "let [_, [x, y], ..._] = xs;")]
+    #[case("let [...all] = xs;", "let [...all] = xs;")]
+    #[case(
+        "let {\"name\": n, \"age\": a} = person;",
+        "let {name:n, age:a} = person;"
+    )]
+    #[case("let {1: [a, ...b]} = h;", "let {1:[a, ...b]} = h;")]
+    #[case("let _ = 1;", "let _ = 1;")]
+    fn test_let_destructuring(#[case] input: &str, #[case] expected: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+
+        assert_eq!(errors, Vec::<ParseError>::new());
+        assert_eq!(program.string(), expected);
+    }
+
+    #[test]
+    fn test_let_pattern_bindings() {
+        let mut parser = Parser::new("let [a, {\"k\": b}, ...c] = xs;".into());
+
+        let (program, errors) = parser.parse_program();
+        assert_eq!(errors, Vec::<ParseError>::new());
+
+        let StatementNode::LetStatement(statement) = &program.statements[0] else {
+            panic!("expected LetStatement, got {:?}", program.statements[0]);
+        };
+
+        let bindings = statement
+            .pattern
+            .bindings()
+            .iter()
+            .map(|b| b.value.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(bindings, vec!["a", "b", "c"]);
+    }
+
+    #[rstest]
+    #[case("let [a, 1] = xs;", "expected identifier, found integer `1` at 1:9")]
+    #[case(
+        "let {\"k\": \"v\"} = h;",
+        "expected identifier, found string \"v\" at 1:11"
+    )]
+    #[case("let [...rest, a] = xs;", "expected ']', found ',' at 1:13")]
+    #[case("let [...[a]] = xs;", "expected identifier, found '[' at 1:9")]
+    #[case("let [a b] = xs;", "expected ',', found identifier `b` at 1:8")]
+    fn test_let_destructuring_errors(#[case] input: &str, #[case] expected: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (_, errors) = parser.parse_program();
+
+        assert_eq!(errors.first().map(|e| e.to_string()), Some(expected.into()));
+    }
 }
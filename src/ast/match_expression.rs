@@ -0,0 +1,221 @@
+use crate::{
+    parser::{precedence::Precedence, Parser},
+    tokens::{span::Span, token::Token},
+};
+
+use super::{
+    block_statement::BlockStatement, expression_statement::ExpressionStatement, pattern::Pattern,
+    AstNode, ExpressionNode, ParsableResult, ParsePrefix, StatementNode,
+};
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: BlockStatement,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchExpression {
+    pub token: Token,
+    pub subject: Box<ExpressionNode>,
+    pub arms: Vec<MatchArm>,
+    pub span: Span,
+}
+
+impl AstNode for MatchExpression {
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn string(&self) -> String {
+        format!(
+            "match {} {{ {} }}",
+            self.subject.string(),
+            self.arms
+                .iter()
+                .map(|arm| format!("{} => {}", arm.pattern.string(), arm.body.string()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl MatchExpression {
+    // an arm is either a block or a single expression followed by a comma,
+    // unless it is the last one
+    fn parse_arm(parser: &mut Parser) -> ParsableResult<MatchArm> {
+        let pattern = Pattern::parse(parser)?;
+
+        parser.expect_token(Token::FAT_ARROW)?;
+
+        if parser.peek_token.is(&Token::LBRACE) {
+            parser.next_token();
+            let body = parser.parse_block()?;
+
+            if parser.peek_token.is(&Token::COMMA) {
+                parser.next_token();
+            }
+
+            return Ok(MatchArm { pattern, body });
+        }
+
+        parser.next_token();
+
+        let token = parser.current_token.clone();
+        let expression = parser.parse_expression(Precedence::LOWEST)?;
+
+        if !parser.peek_token.is(&Token::RBRACE) {
+            parser.expect_token(Token::COMMA)?;
+        }
+
+        let span = expression.span();
+        Ok(MatchArm {
+            pattern,
+            body: BlockStatement {
+                token: token.clone(),
+                statements: vec![StatementNode::ExpressionStatement(ExpressionStatement {
+                    token,
+                    expression,
+                    span,
+                })],
+                span,
+            },
+        })
+    }
+}
+
+impl ParsePrefix for MatchExpression {
+    fn parse_prefix(parser: &mut Parser) -> ParsableResult<ExpressionNode> {
+        let token = parser.current_token.clone();
+        let start = parser.current_span;
+
+        parser.expect_token(Token::LPAREN)?;
+        parser.next_token();
+
+        let subject = parser.parse_expression(Precedence::LOWEST)?;
+
+        parser.expect_token(Token::RPAREN)?;
+        parser.expect_token(Token::LBRACE)?;
+
+        let mut arms = vec![];
+        while !parser.peek_token.is(&Token::RBRACE) {
+            parser.next_token();
+            arms.push(MatchExpression::parse_arm(parser)?);
+        }
+
+        parser.expect_token(Token::RBRACE)?;
+
+        Ok(ExpressionNode::MatchExpression(MatchExpression {
+            token,
+            subject: Box::new(subject),
+            arms,
+            span: parser.span_from(start),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::{
+        ast::{pattern::Pattern, AstNode, ExpressionNode, StatementNode},
+        parser::{parse_error::ParseError, Parser},
+    };
+
+    #[rstest]
+    #[case(
+        "match (x) { 1 => \"one\", _ => \"many\" }",
+        "match x { 1 => one, _ => many }"
+    )]
+    #[case(
+        "match (x) { -1 => 0, 2.5 => 1, true => 2 }",
+        "match x { (-1) => 0, 2.5 => 1, true => 2 }"
+    )]
+    #[case("match (x) { n => { n + 1 } }", "match x { n => (n + 1) }")]
+    #[case(
+        "match (x) { [a, _] => a, [] => 0, }",
+        "match x { [a, _] => a, [] => 0 }"
+    )]
+    #[case(
+        "match (p) { {\"x\": 0, \"y\": y} => y, { 1: [a] } => a }",
+        "match p { {x:0, y:y} => y, {1:[a]} => a }"
+    )]
+    #[case("match (x) { }", "match x {  }")]
+    fn test_match_expression(#[case] input: &str, #[case] expected: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        let empty: Vec<ParseError> = vec![];
+
+        assert_eq!(errors, empty);
+        assert_eq!(program.statements.len(), 1);
+        assert_eq!(program.string(), expected);
+    }
+
+    #[test]
+    fn test_match_patterns() {
+        let input = "match ([1, 2]) { [first, [_, {\"k\": v}]] => first, x => x }";
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        assert_eq!(errors, Vec::<ParseError>::new());
+
+        let StatementNode::ExpressionStatement(expression) = &program.statements[0] else {
+            panic!(
+                "expected ExpressionStatement, got {:?}",
+                program.statements[0]
+            );
+        };
+        let ExpressionNode::MatchExpression(match_expression) = &expression.expression else {
+            panic!("expected MatchExpression, got {:?}", expression.expression);
+        };
+
+        assert_eq!(match_expression.arms.len(), 2);
+
+        let Pattern::Array(elements, _, _) = &match_expression.arms[0].pattern else {
+            panic!(
+                "expected Pattern::Array, got {:?}",
+                match_expression.arms[0].pattern
+            );
+        };
+        assert_eq!(elements.len(), 2);
+
+        let bindings = match_expression.arms[0]
+            .pattern
+            .bindings()
+            .iter()
+            .map(|b| b.value.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(bindings, vec!["first", "v"]);
+
+        assert!(matches!(
+            match_expression.arms[1].pattern,
+            Pattern::Binding(_)
+        ));
+    }
+
+    #[rstest]
+    #[case("match (x) { 1 + 1 => 2 }", "expected '=>', found '+' at 1:15")]
+    #[case(
+        "match (x) { 1 => 2 3 => 4 }",
+        "expected ',', found integer `3` at 1:20"
+    )]
+    #[case("match (x) { f(1) => 2 }", "expected '=>', found '(' at 1:14")]
+    #[case("match (x) { -y => 2 }", "expected pattern, found '-' at 1:13")]
+    #[case(
+        "match (x) { {k: 1} => 2 }",
+        "expected pattern, found identifier `k` at 1:14"
+    )]
+    fn test_match_errors(#[case] input: &str, #[case] expected: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (_, errors) = parser.parse_program();
+
+        assert_eq!(errors.first().map(|e| e.to_string()), Some(expected.into()));
+    }
+}
@@ -1,20 +1,33 @@
-use crate::{parser::Parser, tokens::token::Token};
+use crate::{
+    parser::{parse_error::ParseError, Parser},
+    tokens::{span::Span, token::Token},
+};
 
 use self::{
-    array_literal::ArrayLiteral, block_statement::BlockStatement, boolean_literal::BooleanLiteral,
-    call_expression::CallExpression, expression_statement::ExpressionStatement,
+    array_literal::ArrayLiteral, assign_expression::AssignExpression,
+    block_statement::BlockStatement, boolean_literal::BooleanLiteral,
+    break_statement::BreakStatement, call_expression::CallExpression,
+    continue_statement::ContinueStatement, expression_statement::ExpressionStatement,
+    float_literal::FloatLiteral, for_expression::ForExpression,
     function_expression::FunctionExpression, hash_literal::HashLiteral, identifier::Identifier,
     if_expression::IfExpression, index_expression::IndexExpression,
     infix_expression::InfixExpression, integer_literal::IntegerLiteral,
-    let_statement::LetStatement, prefix_expression::PrefixExpression, program::Program,
-    return_statement::ReturnStatement, string_literal::StringLiteral,
+    let_statement::LetStatement, match_expression::MatchExpression,
+    prefix_expression::PrefixExpression, program::Program, return_statement::ReturnStatement,
+    slice_expression::SliceExpression, string_literal::StringLiteral,
+    while_statement::WhileStatement,
 };
 
 pub mod array_literal;
+pub mod assign_expression;
 pub mod block_statement;
 pub mod boolean_literal;
+pub mod break_statement;
 pub mod call_expression;
+pub mod continue_statement;
 pub mod expression_statement;
+pub mod float_literal;
+pub mod for_expression;
 pub mod function_expression;
 pub mod grouped_expression;
 pub mod hash_literal;
@@ -24,30 +37,40 @@ pub mod index_expression;
 pub mod infix_expression;
 pub mod integer_literal;
 pub mod let_statement;
+pub mod match_expression;
+pub mod pattern;
 pub mod prefix_expression;
 pub mod program;
 pub mod return_statement;
+pub mod slice_expression;
 pub mod string_literal;
+pub mod while_statement;
 
 pub trait AstNode {
     fn token(&self) -> &Token;
     fn string(&self) -> String;
+    fn span(&self) -> Span;
 }
 
 #[derive(Debug, Clone)]
 pub enum ExpressionNode {
     Identifier(Identifier),
     IntegerLiteral(IntegerLiteral),
+    FloatLiteral(FloatLiteral),
     BooleanLiteral(BooleanLiteral),
     StringLiteral(StringLiteral),
     ArrayLiteral(ArrayLiteral),
     PrefixExpression(PrefixExpression),
     InfixExpression(InfixExpression),
     IfExpression(IfExpression),
+    MatchExpression(MatchExpression),
+    ForExpression(ForExpression),
     FunctionExpression(FunctionExpression),
     CallExpression(CallExpression),
     IndexExpresssion(IndexExpression),
+    SliceExpression(SliceExpression),
     HashLiteral(HashLiteral),
+    AssignExpression(AssignExpression),
 }
 
 #[derive(Debug, Clone)]
@@ -56,6 +79,9 @@ pub enum StatementNode {
     ReturnStatement(ReturnStatement),
     BlockStatement(BlockStatement),
     ExpressionStatement(ExpressionStatement),
+    WhileStatement(WhileStatement),
+    BreakStatement(BreakStatement),
+    ContinueStatement(ContinueStatement),
 }
 
 #[derive(Debug, Clone)]
@@ -83,7 +109,7 @@ impl<'a> From<&'a Program> for Node<'a> {
     }
 }
 
-pub type ParsableResult<T> = Result<T, String>;
+pub type ParsableResult<T> = Result<T, ParseError>;
 
 pub trait ParseStatement {
     fn parse(parser: &mut Parser) -> ParsableResult<StatementNode>;
@@ -105,6 +131,9 @@ impl AstNode for StatementNode {
             StatementNode::ReturnStatement(i) => i.token(),
             StatementNode::ExpressionStatement(i) => i.token(),
             StatementNode::BlockStatement(i) => i.token(),
+            StatementNode::WhileStatement(i) => i.token(),
+            StatementNode::BreakStatement(i) => i.token(),
+            StatementNode::ContinueStatement(i) => i.token(),
         }
     }
 
@@ -114,6 +143,21 @@ impl AstNode for StatementNode {
             StatementNode::ReturnStatement(i) => i.string(),
             StatementNode::ExpressionStatement(i) => i.string(),
             StatementNode::BlockStatement(i) => i.string(),
+            StatementNode::WhileStatement(i) => i.string(),
+            StatementNode::BreakStatement(i) => i.string(),
+            StatementNode::ContinueStatement(i) => i.string(),
+        }
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            StatementNode::LetStatement(i) => i.span(),
+            StatementNode::ReturnStatement(i) => i.span(),
+            StatementNode::ExpressionStatement(i) => i.span(),
+            StatementNode::BlockStatement(i) => i.span(),
+            StatementNode::WhileStatement(i) => i.span(),
+            StatementNode::BreakStatement(i) => i.span(),
+            StatementNode::ContinueStatement(i) => i.span(),
         }
     }
 }
@@ -123,16 +167,21 @@ impl AstNode for ExpressionNode {
         match self {
             ExpressionNode::Identifier(i) => i.token(),
             ExpressionNode::IntegerLiteral(i) => i.token(),
+            ExpressionNode::FloatLiteral(i) => i.token(),
             ExpressionNode::PrefixExpression(i) => i.token(),
             ExpressionNode::InfixExpression(i) => i.token(),
             ExpressionNode::BooleanLiteral(i) => i.token(),
             ExpressionNode::IfExpression(i) => i.token(),
+            ExpressionNode::MatchExpression(i) => i.token(),
+            ExpressionNode::ForExpression(i) => i.token(),
             ExpressionNode::FunctionExpression(i) => i.token(),
             ExpressionNode::CallExpression(i) => i.token(),
             ExpressionNode::StringLiteral(i) => i.token(),
             ExpressionNode::ArrayLiteral(i) => i.token(),
             ExpressionNode::IndexExpresssion(i) => i.token(),
+            ExpressionNode::SliceExpression(i) => i.token(),
             ExpressionNode::HashLiteral(i) => i.token(),
+            ExpressionNode::AssignExpression(i) => i.token(),
         }
     }
 
@@ -140,16 +189,43 @@ impl AstNode for ExpressionNode {
         match self {
             ExpressionNode::Identifier(i) => i.string(),
             ExpressionNode::IntegerLiteral(i) => i.string(),
+            ExpressionNode::FloatLiteral(i) => i.string(),
             ExpressionNode::PrefixExpression(i) => i.string(),
             ExpressionNode::InfixExpression(i) => i.string(),
             ExpressionNode::BooleanLiteral(i) => i.string(),
             ExpressionNode::IfExpression(i) => i.string(),
+            ExpressionNode::MatchExpression(i) => i.string(),
+            ExpressionNode::ForExpression(i) => i.string(),
             ExpressionNode::FunctionExpression(i) => i.string(),
             ExpressionNode::CallExpression(i) => i.string(),
             ExpressionNode::StringLiteral(i) => i.string(),
             ExpressionNode::ArrayLiteral(i) => i.string(),
             ExpressionNode::IndexExpresssion(i) => i.string(),
+            ExpressionNode::SliceExpression(i) => i.string(),
             ExpressionNode::HashLiteral(i) => i.string(),
+            ExpressionNode::AssignExpression(i) => i.string(),
+        }
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            ExpressionNode::Identifier(i) => i.span(),
+            ExpressionNode::IntegerLiteral(i) => i.span(),
+            ExpressionNode::FloatLiteral(i) => i.span(),
+            ExpressionNode::PrefixExpression(i) => i.span(),
+            ExpressionNode::InfixExpression(i) => i.span(),
+            ExpressionNode::BooleanLiteral(i) => i.span(),
+            ExpressionNode::IfExpression(i) => i.span(),
+            ExpressionNode::MatchExpression(i) => i.span(),
+            ExpressionNode::ForExpression(i) => i.span(),
+            ExpressionNode::FunctionExpression(i) => i.span(),
+            ExpressionNode::CallExpression(i) => i.span(),
+            ExpressionNode::StringLiteral(i) => i.span(),
+            ExpressionNode::ArrayLiteral(i) => i.span(),
+            ExpressionNode::IndexExpresssion(i) => i.span(),
+            ExpressionNode::SliceExpression(i) => i.span(),
+            ExpressionNode::HashLiteral(i) => i.span(),
+            ExpressionNode::AssignExpression(i) => i.span(),
         }
     }
 }
@@ -0,0 +1,150 @@
+use crate::{
+    parser::{parse_error::ParseErrorKind, Parser},
+    tokens::{span::Span, token::Token},
+};
+
+use super::{identifier::Identifier, AstNode, ExpressionNode, ParsableResult};
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    // `_`, matches anything without binding it
+    Wildcard(Span),
+    Binding(Identifier),
+    // integer, float, string and boolean literals, numbers may be negated
+    Literal(ExpressionNode),
+    // matches arrays of exactly this length, or at least this length when a
+    // trailing `...rest` binding or wildcard collects the remaining elements
+    Array(Vec<Pattern>, Option<Box<Pattern>>, Span),
+    // matches hashes holding at least these keys
+    Hash(Vec<(ExpressionNode, Pattern)>, Span),
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Wildcard(span) | Pattern::Array(_, _, span) | Pattern::Hash(_, span) => *span,
+            Pattern::Binding(ident) => ident.span,
+            Pattern::Literal(literal) => literal.span(),
+        }
+    }
+
+    pub fn string(&self) -> String {
+        match self {
+            Pattern::Wildcard(_) => "_".into(),
+            Pattern::Binding(ident) => ident.string(),
+            Pattern::Literal(literal) => literal.string(),
+            Pattern::Array(elements, rest, _) => format!(
+                "[{}]",
+                elements
+                    .iter()
+                    .map(|e| e.string())
+                    .chain(rest.iter().map(|r| format!("...{}", r.string())))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Pattern::Hash(entries, _) => format!(
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("{}:{}", key.string(), value.string()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    // every identifier the pattern binds, in source order
+    pub fn bindings(&self) -> Vec<&Identifier> {
+        match self {
+            Pattern::Wildcard(_) | Pattern::Literal(_) => vec![],
+            Pattern::Binding(ident) => vec![ident],
+            Pattern::Array(elements, rest, _) => elements
+                .iter()
+                .chain(rest.as_deref())
+                .flat_map(|e| e.bindings())
+                .collect(),
+            Pattern::Hash(entries, _) => entries.iter().flat_map(|(_, p)| p.bindings()).collect(),
+        }
+    }
+
+    // parses the pattern starting at the current token
+    pub fn parse(parser: &mut Parser) -> ParsableResult<Pattern> {
+        match &parser.current_token {
+            Token::IDENT(name) if name == "_" => Ok(Pattern::Wildcard(parser.current_span)),
+            Token::IDENT(name) => Ok(Pattern::Binding(Identifier {
+                token: parser.current_token.clone(),
+                value: name.clone(),
+                span: parser.current_span,
+            })),
+            Token::INT(_) | Token::FLOAT(_) | Token::STRING(_) | Token::TRUE | Token::FALSE => {
+                Ok(Pattern::Literal(parser.parse_prefix()?))
+            }
+            Token::MINUS if matches!(parser.peek_token, Token::INT(_) | Token::FLOAT(_)) => {
+                Ok(Pattern::Literal(parser.parse_prefix()?))
+            }
+            Token::LBRACKET => Pattern::parse_array(parser),
+            Token::LBRACE => Pattern::parse_hash(parser),
+            _ => Err(parser.current_error(ParseErrorKind::ExpectedPattern)),
+        }
+    }
+
+    fn parse_array(parser: &mut Parser) -> ParsableResult<Pattern> {
+        let start = parser.current_span;
+        let mut elements = vec![];
+        let mut rest = None;
+
+        while !parser.peek_token.is(&Token::RBRACKET) {
+            parser.next_token();
+
+            // the rest has to be the last element
+            if parser.current_token.is(&Token::ELLIPSIS) {
+                parser.next_token();
+                if !matches!(parser.current_token, Token::IDENT(_)) {
+                    return Err(parser.current_error(ParseErrorKind::ExpectedIdentifier));
+                }
+
+                rest = Some(Box::new(Pattern::parse(parser)?));
+                break;
+            }
+
+            elements.push(Pattern::parse(parser)?);
+
+            if !parser.peek_token.is(&Token::RBRACKET) {
+                parser.expect_token(Token::COMMA)?;
+            }
+        }
+
+        parser.expect_token(Token::RBRACKET)?;
+
+        Ok(Pattern::Array(elements, rest, parser.span_from(start)))
+    }
+
+    fn parse_hash(parser: &mut Parser) -> ParsableResult<Pattern> {
+        let start = parser.current_span;
+        let mut entries = vec![];
+
+        while !parser.peek_token.is(&Token::RBRACE) {
+            parser.next_token();
+
+            let key = match parser.current_token {
+                Token::INT(_) | Token::STRING(_) | Token::TRUE | Token::FALSE => {
+                    parser.parse_prefix()?
+                }
+                _ => return Err(parser.current_error(ParseErrorKind::ExpectedPattern)),
+            };
+
+            parser.expect_token(Token::COLON)?;
+            parser.next_token();
+
+            entries.push((key, Pattern::parse(parser)?));
+
+            if !parser.peek_token.is(&Token::RBRACE) {
+                parser.expect_token(Token::COMMA)?;
+            }
+        }
+
+        parser.expect_token(Token::RBRACE)?;
+
+        Ok(Pattern::Hash(entries, parser.span_from(start)))
+    }
+}
@@ -2,7 +2,7 @@ use core::panic;
 
 use crate::{
     parser::{precedence::Precedence, Parser},
-    tokens::token::Token,
+    tokens::{span::Span, token::Token},
 };
 
 use super::{AstNode, ExpressionNode, ParsableResult, ParsePrefix};
@@ -12,6 +12,7 @@ pub struct PrefixExpression {
     pub token: Token,
     pub operator: Token,
     pub right: Box<ExpressionNode>,
+    pub span: Span,
 }
 
 impl AstNode for PrefixExpression {
@@ -19,6 +20,10 @@ impl AstNode for PrefixExpression {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         let operator = match self.operator {
             Token::BANG => '!',
@@ -33,6 +38,7 @@ impl ParsePrefix for PrefixExpression {
     fn parse_prefix(parser: &mut Parser) -> ParsableResult<ExpressionNode> {
         let token = parser.current_token.clone();
         let operator = parser.current_token.clone();
+        let start = parser.current_span;
 
         parser.next_token();
 
@@ -42,6 +48,7 @@ impl ParsePrefix for PrefixExpression {
             token,
             operator,
             right: Box::new(expression),
+            span: parser.span_from(start),
         }))
     }
 }
@@ -54,7 +61,7 @@ mod test {
 
     use crate::{
         ast::{test::test_expression, ExpressionNode, StatementNode},
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
         tokens::token::Token,
     };
 
@@ -77,7 +84,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
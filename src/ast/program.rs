@@ -1,3 +1,5 @@
+use crate::tokens::span::Span;
+
 use super::{AstNode, StatementNode};
 
 #[derive(Debug, Clone)]
@@ -13,6 +15,13 @@ impl AstNode for Program {
             .token()
     }
 
+    fn span(&self) -> Span {
+        match (self.statements.first(), self.statements.last()) {
+            (Some(first), Some(last)) => first.span().to(&last.span()),
+            _ => Span::default(),
+        }
+    }
+
     fn string(&self) -> String {
         self.statements
             .iter()
@@ -1,4 +1,7 @@
-use crate::{parser::precedence::Precedence, tokens::token::Token};
+use crate::{
+    parser::precedence::Precedence,
+    tokens::{span::Span, token::Token},
+};
 
 use super::{AstNode, ExpressionNode, ParseStatement};
 
@@ -6,6 +9,7 @@ use super::{AstNode, ExpressionNode, ParseStatement};
 pub struct ReturnStatement {
     pub token: Token,
     pub return_value: ExpressionNode,
+    pub span: Span,
 }
 
 impl AstNode for ReturnStatement {
@@ -13,6 +17,10 @@ impl AstNode for ReturnStatement {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         format!("return {};", self.return_value.string())
     }
@@ -21,6 +29,7 @@ impl AstNode for ReturnStatement {
 impl ParseStatement for ReturnStatement {
     fn parse(parser: &mut crate::parser::Parser) -> super::ParsableResult<super::StatementNode> {
         let token = parser.current_token.clone();
+        let start = parser.current_span;
         parser.next_token();
 
         let expression = parser.parse_expression(Precedence::LOWEST)?;
@@ -32,6 +41,7 @@ impl ParseStatement for ReturnStatement {
         Ok(super::StatementNode::ReturnStatement(ReturnStatement {
             token,
             return_value: expression,
+            span: parser.span_from(start),
         }))
     }
 }
@@ -45,7 +55,7 @@ mod test {
 
     use crate::{
         ast::{test::test_expression, AstNode, StatementNode},
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
         tokens::token::Token,
     };
 
@@ -66,7 +76,7 @@ mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -0,0 +1,126 @@
+use crate::{
+    parser::{precedence::Precedence, Parser},
+    tokens::{span::Span, token::Token},
+};
+
+use super::{AstNode, ExpressionNode, ParsableResult};
+
+#[derive(Debug, Clone)]
+pub struct SliceExpression {
+    pub token: Token,
+    pub left: Box<ExpressionNode>,
+    pub start: Option<Box<ExpressionNode>>,
+    pub end: Option<Box<ExpressionNode>>,
+    pub span: Span,
+}
+
+impl AstNode for SliceExpression {
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn string(&self) -> String {
+        let bound = |b: &Option<Box<ExpressionNode>>| b.as_ref().map_or("".into(), |b| b.string());
+
+        format!(
+            "({}[{}:{}])",
+            self.left.string(),
+            bound(&self.start),
+            bound(&self.end)
+        )
+    }
+}
+
+impl SliceExpression {
+    // continues an index expression once its colon is the current token
+    pub fn parse_slice(
+        parser: &mut Parser,
+        token: Token,
+        left: ExpressionNode,
+        start: Option<ExpressionNode>,
+    ) -> ParsableResult<ExpressionNode> {
+        let span = left.span();
+
+        let end = match parser.peek_token.is(&Token::RBRACKET) {
+            true => None,
+            false => {
+                parser.next_token();
+                Some(parser.parse_expression(Precedence::LOWEST)?)
+            }
+        };
+
+        parser.expect_token(Token::RBRACKET)?;
+
+        Ok(ExpressionNode::SliceExpression(SliceExpression {
+            token,
+            left: Box::new(left),
+            start: start.map(Box::new),
+            end: end.map(Box::new),
+            span: parser.span_from(span),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::{
+        ast::{AstNode, ExpressionNode, StatementNode},
+        parser::{parse_error::ParseError, Parser},
+    };
+
+    #[rstest]
+    #[case("a[1:2]", "(a[1:2])")]
+    #[case("a[:n + 1]", "(a[:(n + 1)])")]
+    #[case("a[-2:]", "(a[(-2):])")]
+    #[case("a[:]", "(a[:])")]
+    #[case("a[1:][0]", "((a[1:])[0])")]
+    #[case("f()[i:j]", "(f()[i:j])")]
+    fn test_slice_expression(#[case] input: &str, #[case] expected: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        let empty: Vec<ParseError> = vec![];
+
+        assert_eq!(errors, empty);
+        assert_eq!(program.statements.len(), 1);
+        assert_eq!(program.string(), expected);
+    }
+
+    #[test]
+    fn test_slice_bounds() {
+        let mut parser = Parser::new("a[:3]".into());
+
+        let (program, errors) = parser.parse_program();
+        assert_eq!(errors, Vec::<ParseError>::new());
+
+        let StatementNode::ExpressionStatement(expression) = &program.statements[0] else {
+            panic!(
+                "expected ExpressionStatement, got {:?}",
+                program.statements[0]
+            );
+        };
+        let ExpressionNode::SliceExpression(slice) = &expression.expression else {
+            panic!("expected SliceExpression, got {:?}", expression.expression);
+        };
+
+        assert!(slice.start.is_none());
+        assert_eq!(slice.end.as_ref().unwrap().string(), "3");
+    }
+
+    #[rstest]
+    #[case("a[1:2:3]", "expected ']', found ':' at 1:6")]
+    #[case("a[1:", "expected expression, found end of input at 1:5")]
+    fn test_slice_errors(#[case] input: &str, #[case] expected: &str) {
+        let mut parser = Parser::new(input.into());
+
+        let (_, errors) = parser.parse_program();
+
+        assert_eq!(errors.first().map(|e| e.to_string()), Some(expected.into()));
+    }
+}
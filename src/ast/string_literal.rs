@@ -1,4 +1,7 @@
-use crate::tokens::token::Token;
+use crate::{
+    parser::parse_error::ParseErrorKind,
+    tokens::{span::Span, token::Token},
+};
 
 use super::{AstNode, ParsePrefix};
 
@@ -6,6 +9,7 @@ use super::{AstNode, ParsePrefix};
 pub struct StringLiteral {
     pub token: Token,
     pub value: String,
+    pub span: Span,
 }
 
 impl AstNode for StringLiteral {
@@ -13,6 +17,10 @@ impl AstNode for StringLiteral {
         &self.token
     }
 
+    fn span(&self) -> Span {
+        self.span
+    }
+
     fn string(&self) -> String {
         self.value.to_string()
     }
@@ -23,12 +31,13 @@ impl ParsePrefix for StringLiteral {
         parser: &mut crate::parser::Parser,
     ) -> super::ParsableResult<super::ExpressionNode> {
         let Token::STRING(value) = parser.current_token.clone() else {
-            return Err(format!("Invalid token {:?}", parser.current_token));
+            return Err(parser.current_error(ParseErrorKind::ExpectedExpression));
         };
 
         Ok(super::ExpressionNode::StringLiteral(StringLiteral {
             token: Token::STRING(value.to_string()),
             value,
+            span: parser.current_span,
         }))
     }
 }
@@ -37,7 +46,7 @@ impl ParsePrefix for StringLiteral {
 pub mod test {
     use crate::{
         ast::{ExpressionNode, StatementNode},
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
     };
 
     #[test]
@@ -46,7 +55,7 @@ pub mod test {
         let mut parser = Parser::new(input.into());
 
         let (program, errors) = parser.parse_program();
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
 
         assert_eq!(errors, empty);
         assert_eq!(program.statements.len(), 1);
@@ -0,0 +1,127 @@
+use crate::{
+    parser::{precedence::Precedence, Parser},
+    tokens::{span::Span, token::Token},
+};
+
+use super::{
+    block_statement::BlockStatement, AstNode, ExpressionNode, ParsableResult, ParseStatement,
+    StatementNode,
+};
+
+#[derive(Debug, Clone)]
+pub struct WhileStatement {
+    pub token: Token,
+    pub condition: ExpressionNode,
+    pub body: BlockStatement,
+    pub span: Span,
+}
+
+impl AstNode for WhileStatement {
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn string(&self) -> String {
+        format!("while {} {}", self.condition.string(), self.body.string())
+    }
+}
+
+impl ParseStatement for WhileStatement {
+    fn parse(parser: &mut Parser) -> ParsableResult<StatementNode> {
+        let token = parser.current_token.clone();
+        let start = parser.current_span;
+
+        parser.expect_token(Token::LPAREN)?;
+        parser.next_token();
+
+        let condition = parser.parse_expression(Precedence::LOWEST)?;
+
+        parser.expect_token(Token::RPAREN)?;
+        parser.expect_token(Token::LBRACE)?;
+
+        parser.loop_depth += 1;
+        let body = parser.parse_block();
+        parser.loop_depth -= 1;
+
+        let body = body?;
+        let span = parser.span_from(start);
+
+        if parser.peek_token.is(&Token::SEMICOLON) {
+            parser.next_token();
+        }
+
+        Ok(StatementNode::WhileStatement(WhileStatement {
+            token,
+            condition,
+            body,
+            span,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ast::{
+            infix_expression::test::test_infix_expression, AstNode, ExpressionNode, StatementNode,
+        },
+        parser::{parse_error::ParseError, Parser},
+        tokens::token::Token,
+    };
+
+    #[test]
+    fn test_while_statement() {
+        let input = "while (x < y) { if (x == 5) { break; } x += 1; continue }";
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        let empty: Vec<ParseError> = vec![];
+
+        assert_eq!(errors, empty);
+        assert_eq!(program.statements.len(), 1);
+
+        let node = program.statements.first().unwrap();
+        let StatementNode::WhileStatement(statement) = node else {
+            panic!("expected WhileStatement for node, got {:?}", node);
+        };
+
+        test_infix_expression(&statement.condition, "x", Token::LT, "y");
+
+        let body = &statement.body.statements;
+        assert_eq!(body.len(), 3);
+        assert!(matches!(
+            &body[0],
+            StatementNode::ExpressionStatement(e)
+                if matches!(e.expression, ExpressionNode::IfExpression(_))
+        ));
+        assert!(matches!(&body[2], StatementNode::ContinueStatement(_)));
+        assert_eq!(
+            statement.string(),
+            "while (x < y) if (x == 5) break; (x += 1)continue;"
+        );
+    }
+
+    #[test]
+    fn test_loop_control_outside_loop() {
+        let input = "break;
+while (true) { fn() { continue; }; break; }
+continue";
+        let mut parser = Parser::new(input.into());
+
+        let (_, errors) = parser.parse_program();
+
+        let messages = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "'break' outside of a loop at 1:1",
+                "'continue' outside of a loop at 2:23",
+                "'continue' outside of a loop at 3:1",
+            ]
+        );
+    }
+}
@@ -10,7 +10,7 @@ pub fn builtin_first(args: Vec<Object>) -> Object {
     };
 
     match args.into_iter().next().unwrap() {
-        Object::Array(a) => a.into_iter().next().unwrap_or(Object::Null),
+        Object::Array(a) => a.first().cloned().unwrap_or(Object::Null),
         e => Object::Error(format!(
             "argument to `first` must be ARRAY, got {}",
             e.type_str()
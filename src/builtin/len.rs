@@ -10,8 +10,9 @@ pub fn builtin_len(args: Vec<Object>) -> Object {
     };
 
     match args.into_iter().next().unwrap() {
-        Object::String(s) => (s.len() as i64).into(),
+        Object::String(s) => (s.chars().count() as i64).into(),
         Object::Array(a) => (a.len() as i64).into(),
+        Object::Range(start, end) => Object::range_len(start, end).into(),
         e => Object::Error(format!(
             "arguments to `len` not supported, got {}",
             e.type_str()
@@ -55,6 +55,7 @@ mod test {
     #[case("len(\"\")", 0)]
     #[case("len(\"four\")", 4)]
     #[case("len(\"hello world\")", 11)]
+    #[case("len(\"héllo\")", 5)]
     #[case("len([1, 2, 3])", 3)]
     #[case("len([])", 0)]
     #[case("first([1, 2, 3])", 1)]
@@ -103,8 +104,8 @@ mod test {
         };
 
         assert_eq!(expected.len(), result.len());
-        for (exp, obj) in expected.iter().zip(result) {
-            test_object(&obj, exp);
+        for (exp, obj) in expected.iter().zip(result.iter()) {
+            test_object(obj, exp);
         }
     }
 
@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use crate::object::Object;
 
 pub fn builtin_push(args: Vec<Object>) -> Object {
@@ -15,7 +17,7 @@ pub fn builtin_push(args: Vec<Object>) -> Object {
 
     match array {
         Object::Array(mut a) => {
-            a.push(item);
+            Rc::make_mut(&mut a).push(item);
 
             Object::Array(a)
         }
@@ -14,7 +14,7 @@ pub fn builtin_rest(args: Vec<Object>) -> Object {
             if a.is_empty() {
                 return Object::Null;
             }
-            Object::Array(a[1..].to_vec())
+            a[1..].to_vec().into()
         }
         e => Object::Error(format!(
             "argument to `last` must be ARRAY, got {}",
@@ -9,13 +9,19 @@ pub mod read_operands;
 pub enum Opcode {
     OpConstant,
     OpPop,
+    OpDup,
     OpNull,
     OpArray,
     OpHash,
     OpIndex,
+    OpSlice,
+    OpRange,
     OpGetBuiltin,
 
     OpCall,
+    // a call whose result is returned right away, it replaces the frame of
+    // the function making it
+    OpTailCall,
     OpReturnValue,
     OpReturn,
 
@@ -23,6 +29,7 @@ pub enum Opcode {
     OpSub,
     OpMul,
     OpDiv,
+    OpMod,
 
     OpTrue,
     OpFalse,
@@ -30,6 +37,9 @@ pub enum Opcode {
     OpEqual,
     OpNotEqual,
     OpGreaterThan,
+    OpGreaterEqual,
+    OpLessThan,
+    OpLessEqual,
 
     OpMinus,
     OpBang,
@@ -37,14 +47,32 @@ pub enum Opcode {
     OpJumpNotTruthy,
     OpJump,
 
+    OpIterInit,
+    OpIterNext,
+
+    OpMatchArray,
+    OpMatchHash,
+    OpHasKey,
+    OpUnpackArray,
+    OpUnpackHash,
+
     OpSetGlobal,
     OpGetGlobal,
 
+    // the variable an index assignment updates, moved out of its slot for the
+    // update so a value nothing else shares is not copied
+    OpSetIndexGlobal,
+    OpSetIndexLocal,
+    OpSetIndexFree,
+
     OpSetLocal,
     OpGetLocal,
 
     OpClosure,
     OpGetFree,
+    OpSetFree,
+    OpCaptureLocal,
+    OpCaptureFree,
     OpCurrentClosure,
 
     OpNoop,
@@ -56,8 +84,14 @@ impl Opcode {
             Opcode::OpSetLocal
             | Opcode::OpGetLocal
             | Opcode::OpCall
+            | Opcode::OpTailCall
+            | Opcode::OpRange
+            | Opcode::OpUnpackHash
             | Opcode::OpGetBuiltin
-            | Opcode::OpGetFree => {
+            | Opcode::OpGetFree
+            | Opcode::OpSetFree
+            | Opcode::OpCaptureLocal
+            | Opcode::OpCaptureFree => {
                 vec![1]
             }
 
@@ -70,25 +104,41 @@ impl Opcode {
             | Opcode::OpSetGlobal => vec![2],
 
             Opcode::OpPop
+            | Opcode::OpDup
             | Opcode::OpCurrentClosure
             | Opcode::OpNull
             | Opcode::OpAdd
             | Opcode::OpSub
             | Opcode::OpMul
             | Opcode::OpDiv
+            | Opcode::OpMod
             | Opcode::OpTrue
             | Opcode::OpFalse
             | Opcode::OpEqual
             | Opcode::OpNotEqual
             | Opcode::OpGreaterThan
+            | Opcode::OpGreaterEqual
+            | Opcode::OpLessThan
+            | Opcode::OpLessEqual
             | Opcode::OpMinus
             | Opcode::OpIndex
+            | Opcode::OpSlice
             | Opcode::OpReturnValue
             | Opcode::OpReturn
             | Opcode::OpNoop
+            | Opcode::OpIterInit
+            | Opcode::OpMatchHash
+            | Opcode::OpHasKey
             | Opcode::OpBang => vec![],
 
             Opcode::OpClosure => vec![2, 1],
+            // jump target once exhausted, number of values pushed per step
+            Opcode::OpIterNext => vec![2, 1],
+            // variable, number of keys, opcode of a compound operator or OpNoop
+            Opcode::OpSetIndexGlobal => vec![2, 1, 1],
+            Opcode::OpSetIndexLocal | Opcode::OpSetIndexFree => vec![1, 1, 1],
+            // number of elements, whether a rest array follows them
+            Opcode::OpMatchArray | Opcode::OpUnpackArray => vec![1, 1],
         };
 
         Definition {
@@ -104,6 +154,62 @@ impl Opcode {
     pub fn is(&self, op: &Opcode) -> bool {
         self == op
     }
+
+    // values an instruction pops and pushes, the ones a jump leaves behind are
+    // handled separately
+    pub fn stack_effect(&self, operands: &[usize]) -> (usize, usize) {
+        match self {
+            Opcode::OpConstant
+            | Opcode::OpNull
+            | Opcode::OpTrue
+            | Opcode::OpFalse
+            | Opcode::OpGetBuiltin
+            | Opcode::OpGetGlobal
+            | Opcode::OpGetLocal
+            | Opcode::OpGetFree
+            | Opcode::OpCaptureLocal
+            | Opcode::OpCaptureFree
+            | Opcode::OpCurrentClosure => (0, 1),
+
+            Opcode::OpPop
+            | Opcode::OpSetGlobal
+            | Opcode::OpSetLocal
+            | Opcode::OpSetFree
+            | Opcode::OpJumpNotTruthy
+            | Opcode::OpReturnValue => (1, 0),
+
+            Opcode::OpDup | Opcode::OpMatchArray | Opcode::OpMatchHash => (1, 2),
+            Opcode::OpMinus | Opcode::OpBang | Opcode::OpIterInit => (1, 1),
+
+            Opcode::OpAdd
+            | Opcode::OpSub
+            | Opcode::OpMul
+            | Opcode::OpDiv
+            | Opcode::OpMod
+            | Opcode::OpEqual
+            | Opcode::OpNotEqual
+            | Opcode::OpGreaterThan
+            | Opcode::OpGreaterEqual
+            | Opcode::OpLessThan
+            | Opcode::OpLessEqual
+            | Opcode::OpIndex
+            | Opcode::OpRange
+            | Opcode::OpHasKey => (2, 1),
+            Opcode::OpSlice => (3, 1),
+
+            Opcode::OpArray | Opcode::OpHash => (operands[0], 1),
+            Opcode::OpClosure => (operands[1], 1),
+            Opcode::OpCall | Opcode::OpTailCall => (operands[0] + 1, 1),
+            Opcode::OpSetIndexGlobal | Opcode::OpSetIndexLocal | Opcode::OpSetIndexFree => {
+                (operands[1] + 1, 1)
+            }
+            Opcode::OpUnpackArray => (1, operands[0] + operands[1]),
+            Opcode::OpUnpackHash => (operands[0] + 1, operands[0]),
+            Opcode::OpIterNext => (1, operands[1] + 1),
+
+            Opcode::OpJump | Opcode::OpReturn | Opcode::OpNoop => (0, 0),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Clone)]
@@ -115,11 +221,39 @@ impl Debug for Instructions {
     }
 }
 
+impl Instructions {
+    // the offset of the instruction whose opcode or operands are at `offset`
+    pub fn instruction_start(&self, offset: usize) -> usize {
+        let mut i = 0;
+        while i < self.0.len() {
+            let Ok(op) = Opcode::try_from(self.0[i]) else {
+                break;
+            };
+            let (_, read) = read_operands(&op.definition(), &self.0[i + 1..]);
+
+            if offset < i + 1 + read {
+                break;
+            }
+
+            i += 1 + read;
+        }
+
+        i
+    }
+}
+
 impl Display for Instructions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut i = 0;
         while i < self.0.len() {
-            let op: Opcode = self.0[i].into();
+            let op = match Opcode::try_from(self.0[i]) {
+                Ok(op) => op,
+                Err(e) => {
+                    writeln!(f, "{:04} ERROR: {}", i, e)?;
+                    i += 1;
+                    continue;
+                }
+            };
             let def = op.definition();
 
             let (ops, read) = read_operands(&def, &self.0[i + 1..]);
@@ -145,16 +279,73 @@ impl From<Opcode> for u8 {
     }
 }
 
-impl From<u8> for Opcode {
-    fn from(value: u8) -> Self {
-        unsafe { std::mem::transmute(value) }
+impl TryFrom<u8> for Opcode {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        let op = match value {
+            0 => Opcode::OpConstant,
+            1 => Opcode::OpPop,
+            2 => Opcode::OpDup,
+            3 => Opcode::OpNull,
+            4 => Opcode::OpArray,
+            5 => Opcode::OpHash,
+            6 => Opcode::OpIndex,
+            7 => Opcode::OpSlice,
+            8 => Opcode::OpRange,
+            9 => Opcode::OpGetBuiltin,
+            10 => Opcode::OpCall,
+            11 => Opcode::OpTailCall,
+            12 => Opcode::OpReturnValue,
+            13 => Opcode::OpReturn,
+            14 => Opcode::OpAdd,
+            15 => Opcode::OpSub,
+            16 => Opcode::OpMul,
+            17 => Opcode::OpDiv,
+            18 => Opcode::OpMod,
+            19 => Opcode::OpTrue,
+            20 => Opcode::OpFalse,
+            21 => Opcode::OpEqual,
+            22 => Opcode::OpNotEqual,
+            23 => Opcode::OpGreaterThan,
+            24 => Opcode::OpGreaterEqual,
+            25 => Opcode::OpLessThan,
+            26 => Opcode::OpLessEqual,
+            27 => Opcode::OpMinus,
+            28 => Opcode::OpBang,
+            29 => Opcode::OpJumpNotTruthy,
+            30 => Opcode::OpJump,
+            31 => Opcode::OpIterInit,
+            32 => Opcode::OpIterNext,
+            33 => Opcode::OpMatchArray,
+            34 => Opcode::OpMatchHash,
+            35 => Opcode::OpHasKey,
+            36 => Opcode::OpUnpackArray,
+            37 => Opcode::OpUnpackHash,
+            38 => Opcode::OpSetGlobal,
+            39 => Opcode::OpGetGlobal,
+            40 => Opcode::OpSetIndexGlobal,
+            41 => Opcode::OpSetIndexLocal,
+            42 => Opcode::OpSetIndexFree,
+            43 => Opcode::OpSetLocal,
+            44 => Opcode::OpGetLocal,
+            45 => Opcode::OpClosure,
+            46 => Opcode::OpGetFree,
+            47 => Opcode::OpSetFree,
+            48 => Opcode::OpCaptureLocal,
+            49 => Opcode::OpCaptureFree,
+            50 => Opcode::OpCurrentClosure,
+            51 => Opcode::OpNoop,
+            _ => return Err(format!("unknown opcode {}", value)),
+        };
+
+        Ok(op)
     }
 }
 
-#[allow(dead_code)]
 pub struct Definition {
-    name: String,
-    operand_widths: Vec<usize>,
+    pub name: String,
+    pub operand_widths: Vec<usize>,
 }
 
 #[cfg(test)]
@@ -180,6 +371,20 @@ pub mod test {
         assert_eq!(operands, operands_read)
     }
 
+    #[test]
+    fn test_opcode_try_from() {
+        for byte in 0..=Opcode::OpNoop as u8 {
+            assert_eq!(Opcode::try_from(byte).map(|op| op as u8), Ok(byte));
+        }
+
+        assert_eq!(Opcode::try_from(0), Ok(Opcode::OpConstant));
+        assert_eq!(Opcode::try_from(Opcode::OpNoop as u8), Ok(Opcode::OpNoop));
+        assert_eq!(
+            Opcode::try_from(Opcode::OpNoop as u8 + 1),
+            Err(format!("unknown opcode {}", Opcode::OpNoop as u8 + 1))
+        );
+    }
+
     #[test]
     fn test_instructions_string() {
         let instructions = vec![
@@ -0,0 +1,362 @@
+use std::rc::Rc;
+
+use crate::{
+    code::Instructions,
+    object::{CompiledFunction, Object},
+};
+
+use super::Bytecode;
+
+// layout of a `.mkc` file, every number is little endian:
+//
+//   magic "MKC\0", version u16
+//   instructions of the main program, its line table, names of the globals
+//   constant count u32, then every constant as a tag byte and its payload
+//
+// byte strings and lists are prefixed with their length as a u32
+const MAGIC: &[u8; 4] = b"MKC\0";
+// 2: names of globals, locals and free values
+// 3: OpLessThan and OpLessEqual, the opcodes after them moved up
+// 4: OpSetIndex split into one opcode per scope of the variable
+const VERSION: u16 = 4;
+
+const TAG_INTEGER: u8 = 0;
+const TAG_FLOAT: u8 = 1;
+const TAG_STRING: u8 = 2;
+const TAG_FUNCTION: u8 = 3;
+
+impl Bytecode {
+    pub fn serialize(&self) -> Result<Vec<u8>, String> {
+        let mut writer = Writer(vec![]);
+
+        writer.0.extend_from_slice(MAGIC);
+        writer.u16(VERSION);
+
+        writer.bytes(&self.instructions.0);
+        writer.lines(&self.lines);
+        writer.names(&self.globals);
+
+        writer.u32(self.constants.len());
+        for constant in &self.constants {
+            writer.constant(constant)?;
+        }
+
+        Ok(writer.0)
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Bytecode, String> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        if reader.take(MAGIC.len()).ok() != Some(MAGIC.as_slice()) {
+            return Err("not a monkey bytecode file".into());
+        }
+
+        let version = reader.u16()?;
+        if version != VERSION {
+            return Err(format!(
+                "unsupported bytecode version {}, expected {}",
+                version, VERSION
+            ));
+        }
+
+        let instructions = Instructions(reader.bytes()?.to_vec());
+        let lines = reader.lines()?;
+        let globals = reader.names()?;
+
+        let count = reader.u32()?;
+        let constants = (0..count)
+            .map(|_| reader.constant())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if reader.pos != bytes.len() {
+            return Err(format!(
+                "{} trailing bytes after the constants",
+                bytes.len() - reader.pos
+            ));
+        }
+
+        Ok(Bytecode {
+            instructions,
+            constants,
+            lines,
+            globals,
+        })
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: usize) {
+        self.0.extend_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.u32(bytes.len());
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn lines(&mut self, lines: &[(usize, usize)]) {
+        self.u32(lines.len());
+        for (offset, line) in lines {
+            self.u32(*offset);
+            self.u32(*line);
+        }
+    }
+
+    fn names(&mut self, names: &[String]) {
+        self.u32(names.len());
+        for name in names {
+            self.bytes(name.as_bytes());
+        }
+    }
+
+    fn constant(&mut self, constant: &Object) -> Result<(), String> {
+        match constant {
+            Object::Integer(i) => {
+                self.u8(TAG_INTEGER);
+                self.0.extend_from_slice(&i.to_le_bytes());
+            }
+            Object::Float(f) => {
+                self.u8(TAG_FLOAT);
+                self.0.extend_from_slice(&f.to_bits().to_le_bytes());
+            }
+            Object::String(s) => {
+                self.u8(TAG_STRING);
+                self.bytes(s.as_bytes());
+            }
+            Object::CompiledFunction(function) => {
+                self.u8(TAG_FUNCTION);
+                self.bytes(&function.instructions.0);
+                self.u32(function.num_locals);
+                self.u32(function.num_parameters);
+                self.u8(function.rest as u8);
+
+                match &function.name {
+                    Some(name) => {
+                        self.u8(1);
+                        self.bytes(name.as_bytes());
+                    }
+                    None => self.u8(0),
+                }
+
+                self.lines(&function.lines);
+
+                self.u32(function.entries.len());
+                for entry in &function.entries {
+                    self.u32(*entry);
+                }
+
+                self.names(&function.locals);
+                self.names(&function.free);
+            }
+            other => {
+                return Err(format!(
+                    "cannot serialize constant of type {}",
+                    other.type_str()
+                ))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("unexpected end of bytecode file")?;
+
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<usize, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.u32()?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| "invalid utf-8 in string".into())
+    }
+
+    fn lines(&mut self) -> Result<Vec<(usize, usize)>, String> {
+        let count = self.u32()?;
+
+        (0..count).map(|_| Ok((self.u32()?, self.u32()?))).collect()
+    }
+
+    fn names(&mut self) -> Result<Vec<String>, String> {
+        let count = self.u32()?;
+
+        (0..count).map(|_| self.string()).collect()
+    }
+
+    fn constant(&mut self) -> Result<Object, String> {
+        match self.u8()? {
+            TAG_INTEGER => Ok(Object::Integer(self.u64()? as i64)),
+            TAG_FLOAT => Ok(Object::Float(f64::from_bits(self.u64()?))),
+            TAG_STRING => Ok(self.string()?.into()),
+            TAG_FUNCTION => {
+                let instructions = Instructions(self.bytes()?.to_vec());
+                let num_locals = self.u32()?;
+                let num_parameters = self.u32()?;
+                let rest = self.u8()? == 1;
+
+                let name = match self.u8()? {
+                    0 => None,
+                    _ => Some(self.string()?),
+                };
+
+                let lines = self.lines()?;
+
+                let count = self.u32()?;
+                let entries = (0..count)
+                    .map(|_| self.u32())
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let locals = self.names()?;
+                let free = self.names()?;
+
+                Ok(Object::CompiledFunction(Rc::new(CompiledFunction {
+                    instructions,
+                    num_locals,
+                    num_parameters,
+                    rest,
+                    name,
+                    lines,
+                    entries,
+                    locals,
+                    free,
+                })))
+            }
+            tag => Err(format!("unknown constant tag {}", tag)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::{compile, compiler::Bytecode, object::Object, parse, run};
+
+    use super::VERSION;
+
+    fn compiled(input: &str) -> Bytecode {
+        let program = parse(input).expect("failed to parse");
+
+        compile(&program).expect("failed to compile")
+    }
+
+    #[rstest]
+    #[case("1 + 2", "3")]
+    #[case("2.5 * 2", "5.0")]
+    #[case("\"mon\" + \"key\"", "monkey")]
+    #[case(
+        "let add = fn(a, b = 2, ...rest) { a + b + len(rest) }; add(1) + add(1, 1, 1, 1)",
+        "7"
+    )]
+    #[case(
+        "let counter = fn() { let n = 0; fn() { n += 1 } }; let c = counter(); c(); c()",
+        "2"
+    )]
+    fn test_round_trip(#[case] input: &str, #[case] expected: &str) {
+        let bytecode = compiled(input);
+        let bytes = bytecode.serialize().unwrap();
+        let loaded = Bytecode::deserialize(&bytes).unwrap();
+
+        assert_eq!(loaded.instructions, bytecode.instructions);
+        assert_eq!(loaded.lines, bytecode.lines);
+        assert_eq!(loaded.serialize().unwrap(), bytes);
+
+        assert_eq!(run(loaded).unwrap().to_string(), expected);
+    }
+
+    #[test]
+    fn test_function_details_survive() {
+        let bytes = compiled("let f = fn(a, b = 1) {\n a + b\n};")
+            .serialize()
+            .unwrap();
+        let loaded = Bytecode::deserialize(&bytes).unwrap();
+
+        let Some(Object::CompiledFunction(function)) = loaded.constants.get(1) else {
+            panic!("expected a compiled function, got {:?}", loaded.constants);
+        };
+
+        assert_eq!(function.name.as_deref(), Some("f"));
+        assert_eq!(function.num_parameters, 2);
+        assert_eq!(function.entries.len(), 2);
+        assert_eq!(function.line(0), Some(1));
+        assert_eq!(function.lines.last().map(|(_, line)| *line), Some(2));
+        assert_eq!(function.locals, vec!["a", "b"]);
+        assert_eq!(loaded.globals, vec!["f"]);
+    }
+
+    #[test]
+    fn test_rejects_bad_files() {
+        let bytes = compiled("1").serialize().unwrap();
+
+        assert_eq!(
+            Bytecode::deserialize(b"#!monkey").err(),
+            Some("not a monkey bytecode file".into())
+        );
+
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert_eq!(
+            Bytecode::deserialize(&newer).err(),
+            Some(format!(
+                "unsupported bytecode version {}, expected {}",
+                VERSION + 1,
+                VERSION
+            ))
+        );
+
+        assert_eq!(
+            Bytecode::deserialize(&bytes[..bytes.len() - 1]).err(),
+            Some("unexpected end of bytecode file".into())
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Bytecode::deserialize(&trailing).err(),
+            Some("1 trailing bytes after the constants".into())
+        );
+    }
+}
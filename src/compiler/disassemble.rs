@@ -0,0 +1,318 @@
+use std::collections::HashSet;
+
+use crate::{
+    builtin::BUILTINS,
+    code::{
+        read_operands::{fmt_instruction, read_operands},
+        Opcode,
+    },
+    object::{CompiledFunction, Object},
+};
+
+use super::Bytecode;
+
+// annotations start in this column when the instruction is shorter
+const NOTE_COLUMN: usize = 24;
+
+impl Bytecode {
+    // a listing of the main program followed by every function it creates,
+    // with the operands resolved to the values and names they refer to. only
+    // meant for bytecode the compiler made or the verifier accepted
+    pub fn disassemble(&self) -> String {
+        let main = CompiledFunction {
+            lines: self.lines.clone(),
+            ..CompiledFunction::new(self.instructions.clone(), 0, 0)
+        };
+
+        let mut sections = vec![self.section(&main, None)];
+
+        // functions in the order their closures are made, nested ones right
+        // after the function creating them
+        let mut seen = HashSet::new();
+        let mut pending = closures(&main);
+        pending.reverse();
+        while let Some(index) = pending.pop() {
+            let Some(Object::CompiledFunction(function)) = self.constants.get(index) else {
+                continue;
+            };
+            if !seen.insert(index) {
+                continue;
+            }
+
+            sections.push(self.section(function, Some(index)));
+            pending.extend(closures(function).into_iter().rev());
+        }
+
+        // anything no closure refers to still gets listed
+        for (index, constant) in self.constants.iter().enumerate() {
+            if let Object::CompiledFunction(function) = constant {
+                if seen.insert(index) {
+                    sections.push(self.section(function, Some(index)));
+                }
+            }
+        }
+
+        sections.join("\n")
+    }
+
+    fn section(&self, function: &CompiledFunction, constant: Option<usize>) -> String {
+        let mut out = String::new();
+
+        // the main program has globals where functions have parameters
+        let globals: &[String] = match constant {
+            Some(index) => {
+                out.push_str(&format!(
+                    "== {} (constant {}) ==\n",
+                    function_name(function),
+                    index
+                ));
+                out.push_str(&format!(
+                    "parameters: {}{}\n",
+                    function.num_parameters,
+                    if function.rest { " and rest" } else { "" },
+                ));
+                &[]
+            }
+            None => {
+                out.push_str("== <main> ==\n");
+                &self.globals
+            }
+        };
+
+        for (label, names) in [
+            ("globals", globals),
+            ("locals", &function.locals),
+            ("free", &function.free),
+        ] {
+            if !names.is_empty() {
+                out.push_str(&format!("{}: {}\n", label, names.join(", ")));
+            }
+        }
+        if function.entries.len() > 1 {
+            let entries = function
+                .entries
+                .iter()
+                .map(|entry| format!("{:04}", entry))
+                .collect::<Vec<_>>();
+            out.push_str(&format!("entries: {}\n", entries.join(", ")));
+        }
+
+        let instructions = decode(function);
+
+        let mut targets = instructions
+            .iter()
+            .filter_map(|(_, op, operands)| jump_target(*op, operands))
+            .collect::<Vec<_>>();
+        targets.sort();
+        targets.dedup();
+        let label = |offset: usize| {
+            targets
+                .binary_search(&offset)
+                .ok()
+                .map(|i| format!("L{}", i))
+        };
+
+        let mut line = None;
+        for (offset, op, operands) in &instructions {
+            if let Some(label) = label(*offset) {
+                out.push_str(&format!("{}:\n", label));
+            }
+
+            if let Some(current) = function.line(*offset).filter(|l| Some(*l) != line) {
+                out.push_str(&format!("  ; line {}\n", current));
+                line = Some(current);
+            }
+
+            let text = format!(
+                "  {:04} {}",
+                offset,
+                fmt_instruction(&op.definition(), operands)
+            );
+            match self.note(function, *op, operands, &label) {
+                Some(note) => out.push_str(&format!("{:<NOTE_COLUMN$} {}\n", text, note)),
+                None => out.push_str(&format!("{}\n", text)),
+            }
+        }
+
+        // jumping to the very end finishes the main program
+        if let Some(label) = label(function.instructions.0.len()) {
+            out.push_str(&format!("{}:\n", label));
+        }
+
+        out
+    }
+
+    // what an operand refers to, if that is more than a number
+    fn note(
+        &self,
+        function: &CompiledFunction,
+        op: Opcode,
+        operands: &[usize],
+        label: &dyn Fn(usize) -> Option<String>,
+    ) -> Option<String> {
+        let name = |names: &[String], index: usize| names.get(index).cloned();
+
+        match op {
+            Opcode::OpConstant => self.constants.get(operands[0]).map(describe),
+            Opcode::OpClosure => self
+                .constants
+                .get(operands[0])
+                .map(|constant| format!("{}, {} free", describe(constant), operands[1])),
+            Opcode::OpGetGlobal | Opcode::OpSetGlobal => name(&self.globals, operands[0]),
+            Opcode::OpGetLocal | Opcode::OpSetLocal | Opcode::OpCaptureLocal => {
+                name(&function.locals, operands[0])
+            }
+            Opcode::OpGetFree | Opcode::OpSetFree | Opcode::OpCaptureFree => {
+                name(&function.free, operands[0])
+            }
+            Opcode::OpSetIndexGlobal | Opcode::OpSetIndexLocal | Opcode::OpSetIndexFree => {
+                let names = match op {
+                    Opcode::OpSetIndexGlobal => &self.globals,
+                    Opcode::OpSetIndexLocal => &function.locals,
+                    _ => &function.free,
+                };
+                let operator = Opcode::try_from(operands[2] as u8).ok()?;
+
+                let name = name(names, operands[0])?;
+                Some(match operator {
+                    Opcode::OpNoop => name,
+                    operator => format!("{} {}", name, operator.definition().name),
+                })
+            }
+            Opcode::OpGetBuiltin => BUILTINS.get(operands[0]).map(|(name, _)| name.to_string()),
+            Opcode::OpCurrentClosure => Some(function_name(function)),
+            Opcode::OpJump | Opcode::OpJumpNotTruthy | Opcode::OpIterNext => {
+                label(operands[0]).map(|label| format!("-> {}", label))
+            }
+            _ => None,
+        }
+    }
+}
+
+// every instruction with its offset and operands
+fn decode(function: &CompiledFunction) -> Vec<(usize, Opcode, Vec<usize>)> {
+    let bytes = &function.instructions.0;
+    let mut instructions = vec![];
+
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let Ok(op) = Opcode::try_from(bytes[offset]) else {
+            break;
+        };
+        let (operands, read) = read_operands(&op.definition(), &bytes[offset + 1..]);
+
+        instructions.push((offset, op, operands));
+        offset += 1 + read;
+    }
+
+    instructions
+}
+
+// constants of the functions `function` makes closures of, in order
+fn closures(function: &CompiledFunction) -> Vec<usize> {
+    decode(function)
+        .into_iter()
+        .filter(|(_, op, _)| op.is(&Opcode::OpClosure))
+        .map(|(_, _, operands)| operands[0])
+        .collect()
+}
+
+fn jump_target(op: Opcode, operands: &[usize]) -> Option<usize> {
+    match op {
+        Opcode::OpJump | Opcode::OpJumpNotTruthy | Opcode::OpIterNext => Some(operands[0]),
+        _ => None,
+    }
+}
+
+fn function_name(function: &CompiledFunction) -> String {
+    function
+        .name
+        .clone()
+        .unwrap_or_else(|| "<anonymous>".into())
+}
+
+fn describe(constant: &Object) -> String {
+    match constant {
+        Object::String(s) => format!("{:?}", s.as_ref()),
+        Object::CompiledFunction(function) => format!("fn {}", function_name(function)),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::{compile, parse};
+
+    fn disassemble(input: &str) -> String {
+        let program = parse(input).expect("failed to parse");
+
+        compile(&program).expect("failed to compile").disassemble()
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let input = "let add = fn(a, b) {
+  let c = a + b;
+  fn() { c }
+};
+if (true) { add(1, \"x\") } else { len([]) };";
+
+        assert_eq!(
+            disassemble(input),
+            "== <main> ==
+globals: add
+  ; line 1
+  0000 OpClosure 1 0     fn add, 0 free
+  0004 OpSetGlobal 0     add
+  ; line 5
+  0007 OpTrue
+  0008 OpJumpNotTruthy 25 -> L0
+  0011 OpGetGlobal 0     add
+  0014 OpConstant 2      1
+  0017 OpConstant 3      \"x\"
+  0020 OpCall 2
+  0022 OpJump 32         -> L1
+L0:
+  0025 OpGetBuiltin 0    len
+  0027 OpArray 0
+  0030 OpCall 1
+L1:
+  0032 OpPop
+
+== add (constant 1) ==
+parameters: 2
+locals: a, b, c
+  ; line 2
+  0000 OpGetLocal 0      a
+  0002 OpGetLocal 1      b
+  0004 OpAdd
+  0005 OpSetLocal 2      c
+  ; line 3
+  0007 OpCaptureLocal 2  c
+  0009 OpClosure 0 1     fn <anonymous>, 1 free
+  0013 OpReturnValue
+
+== <anonymous> (constant 0) ==
+parameters: 0
+free: c
+  ; line 3
+  0000 OpGetFree 0       c
+  0002 OpReturnValue
+"
+        );
+    }
+
+    #[rstest]
+    #[case("let a = [1]; a[0] *= 2", "OpMul\n")]
+    #[case("let f = fn(n) { f(n) }; f(1)", "OpCurrentClosure  f\n")]
+    #[case("let f = fn(a, b = 1, ...c) { a }", "parameters: 2 and rest\n")]
+    #[case("let f = fn(a, b = 1) { a }", "entries: 0000, 0005\n")]
+    #[case("let a = 1; let a = 2; a", "globals: a, a\n")]
+    fn test_disassemble_notes(#[case] input: &str, #[case] expected: &str) {
+        let listing = disassemble(input);
+
+        assert!(listing.contains(expected), "{}", listing);
+    }
+}
@@ -1,10 +1,22 @@
+mod bytecode_file;
+mod disassemble;
 mod symbol_table;
 
+use std::{mem, rc::Rc};
+
 use crate::{
-    ast::{ExpressionNode, Node, StatementNode},
+    ast::{
+        assign_expression::AssignExpression, infix_expression::InfixExpression,
+        match_expression::MatchExpression, pattern::Pattern, AstNode, ExpressionNode, Node,
+        StatementNode,
+    },
     builtin::BUILTINS,
-    code::{make::make, Instructions, Opcode},
-    object::Object,
+    code::{
+        make::make,
+        read_operands::{read_operands, read_u16},
+        Instructions, Opcode,
+    },
+    object::{CompiledFunction, Object},
     tokens::token::Token,
 };
 
@@ -15,6 +27,23 @@ pub struct CompilerScope {
 
     pub previous_instruction: (Opcode, usize),
     pub last_instruction: (Opcode, usize),
+
+    // (offset, line) of every instruction starting a new source line
+    pub lines: Vec<(usize, usize)>,
+
+    // values on the stack where the next instruction goes, jumps out of the
+    // straight line code are corrected for by the constructs making them
+    depth: usize,
+
+    loops: Vec<Loop>,
+}
+
+// jump target for `continue` and the `break` jumps waiting for the loop end,
+// both leave the stack as deep as it was at the start of the body
+struct Loop {
+    start: usize,
+    depth: usize,
+    breaks: Vec<usize>,
 }
 
 impl CompilerScope {
@@ -23,6 +52,9 @@ impl CompilerScope {
             instructions: Instructions(vec![]),
             previous_instruction: (Opcode::OpNoop, 0),
             last_instruction: (Opcode::OpNoop, 0),
+            lines: vec![],
+            depth: 0,
+            loops: vec![],
         }
     }
 }
@@ -31,11 +63,16 @@ pub struct Compiler {
     constants: Vec<Object>,
     symbol_table: SymbolTable,
     scopes: Vec<CompilerScope>,
+    // source line of the node being compiled
+    line: usize,
 }
 
 pub struct Bytecode {
     pub instructions: Instructions,
     pub constants: Vec<Object>,
+    pub lines: Vec<(usize, usize)>,
+    // names of the globals by index
+    pub globals: Vec<String>,
 }
 
 impl Bytecode {
@@ -43,6 +80,8 @@ impl Bytecode {
         Bytecode {
             instructions: Instructions(vec![]),
             constants: vec![],
+            lines: vec![],
+            globals: vec![],
         }
     }
 }
@@ -55,6 +94,7 @@ impl Compiler {
             constants: vec![],
             symbol_table: SymbolTable::new(),
             scopes: vec![CompilerScope::new()],
+            line: 0,
         };
 
         for (idx, (name, _)) in BUILTINS.iter().enumerate() {
@@ -69,6 +109,7 @@ impl Compiler {
             constants: self.constants,
             symbol_table: self.symbol_table,
             scopes: vec![CompilerScope::new()],
+            line: 0,
         }
     }
 
@@ -106,27 +147,71 @@ impl Compiler {
         };
     }
 
+    // pushes the cell a closure shares with the scope defining the variable
+    fn capture_symbol(&mut self, symbol: &Symbol) {
+        match symbol.scope {
+            symbol_table::Scope::Local => self.emit(Opcode::OpCaptureLocal, vec![symbol.index]),
+            symbol_table::Scope::Free => self.emit(Opcode::OpCaptureFree, vec![symbol.index]),
+            symbol_table::Scope::Function => self.emit(Opcode::OpCurrentClosure, vec![]),
+            _ => unreachable!("globals and builtins are never captured"),
+        };
+    }
+
+    fn store_symbol(&mut self, symbol: &Symbol) -> R {
+        // a closure only holds the value of the function it is nested in, not
+        // a variable it could write back to
+        let scope = match self.symbol_table.original(symbol).scope {
+            symbol_table::Scope::Function => symbol_table::Scope::Function,
+            _ => symbol.scope.clone(),
+        };
+
+        match scope {
+            symbol_table::Scope::Global => self.emit(Opcode::OpSetGlobal, vec![symbol.index]),
+            symbol_table::Scope::Local => self.emit(Opcode::OpSetLocal, vec![symbol.index]),
+            symbol_table::Scope::Free => self.emit(Opcode::OpSetFree, vec![symbol.index]),
+            symbol_table::Scope::Builtin => {
+                return Err(format!("cannot assign to builtin {}", symbol.name))
+            }
+            symbol_table::Scope::Function => {
+                return Err(format!(
+                    "cannot assign to function {} inside its own body",
+                    symbol.name
+                ))
+            }
+        };
+
+        Ok(())
+    }
+
     fn compile_statement(&mut self, statement: &StatementNode) -> R {
+        let line = mem::replace(&mut self.line, statement.span().line);
+        let result = self.compile_statement_node(statement);
+        self.line = line;
+
+        result
+    }
+
+    fn compile_statement_node(&mut self, statement: &StatementNode) -> R {
         match statement {
             StatementNode::LetStatement(node) => {
-                self.symbol_table.define(&node.identifier.value);
-                let symbol = self.symbol_table.resolve(&node.identifier.value).unwrap();
+                // a single name is visible to its own value so functions can
+                // recurse, names taken apart from a value are defined after it
+                let (before, after) = match node.pattern {
+                    Pattern::Binding(_) => (node.pattern.bindings(), vec![]),
+                    _ => (vec![], node.pattern.bindings()),
+                };
+
+                for ident in before {
+                    self.symbol_table.define(&ident.value);
+                }
 
                 self.compile_expression(&node.value)?;
 
-                match symbol.scope {
-                    symbol_table::Scope::Global => {
-                        self.emit(Opcode::OpSetGlobal, vec![symbol.index])
-                    }
-                    symbol_table::Scope::Local => self.emit(Opcode::OpSetLocal, vec![symbol.index]),
-                    symbol_table::Scope::Builtin => {
-                        self.emit(Opcode::OpGetBuiltin, vec![symbol.index])
-                    }
-                    symbol_table::Scope::Free => todo!(),
-                    symbol_table::Scope::Function => todo!(),
-                };
+                for ident in after {
+                    self.symbol_table.define(&ident.value);
+                }
 
-                Ok(())
+                self.compile_destructure(&node.pattern)
             }
             StatementNode::ReturnStatement(node) => {
                 self.compile_expression(&node.return_value)?;
@@ -136,6 +221,47 @@ impl Compiler {
                 Ok(())
             }
             StatementNode::BlockStatement(node) => self.compile_statements(&node.statements),
+            StatementNode::WhileStatement(node) => {
+                let start = self.scope().instructions.0.len();
+
+                self.compile_expression(&node.condition)?;
+                let jump_not_truthy_pos = self.emit(Opcode::OpJumpNotTruthy, vec![9999]);
+
+                self.push_loop(start);
+                let body = self.compile_statements(&node.body.statements);
+                let lp = self.scope_mut().loops.pop().unwrap();
+                body?;
+
+                self.emit(Opcode::OpJump, vec![start]);
+
+                let end = self.scope().instructions.0.len();
+                self.change_operand(jump_not_truthy_pos, end);
+                for pos in lp.breaks {
+                    self.change_operand(pos, end);
+                }
+
+                Ok(())
+            }
+            StatementNode::BreakStatement(_) => {
+                let Some(depth) = self.scope().loops.last().map(|lp| lp.depth) else {
+                    return Err("break outside of a loop".to_string());
+                };
+
+                let pos = self.exit_to(depth, 9999);
+                self.scope_mut().loops.last_mut().unwrap().breaks.push(pos);
+
+                Ok(())
+            }
+            StatementNode::ContinueStatement(_) => {
+                let Some((start, depth)) = self.scope().loops.last().map(|lp| (lp.start, lp.depth))
+                else {
+                    return Err("continue outside of a loop".to_string());
+                };
+
+                self.exit_to(depth, start);
+
+                Ok(())
+            }
             StatementNode::ExpressionStatement(node) => {
                 self.compile_expression(&node.expression)?;
                 self.emit(Opcode::OpPop, vec![]);
@@ -153,19 +279,31 @@ impl Compiler {
         self.scopes.push(scope);
     }
 
-    fn leave_scope(&mut self) -> Instructions {
+    fn leave_scope(&mut self) -> CompilerScope {
         let scope = self.scopes.pop().unwrap();
 
         self.symbol_table.pop();
 
-        scope.instructions
+        scope
     }
 
+    // instructions take the line of the innermost node they are compiled for
     fn compile_expression(&mut self, expression: &ExpressionNode) -> R {
+        let line = mem::replace(&mut self.line, expression.span().line);
+        let result = self.compile_expression_node(expression);
+        self.line = line;
+
+        result
+    }
+
+    fn compile_expression_node(&mut self, expression: &ExpressionNode) -> R {
         match expression {
             ExpressionNode::Identifier(node) => {
                 let Some(symbol) = self.symbol_table.resolve(&node.value) else {
-                    return Err(format!("undefined variable {}", node.value));
+                    return Err(format!(
+                        "undefined variable {} at {}",
+                        node.value, node.span
+                    ));
                 };
 
                 match symbol.scope {
@@ -190,6 +328,14 @@ impl Compiler {
 
                 Ok(())
             }
+            ExpressionNode::FloatLiteral(f) => {
+                let float = Object::Float(f.value);
+                let pos = self.add_constant(float);
+
+                self.emit(Opcode::OpConstant, vec![pos]);
+
+                Ok(())
+            }
             ExpressionNode::BooleanLiteral(node) => {
                 if node.value {
                     self.emit(Opcode::OpTrue, vec![]);
@@ -199,7 +345,7 @@ impl Compiler {
                 Ok(())
             }
             ExpressionNode::StringLiteral(node) => {
-                let obj = Object::String(node.value.to_string());
+                let obj = Object::String(node.value.as_str().into());
                 let pos = self.add_constant(obj);
                 self.emit(Opcode::OpConstant, vec![pos]);
 
@@ -225,25 +371,32 @@ impl Compiler {
 
                 Ok(())
             }
+            ExpressionNode::AssignExpression(node) => self.compile_assign(node),
+            ExpressionNode::InfixExpression(node)
+                if matches!(node.operator, Token::AND | Token::OR) =>
+            {
+                self.compile_logical(node)
+            }
             ExpressionNode::InfixExpression(node) => {
-                if node.operator.is(&Token::LT) {
-                    // invert left and right on less then
-                    self.compile_expression(&node.right)?;
-                    self.compile_expression(&node.left)?;
-                } else {
-                    self.compile_expression(&node.left)?;
-                    self.compile_expression(&node.right)?;
-                }
+                self.compile_expression(&node.left)?;
+                self.compile_expression(&node.right)?;
 
                 match &node.operator {
                     Token::PLUS => self.emit(Opcode::OpAdd, vec![]),
                     Token::MINUS => self.emit(Opcode::OpSub, vec![]),
                     Token::ASTERISK => self.emit(Opcode::OpMul, vec![]),
                     Token::SLASH => self.emit(Opcode::OpDiv, vec![]),
+                    Token::PERCENT => self.emit(Opcode::OpMod, vec![]),
 
-                    Token::GT | Token::LT => self.emit(Opcode::OpGreaterThan, vec![]),
+                    Token::GT => self.emit(Opcode::OpGreaterThan, vec![]),
+                    Token::GT_EQ => self.emit(Opcode::OpGreaterEqual, vec![]),
+                    Token::LT => self.emit(Opcode::OpLessThan, vec![]),
+                    Token::LT_EQ => self.emit(Opcode::OpLessEqual, vec![]),
                     Token::EQ => self.emit(Opcode::OpEqual, vec![]),
                     Token::NOT_EQ => self.emit(Opcode::OpNotEqual, vec![]),
+
+                    Token::DOT_DOT => self.emit(Opcode::OpRange, vec![0]),
+                    Token::DOT_DOT_EQ => self.emit(Opcode::OpRange, vec![1]),
                     e => Err(format!("unknown infix operator {e:?}"))?,
                 };
 
@@ -256,22 +409,18 @@ impl Compiler {
                 let jump_not_truthy_pos = self.emit(Opcode::OpJumpNotTruthy, vec![9999]);
 
                 self.compile_statements(&node.concequence.statements)?;
-
-                if self.scope().last_instruction.0.is(&Opcode::OpPop) {
-                    self.remove_last();
-                }
+                self.keep_block_value();
 
                 let jump_pos = self.emit(Opcode::OpJump, vec![9999]);
+                // the alternative starts without the value of the consequence
+                self.scope_mut().depth -= 1;
 
                 let after_concequence_pos = self.scope().instructions.0.len();
                 self.change_operand(jump_not_truthy_pos, after_concequence_pos);
 
                 if let Some(alternative) = &node.alternative {
                     self.compile_statements(&alternative.statements)?;
-
-                    if self.scope().last_instruction.0.is(&Opcode::OpPop) {
-                        self.remove_last();
-                    }
+                    self.keep_block_value();
                 } else {
                     self.emit(Opcode::OpNull, vec![]);
                 }
@@ -281,6 +430,45 @@ impl Compiler {
 
                 Ok(())
             }
+            ExpressionNode::MatchExpression(node) => self.compile_match(node),
+            ExpressionNode::ForExpression(node) => {
+                // the iterator stays on the stack for the whole loop
+                self.compile_expression(&node.iterable)?;
+                self.emit(Opcode::OpIterInit, vec![]);
+
+                let start = self.scope().instructions.0.len();
+                let iter_next_pos = self.emit(Opcode::OpIterNext, vec![9999, node.variables.len()]);
+
+                let symbols = node
+                    .variables
+                    .iter()
+                    .map(|variable| {
+                        self.symbol_table.define(&variable.value);
+                        self.symbol_table.resolve(&variable.value).unwrap()
+                    })
+                    .collect::<Vec<_>>();
+                for symbol in symbols.iter().rev() {
+                    self.store_symbol(symbol)?;
+                }
+
+                self.push_loop(start);
+                let body = self.compile_statements(&node.body.statements);
+                let lp = self.scope_mut().loops.pop().unwrap();
+                body?;
+
+                self.emit(Opcode::OpJump, vec![start]);
+
+                let end = self.scope().instructions.0.len();
+                self.change_operand(iter_next_pos, end);
+                for pos in lp.breaks {
+                    self.change_operand(pos, end);
+                }
+
+                self.emit(Opcode::OpPop, vec![]);
+                self.emit(Opcode::OpNull, vec![]);
+
+                Ok(())
+            }
             ExpressionNode::FunctionExpression(node) => {
                 self.enter_scope();
 
@@ -288,9 +476,23 @@ impl Compiler {
                     self.symbol_table.define_function_name(name);
                 }
 
+                // defaults are filled in before the body runs, each one only
+                // sees the parameters before it
+                let mut entries = vec![];
                 for parameter in &node.parameters {
-                    self.symbol_table.define(&parameter.value);
+                    if let Some(default) = &parameter.default {
+                        entries.push(self.scope().instructions.0.len());
+                        self.compile_expression(default)?;
+                    }
+
+                    self.symbol_table.define(&parameter.ident.value);
+
+                    if parameter.default.is_some() {
+                        let symbol = self.symbol_table.resolve(&parameter.ident.value).unwrap();
+                        self.store_symbol(&symbol)?;
+                    }
                 }
+                entries.push(self.scope().instructions.0.len());
 
                 self.compile_statements(&node.body.statements)?;
 
@@ -302,16 +504,34 @@ impl Compiler {
                 }
 
                 let scope = self.symbol_table.current.clone();
-                let num_locals = self.symbol_table.current.lock().unwrap().count;
-                let instructions = self.leave_scope();
+                let (num_locals, locals) = {
+                    let current = self.symbol_table.current.lock().unwrap();
+                    (current.count, current.names.clone())
+                };
+                let CompilerScope {
+                    mut instructions,
+                    lines,
+                    ..
+                } = self.leave_scope();
+                mark_tail_calls(&mut instructions);
 
                 let free_symbols = &scope.lock().unwrap().free_symbols;
                 for s in free_symbols {
-                    self.load_symbol(s);
+                    self.capture_symbol(s);
                 }
 
-                let compiled_fn =
-                    Object::CompiledFunction(instructions, num_locals, node.parameters.len());
+                let rest = node.parameters.last().is_some_and(|p| p.rest);
+                let compiled_fn = Object::CompiledFunction(Rc::new(CompiledFunction {
+                    instructions,
+                    num_locals,
+                    num_parameters: node.parameters.len() - rest as usize,
+                    rest,
+                    name: node.name.clone(),
+                    lines,
+                    entries,
+                    locals,
+                    free: free_symbols.iter().map(|s| s.name.clone()).collect(),
+                }));
 
                 let operand = self.add_constant(compiled_fn);
 
@@ -338,6 +558,22 @@ impl Compiler {
 
                 Ok(())
             }
+            ExpressionNode::SliceExpression(node) => {
+                self.compile_expression(&node.left)?;
+
+                for bound in [&node.start, &node.end] {
+                    match bound {
+                        Some(bound) => self.compile_expression(bound)?,
+                        None => {
+                            self.emit(Opcode::OpNull, vec![]);
+                        }
+                    }
+                }
+
+                self.emit(Opcode::OpSlice, vec![]);
+
+                Ok(())
+            }
             ExpressionNode::HashLiteral(node) => {
                 for item in &node.map {
                     self.compile_expression(&item.0)?;
@@ -351,7 +587,321 @@ impl Compiler {
         }
     }
 
+    // index targets compile every key and the value, then the OpSetIndex for
+    // the scope of the variable updates it and leaves the assigned value
+    fn compile_assign(&mut self, node: &AssignExpression) -> R {
+        let (target, keys) = node.path();
+        let ExpressionNode::Identifier(identifier) = target else {
+            return Err("invalid assignment target".to_string());
+        };
+
+        let Some(symbol) = self.symbol_table.resolve(&identifier.value) else {
+            return Err(format!(
+                "undefined variable {} at {}",
+                identifier.value, identifier.span
+            ));
+        };
+
+        let operator = match node.operator() {
+            Some(operator) => Some(
+                arithmetic_opcode(&operator)
+                    .ok_or_else(|| format!("unknown assignment operator {:?}", node.token))?,
+            ),
+            None => None,
+        };
+
+        if keys.is_empty() {
+            if let Some(operator) = operator {
+                self.load_symbol(&symbol);
+                self.compile_expression(&node.value)?;
+                self.emit(operator, vec![]);
+            } else {
+                self.compile_expression(&node.value)?;
+            }
+
+            self.store_symbol(&symbol)?;
+            self.load_symbol(&symbol);
+
+            return Ok(());
+        }
+
+        for key in &keys {
+            self.compile_expression(key)?;
+        }
+        self.compile_expression(&node.value)?;
+
+        let set_index = match symbol.scope {
+            symbol_table::Scope::Global => Opcode::OpSetIndexGlobal,
+            symbol_table::Scope::Local => Opcode::OpSetIndexLocal,
+            symbol_table::Scope::Free => Opcode::OpSetIndexFree,
+            // reports the same error as a plain assignment
+            _ => return self.store_symbol(&symbol),
+        };
+        let operator = operator.unwrap_or(Opcode::OpNoop);
+        self.emit(set_index, vec![symbol.index, keys.len(), operator as usize]);
+
+        Ok(())
+    }
+
+    // `a && b` jumps to the false branch as soon as an operand is falsy,
+    // `a || b` negates each operand so it can jump to the true branch
+    fn compile_logical(&mut self, node: &InfixExpression) -> R {
+        let negate = node.operator.is(&Token::OR);
+        let mut jumps = vec![];
+
+        for operand in [&node.left, &node.right] {
+            self.compile_expression(operand)?;
+            if negate {
+                self.emit(Opcode::OpBang, vec![]);
+            }
+            jumps.push(self.emit(Opcode::OpJumpNotTruthy, vec![9999]));
+        }
+
+        let (fallthrough, shortcut) = if negate {
+            (Opcode::OpFalse, Opcode::OpTrue)
+        } else {
+            (Opcode::OpTrue, Opcode::OpFalse)
+        };
+
+        self.emit(fallthrough, vec![]);
+        let jump_pos = self.emit(Opcode::OpJump, vec![9999]);
+        self.scope_mut().depth -= 1;
+
+        let shortcut_pos = self.scope().instructions.0.len();
+        for jump in jumps {
+            self.change_operand(jump, shortcut_pos);
+        }
+
+        self.emit(shortcut, vec![]);
+
+        let after_pos = self.scope().instructions.0.len();
+        self.change_operand(jump_pos, after_pos);
+
+        Ok(())
+    }
+
+    // stores the value on top of the stack into the names a `let` pattern
+    // binds, the unpacking opcodes push the parts in reverse so the first one
+    // ends up on top
+    fn compile_destructure(&mut self, pattern: &Pattern) -> R {
+        self.compile_unpack(pattern, false)
+    }
+
+    // like `compile_destructure`, for a value already known to match the
+    // pattern so that its literals only have to be dropped
+    fn compile_unpack(&mut self, pattern: &Pattern, matched: bool) -> R {
+        let patterns = match pattern {
+            Pattern::Wildcard(_) => {
+                self.emit(Opcode::OpPop, vec![]);
+                return Ok(());
+            }
+            Pattern::Binding(ident) => {
+                let symbol = self.symbol_table.resolve(&ident.value).unwrap();
+                return self.store_symbol(&symbol);
+            }
+            Pattern::Literal(_) if matched => {
+                self.emit(Opcode::OpPop, vec![]);
+                return Ok(());
+            }
+            Pattern::Literal(literal) => {
+                return Err(format!("unsupported pattern in let: {}", literal.string()))
+            }
+            Pattern::Array(elements, rest, _) => {
+                self.emit(
+                    Opcode::OpUnpackArray,
+                    vec![elements.len(), rest.is_some() as usize],
+                );
+                elements.iter().chain(rest.as_deref()).collect::<Vec<_>>()
+            }
+            Pattern::Hash(entries, _) => {
+                for (key, _) in entries {
+                    self.compile_expression(key)?;
+                }
+                self.emit(Opcode::OpUnpackHash, vec![entries.len()]);
+                entries.iter().map(|(_, entry)| entry).collect()
+            }
+        };
+
+        for pattern in patterns {
+            self.compile_unpack(pattern, matched)?;
+        }
+
+        Ok(())
+    }
+
+    // the subject stays on the stack while each arm tests a copy of it, a failed
+    // pattern jumps to the pad that pops whatever it left behind and falls
+    // through to the next arm. only once the whole pattern matched is another
+    // copy taken apart into the names it binds, which like any name the arm
+    // defines are only visible inside it
+    fn compile_match(&mut self, node: &MatchExpression) -> R {
+        self.compile_expression(&node.subject)?;
+        let subject_depth = self.scope().depth;
+
+        let mut end_jumps = vec![];
+        for arm in &node.arms {
+            let mut fails = vec![];
+
+            self.emit(Opcode::OpDup, vec![]);
+            self.compile_pattern(&arm.pattern, 1, &mut fails)?;
+
+            let names = self.symbol_table.scope_names();
+            let bindings = arm.pattern.bindings();
+            if !bindings.is_empty() {
+                for ident in bindings {
+                    self.symbol_table.define(&ident.value);
+                }
+
+                self.emit(Opcode::OpDup, vec![]);
+                self.compile_unpack(&arm.pattern, true)?;
+            }
+
+            self.emit(Opcode::OpPop, vec![]);
+            let body = if arm.body.statements.is_empty() {
+                self.emit(Opcode::OpNull, vec![]);
+                Ok(())
+            } else {
+                self.compile_statements(&arm.body.statements)
+                    .map(|_| self.keep_block_value())
+            };
+            self.symbol_table.restore_names(names);
+            body?;
+
+            end_jumps.push(self.emit(Opcode::OpJump, vec![9999]));
+
+            let deepest = fails.iter().map(|(_, depth)| *depth).max().unwrap_or(0);
+            self.scope_mut().depth = subject_depth + deepest;
+            for depth in (0..=deepest).rev() {
+                let pad = self.scope().instructions.0.len();
+                for (pos, _) in fails.iter().filter(|(_, d)| *d == depth) {
+                    self.change_operand(*pos, pad);
+                }
+
+                if depth > 0 {
+                    self.emit(Opcode::OpPop, vec![]);
+                }
+            }
+        }
+
+        self.emit(Opcode::OpPop, vec![]);
+        self.emit(Opcode::OpNull, vec![]);
+
+        let end = self.scope().instructions.0.len();
+        for pos in end_jumps {
+            self.change_operand(pos, end);
+        }
+
+        Ok(())
+    }
+
+    // tests the value on top of the stack, which sits `depth` values above the
+    // subject, and consumes it on success. every failure jump is recorded with
+    // the number of values it leaves above the subject
+    fn compile_pattern(
+        &mut self,
+        pattern: &Pattern,
+        depth: usize,
+        fails: &mut Vec<(usize, usize)>,
+    ) -> R {
+        match pattern {
+            Pattern::Wildcard(_) | Pattern::Binding(_) => {
+                self.emit(Opcode::OpPop, vec![]);
+            }
+            Pattern::Literal(literal) => {
+                self.compile_expression(literal)?;
+                self.emit(Opcode::OpEqual, vec![]);
+                fails.push((self.emit(Opcode::OpJumpNotTruthy, vec![9999]), depth - 1));
+            }
+            Pattern::Array(elements, rest, _) => {
+                self.emit(
+                    Opcode::OpMatchArray,
+                    vec![elements.len(), rest.is_some() as usize],
+                );
+                fails.push((self.emit(Opcode::OpJumpNotTruthy, vec![9999]), depth));
+
+                for (i, element) in elements.iter().enumerate() {
+                    self.emit(Opcode::OpDup, vec![]);
+                    let index = self.add_constant(Object::Integer(i as i64));
+                    self.emit(Opcode::OpConstant, vec![index]);
+                    self.emit(Opcode::OpIndex, vec![]);
+
+                    self.compile_pattern(element, depth + 1, fails)?;
+                }
+
+                if let Some(rest) = rest {
+                    self.emit(Opcode::OpDup, vec![]);
+                    let start = self.add_constant(Object::Integer(elements.len() as i64));
+                    self.emit(Opcode::OpConstant, vec![start]);
+                    self.emit(Opcode::OpNull, vec![]);
+                    self.emit(Opcode::OpSlice, vec![]);
+
+                    self.compile_pattern(rest, depth + 1, fails)?;
+                }
+
+                self.emit(Opcode::OpPop, vec![]);
+            }
+            Pattern::Hash(entries, _) => {
+                self.emit(Opcode::OpMatchHash, vec![]);
+                fails.push((self.emit(Opcode::OpJumpNotTruthy, vec![9999]), depth));
+
+                for (key, entry) in entries {
+                    self.emit(Opcode::OpDup, vec![]);
+                    self.compile_expression(key)?;
+                    self.emit(Opcode::OpHasKey, vec![]);
+                    fails.push((self.emit(Opcode::OpJumpNotTruthy, vec![9999]), depth));
+
+                    self.emit(Opcode::OpDup, vec![]);
+                    self.compile_expression(key)?;
+                    self.emit(Opcode::OpIndex, vec![]);
+
+                    self.compile_pattern(entry, depth + 1, fails)?;
+                }
+
+                self.emit(Opcode::OpPop, vec![]);
+            }
+        }
+
+        Ok(())
+    }
+
+    // leaves the value of a block's trailing expression on the stack, or null
+    // when it ends in a statement that produces no value
+    fn keep_block_value(&mut self) {
+        if self.scope().last_instruction.0.is(&Opcode::OpPop) {
+            self.remove_last();
+        } else {
+            self.emit(Opcode::OpNull, vec![]);
+        }
+    }
+
+    // jumps to `target` after popping the values above `depth`, the code
+    // after it is only reached through other jumps so it keeps the depth
+    fn exit_to(&mut self, depth: usize, target: usize) -> usize {
+        let current = self.scope().depth;
+        for _ in depth..current {
+            self.emit(Opcode::OpPop, vec![]);
+        }
+
+        let pos = self.emit(Opcode::OpJump, vec![target]);
+        self.scope_mut().depth = current;
+
+        pos
+    }
+
+    fn push_loop(&mut self, start: usize) {
+        let depth = self.scope().depth;
+
+        self.scope_mut().loops.push(Loop {
+            start,
+            depth,
+            breaks: vec![],
+        });
+    }
+
     fn emit(&mut self, op: Opcode, operands: Vec<usize>) -> usize {
+        let (pops, pushes) = op.stack_effect(&operands);
+        self.scope_mut().depth = self.scope().depth.saturating_sub(pops) + pushes;
+
         let instruction = make(op, &operands);
         let pos = self.add_instruction(instruction);
 
@@ -375,7 +925,7 @@ impl Compiler {
     }
 
     fn change_operand(&mut self, op_pos: usize, operand: usize) {
-        let op: Opcode = self.scope().instructions.0[op_pos].into();
+        let op = Opcode::try_from(self.scope().instructions.0[op_pos]).unwrap();
 
         let instruction = make(op, &[operand]);
 
@@ -383,8 +933,14 @@ impl Compiler {
     }
 
     fn remove_last(&mut self) {
-        let r = ..self.scope().last_instruction.1;
-        self.scope_mut().instructions.0 = self.scope().instructions.0[r].to_vec();
+        let (op, end) = self.scope().last_instruction;
+        let (operands, _) =
+            read_operands(&op.definition(), &self.scope().instructions.0[end + 1..]);
+        let (pops, pushes) = op.stack_effect(&operands);
+        self.scope_mut().depth = self.scope().depth + pops - pushes;
+
+        self.scope_mut().instructions.0 = self.scope().instructions.0[..end].to_vec();
+        self.scope_mut().lines.retain(|(offset, _)| *offset < end);
 
         self.scope_mut().last_instruction = self.scope().previous_instruction;
     }
@@ -398,6 +954,12 @@ impl Compiler {
 
     fn add_instruction(&mut self, mut instruction: Vec<u8>) -> usize {
         let pos = self.scope().instructions.0.len();
+
+        let line = self.line;
+        let lines = &mut self.scope_mut().lines;
+        if lines.last().is_none_or(|(_, last)| *last != line) {
+            lines.push((pos, line));
+        }
         self.scope_mut().instructions.0.append(&mut instruction);
         pos
     }
@@ -412,6 +974,47 @@ impl Compiler {
         Bytecode {
             instructions: self.scope().instructions.clone(),
             constants: self.constants.clone(),
+            lines: self.scope().lines.clone(),
+            globals: self.symbol_table.global_names(),
+        }
+    }
+}
+
+fn arithmetic_opcode(operator: &Token) -> Option<Opcode> {
+    match operator {
+        Token::PLUS => Some(Opcode::OpAdd),
+        Token::MINUS => Some(Opcode::OpSub),
+        Token::ASTERISK => Some(Opcode::OpMul),
+        Token::SLASH => Some(Opcode::OpDiv),
+        Token::PERCENT => Some(Opcode::OpMod),
+        _ => None,
+    }
+}
+
+// a call followed by a return, directly or by jumping to one, is in tail
+// position and can reuse the frame of the function making it
+fn mark_tail_calls(instructions: &mut Instructions) {
+    let mut ip = 0;
+    while ip < instructions.0.len() {
+        let op = Opcode::try_from(instructions.0[ip]).unwrap();
+        let (_, read) = read_operands(&op.definition(), &instructions.0[ip + 1..]);
+
+        if op.is(&Opcode::OpCall) && returns_at(&instructions.0, ip + 1 + read) {
+            instructions.0[ip] = Opcode::OpTailCall.into();
+        }
+
+        ip += 1 + read;
+    }
+}
+
+fn returns_at(instructions: &[u8], mut ip: usize) -> bool {
+    // loops jump back to their condition or OpIterNext, never to another
+    // jump, so following jumps always ends
+    loop {
+        match instructions.get(ip).map(|op| Opcode::try_from(*op)) {
+            Some(Ok(Opcode::OpReturnValue)) => return true,
+            Some(Ok(Opcode::OpJump)) => ip = read_u16(&instructions[ip + 1..]),
+            _ => return false,
         }
     }
 }
@@ -424,17 +1027,29 @@ impl Default for Compiler {
 
 #[cfg(test)]
 pub mod test {
-    use std::any::Any;
+    use std::{any::Any, rc::Rc};
 
     use rstest::rstest;
 
     use crate::{
         code::{make::make, Instructions, Opcode},
         compiler::Compiler,
-        object::{test::test_object, Object},
-        parser::Parser,
+        object::{test::test_object, CompiledFunction, Object},
+        parser::{parse_error::ParseError, Parser},
     };
 
+    fn compiled_function(
+        instructions: Instructions,
+        num_locals: usize,
+        num_parameters: usize,
+    ) -> Object {
+        Object::CompiledFunction(Rc::new(CompiledFunction::new(
+            instructions,
+            num_locals,
+            num_parameters,
+        )))
+    }
+
     #[rstest]
     #[case("1 + 2",vec![1,2],vec![
         make(Opcode::OpConstant,&[0]),
@@ -460,6 +1075,12 @@ pub mod test {
         make(Opcode::OpDiv,&[]),
         make(Opcode::OpPop,&[]),
     ])]
+    #[case("5 % 2",vec![5,2],vec![
+        make(Opcode::OpConstant,&[0]),
+        make(Opcode::OpConstant,&[1]),
+        make(Opcode::OpMod,&[]),
+        make(Opcode::OpPop,&[]),
+    ])]
     #[case("1; 2",vec![1,2],vec![
         make(Opcode::OpConstant,&[0]),
         make(Opcode::OpPop,&[]),
@@ -483,7 +1104,31 @@ pub mod test {
     #[case("true", vec![], vec![make(Opcode::OpTrue, &[]), make(Opcode::OpPop, &[])])]
     #[case("false", vec![], vec![make(Opcode::OpFalse, &[]), make(Opcode::OpPop, &[])])]
     #[case("1 > 2", vec![1, 2], vec![make(Opcode::OpConstant, &[0]), make(Opcode::OpConstant, &[1]), make(Opcode::OpGreaterThan, &[]), make(Opcode::OpPop, &[])])]
-    #[case("1 < 2", vec![2, 1], vec![make(Opcode::OpConstant, &[0]), make(Opcode::OpConstant, &[1]), make(Opcode::OpGreaterThan, &[]), make(Opcode::OpPop, &[])])]
+    #[case("1 < 2", vec![1, 2], vec![make(Opcode::OpConstant, &[0]), make(Opcode::OpConstant, &[1]), make(Opcode::OpLessThan, &[]), make(Opcode::OpPop, &[])])]
+    #[case("1 >= 2", vec![1, 2], vec![make(Opcode::OpConstant, &[0]), make(Opcode::OpConstant, &[1]), make(Opcode::OpGreaterEqual, &[]), make(Opcode::OpPop, &[])])]
+    #[case("1 <= 2", vec![1, 2], vec![make(Opcode::OpConstant, &[0]), make(Opcode::OpConstant, &[1]), make(Opcode::OpLessEqual, &[]), make(Opcode::OpPop, &[])])]
+    #[case("true && false", vec![], vec![
+        make(Opcode::OpTrue, &[]),
+        make(Opcode::OpJumpNotTruthy, &[12]),
+        make(Opcode::OpFalse, &[]),
+        make(Opcode::OpJumpNotTruthy, &[12]),
+        make(Opcode::OpTrue, &[]),
+        make(Opcode::OpJump, &[13]),
+        make(Opcode::OpFalse, &[]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("true || false", vec![], vec![
+        make(Opcode::OpTrue, &[]),
+        make(Opcode::OpBang, &[]),
+        make(Opcode::OpJumpNotTruthy, &[14]),
+        make(Opcode::OpFalse, &[]),
+        make(Opcode::OpBang, &[]),
+        make(Opcode::OpJumpNotTruthy, &[14]),
+        make(Opcode::OpFalse, &[]),
+        make(Opcode::OpJump, &[15]),
+        make(Opcode::OpTrue, &[]),
+        make(Opcode::OpPop, &[]),
+    ])]
     #[case("1 == 2", vec![1, 2], vec![make(Opcode::OpConstant, &[0]), make(Opcode::OpConstant, &[1]), make(Opcode::OpEqual, &[]), make(Opcode::OpPop, &[])])]
     #[case("1 != 2", vec![1, 2], vec![make(Opcode::OpConstant, &[0]), make(Opcode::OpConstant, &[1]), make(Opcode::OpNotEqual, &[]), make(Opcode::OpPop, &[])])]
     #[case("true == true", vec![], vec![make(Opcode::OpTrue, &[]), make(Opcode::OpTrue, &[]), make(Opcode::OpEqual, &[]), make(Opcode::OpPop, &[])])]
@@ -526,6 +1171,122 @@ pub mod test {
         test_compiler(input, constants, instructions)
     }
 
+    #[rstest]
+    #[case("match (1) { 2 => 3, x => x }", vec![1, 2, 3], vec![
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpDup, &[]),
+        make(Opcode::OpConstant, &[1]),
+        make(Opcode::OpEqual, &[]),
+        make(Opcode::OpJumpNotTruthy, &[18]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpConstant, &[2]),
+        make(Opcode::OpJump, &[33]),
+        make(Opcode::OpDup, &[]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpDup, &[]),
+        make(Opcode::OpSetGlobal, &[0]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpGetGlobal, &[0]),
+        make(Opcode::OpJump, &[33]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpNull, &[]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("match (1) { [_] => 2 }", vec![1, 0, 2], vec![
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpDup, &[]),
+        make(Opcode::OpMatchArray, &[1, 0]),
+        make(Opcode::OpJumpNotTruthy, &[24]),
+        make(Opcode::OpDup, &[]),
+        make(Opcode::OpConstant, &[1]),
+        make(Opcode::OpIndex, &[]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpConstant, &[2]),
+        make(Opcode::OpJump, &[27]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpNull, &[]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    fn test_match(
+        #[case] input: &str,
+        #[case] constants: Vec<i64>,
+        #[case] instructions: Vec<Vec<u8>>,
+    ) {
+        test_compiler(input, constants, instructions)
+    }
+
+    #[rstest]
+    #[case("while (true) { 10; break; }; 20;", vec![10, 20], vec![
+        make(Opcode::OpTrue, &[]),
+        make(Opcode::OpJumpNotTruthy, &[14]),
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpJump, &[14]),
+        make(Opcode::OpJump, &[0]),
+        make(Opcode::OpConstant, &[1]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("while (false) { continue; }", vec![], vec![
+        make(Opcode::OpFalse, &[]),
+        make(Opcode::OpJumpNotTruthy, &[10]),
+        make(Opcode::OpJump, &[0]),
+        make(Opcode::OpJump, &[0]),
+    ])]
+    fn test_while(
+        #[case] input: &str,
+        #[case] constants: Vec<i64>,
+        #[case] instructions: Vec<Vec<u8>>,
+    ) {
+        test_compiler(input, constants, instructions)
+    }
+
+    #[rstest]
+    #[case("0..=5; 1..2", vec![0, 5, 1, 2], vec![
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpConstant, &[1]),
+        make(Opcode::OpRange, &[1]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpConstant, &[2]),
+        make(Opcode::OpConstant, &[3]),
+        make(Opcode::OpRange, &[0]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("for (x in [1]) { x }", vec![1], vec![
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpArray, &[1]),
+        make(Opcode::OpIterInit, &[]),
+        make(Opcode::OpIterNext, &[21, 1]),
+        make(Opcode::OpSetGlobal, &[0]),
+        make(Opcode::OpGetGlobal, &[0]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpJump, &[7]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpNull, &[]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("for (k, v in {}) { break }", vec![], vec![
+        make(Opcode::OpHash, &[0]),
+        make(Opcode::OpIterInit, &[]),
+        make(Opcode::OpIterNext, &[20, 2]),
+        make(Opcode::OpSetGlobal, &[1]),
+        make(Opcode::OpSetGlobal, &[0]),
+        make(Opcode::OpJump, &[20]),
+        make(Opcode::OpJump, &[4]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpNull, &[]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    fn test_for(
+        #[case] input: &str,
+        #[case] constants: Vec<i64>,
+        #[case] instructions: Vec<Vec<u8>>,
+    ) {
+        test_compiler(input, constants, instructions)
+    }
+
     #[rstest]
     #[case("let one = 1; let two = 2;", vec![1, 2], vec![
         make(Opcode::OpConstant, &[0]),
@@ -547,6 +1308,23 @@ pub mod test {
         make(Opcode::OpGetGlobal, &[1]),
         make(Opcode::OpPop, &[]),
     ])]
+    #[case("let [a, ...b] = [1];", vec![1], vec![
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpArray, &[1]),
+        make(Opcode::OpUnpackArray, &[1, 1]),
+        make(Opcode::OpSetGlobal, &[0]),
+        make(Opcode::OpSetGlobal, &[1]),
+    ])]
+    #[case("let {1: [x, _]} = {}; x;", vec![1], vec![
+        make(Opcode::OpHash, &[0]),
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpUnpackHash, &[1]),
+        make(Opcode::OpUnpackArray, &[2, 0]),
+        make(Opcode::OpSetGlobal, &[0]),
+        make(Opcode::OpPop, &[]),
+        make(Opcode::OpGetGlobal, &[0]),
+        make(Opcode::OpPop, &[]),
+    ])]
     fn test_global_let_statements(
         #[case] input: &str,
         #[case] constants: Vec<i64>,
@@ -555,6 +1333,96 @@ pub mod test {
         test_compiler(input, constants, instructions)
     }
 
+    #[rstest]
+    #[case("let x = 1; x = 2;", vec![Object::Integer(1), Object::Integer(2)], vec![
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpSetGlobal, &[0]),
+        make(Opcode::OpConstant, &[1]),
+        make(Opcode::OpSetGlobal, &[0]),
+        make(Opcode::OpGetGlobal, &[0]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("let x = 1; x += 2;", vec![Object::Integer(1), Object::Integer(2)], vec![
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpSetGlobal, &[0]),
+        make(Opcode::OpGetGlobal, &[0]),
+        make(Opcode::OpConstant, &[1]),
+        make(Opcode::OpAdd, &[]),
+        make(Opcode::OpSetGlobal, &[0]),
+        make(Opcode::OpGetGlobal, &[0]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("let a = [1]; a[0] *= 3;", vec![Object::Integer(1), Object::Integer(0), Object::Integer(3)], vec![
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpArray, &[1]),
+        make(Opcode::OpSetGlobal, &[0]),
+        make(Opcode::OpConstant, &[1]),
+        make(Opcode::OpConstant, &[2]),
+        make(Opcode::OpSetIndexGlobal, &[0, 1, Opcode::OpMul as usize]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("
+fn(a) {
+    fn() {
+        a = a - 1
+    }
+}
+", vec![
+    Object::Integer(1),
+    compiled_function(Instructions(vec![
+        make(Opcode::OpGetFree, &[0]),
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpSub, &[]),
+        make(Opcode::OpSetFree, &[0]),
+        make(Opcode::OpGetFree, &[0]),
+        make(Opcode::OpReturnValue, &[]),
+    ].into_iter().flatten().collect()), 0, 0),
+    compiled_function(Instructions(vec![
+        make(Opcode::OpCaptureLocal, &[0]),
+        make(Opcode::OpClosure, &[1, 1]),
+        make(Opcode::OpReturnValue, &[]),
+    ].into_iter().flatten().collect()), 1, 1)
+    ],
+    vec![
+        make(Opcode::OpClosure, &[2, 0]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    fn test_assignment(
+        #[case] input: &str,
+        #[case] constants: Vec<Object>,
+        #[case] instructions: Vec<Vec<u8>>,
+    ) {
+        test_compiler(input, constants, instructions)
+    }
+
+    #[rstest]
+    #[case("y = 1", "undefined variable y at 1:1")]
+    #[case("let a = []; b[0] += 1", "undefined variable b at 1:13")]
+    #[case("len = 1", "cannot assign to builtin len")]
+    #[case(
+        "let f = fn() { f = 1 }",
+        "cannot assign to function f inside its own body"
+    )]
+    #[case(
+        "let f = fn() { let g = fn() { f = 1 }; g(); f }",
+        "cannot assign to function f inside its own body"
+    )]
+    #[case(
+        "let f = fn() { fn() { fn() { f += 1 } } }",
+        "cannot assign to function f inside its own body"
+    )]
+    fn test_assignment_errors(#[case] input: &str, #[case] expected_error: &str) {
+        let mut parser = Parser::new(input.into());
+        let (program, errors) = parser.parse_program();
+
+        assert_eq!(errors, Vec::<ParseError>::new());
+
+        let mut compiler = Compiler::new();
+        let result = compiler.compile((&program).into());
+
+        assert_eq!(result, Err(expected_error.to_string()));
+    }
+
     #[rstest]
     #[case("\"monkey\"", vec!["monkey"], vec![
         make(Opcode::OpConstant, &[0]),
@@ -673,6 +1541,21 @@ pub mod test {
         make(Opcode::OpIndex, &[]),
         make(Opcode::OpPop, &[]),
     ])]
+    #[case("[1][:1]; [2][1:]", vec![1, 1, 2, 1], vec![
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpArray, &[1]),
+        make(Opcode::OpNull, &[]),
+        make(Opcode::OpConstant, &[1]),
+        make(Opcode::OpSlice, &[]),
+        make(Opcode::OpPop, &[]),
+
+        make(Opcode::OpConstant, &[2]),
+        make(Opcode::OpArray, &[1]),
+        make(Opcode::OpConstant, &[3]),
+        make(Opcode::OpNull, &[]),
+        make(Opcode::OpSlice, &[]),
+        make(Opcode::OpPop, &[]),
+    ])]
     fn test_index_expression(
         #[case] input: &str,
         #[case] constants: Vec<i64>,
@@ -682,7 +1565,7 @@ pub mod test {
     }
 
     #[rstest]
-    #[case("fn() {return 5+10}", vec![Object::Integer(5),Object::Integer(10), Object::CompiledFunction(Instructions(vec![
+    #[case("fn() {return 5+10}", vec![Object::Integer(5),Object::Integer(10), compiled_function(Instructions(vec![
         make(Opcode::OpConstant, &[0]),
         make(Opcode::OpConstant, &[1]),
         make(Opcode::OpAdd, &[]),
@@ -691,7 +1574,7 @@ pub mod test {
         make(Opcode::OpClosure, &[2, 0]),
         make(Opcode::OpPop, &[]),
     ])]
-    #[case("fn() {5+10}", vec![Object::Integer(5),Object::Integer(10), Object::CompiledFunction(Instructions(vec![
+    #[case("fn() {5+10}", vec![Object::Integer(5),Object::Integer(10), compiled_function(Instructions(vec![
         make(Opcode::OpConstant, &[0]),
         make(Opcode::OpConstant, &[1]),
         make(Opcode::OpAdd, &[]),
@@ -700,7 +1583,7 @@ pub mod test {
         make(Opcode::OpClosure, &[2, 0]),
         make(Opcode::OpPop, &[]),
     ])]
-    #[case("fn() {1;2}", vec![Object::Integer(1),Object::Integer(2), Object::CompiledFunction(Instructions(vec![
+    #[case("fn() {1;2}", vec![Object::Integer(1),Object::Integer(2), compiled_function(Instructions(vec![
         make(Opcode::OpConstant, &[0]),
         make(Opcode::OpPop, &[]),
         make(Opcode::OpConstant, &[1]),
@@ -709,12 +1592,48 @@ pub mod test {
         make(Opcode::OpClosure, &[2, 0]),
         make(Opcode::OpPop, &[]),
     ])]
-    #[case("fn() {}", vec![Object::CompiledFunction(Instructions(vec![
+    #[case("fn() {}", vec![compiled_function(Instructions(vec![
         make(Opcode::OpReturn, &[]),
     ].into_iter().flatten().collect()), 0, 0)], vec![
         make(Opcode::OpClosure, &[0, 0]),
         make(Opcode::OpPop, &[]),
     ])]
+    #[case("fn(a, b = 1) { a }", vec![Object::Integer(1), Object::CompiledFunction(Rc::new(CompiledFunction {
+        instructions: Instructions(vec![
+            make(Opcode::OpConstant, &[0]),
+            make(Opcode::OpSetLocal, &[1]),
+            make(Opcode::OpGetLocal, &[0]),
+            make(Opcode::OpReturnValue, &[]),
+        ].into_iter().flatten().collect()),
+        num_locals: 2,
+        num_parameters: 2,
+        rest: false,
+        name: None,
+        lines: vec![],
+        entries: vec![0, 5],
+        locals: vec![],
+        free: vec![],
+    }))], vec![
+        make(Opcode::OpClosure, &[1, 0]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("fn(a, ...b) { b }", vec![Object::CompiledFunction(Rc::new(CompiledFunction {
+        instructions: Instructions(vec![
+            make(Opcode::OpGetLocal, &[1]),
+            make(Opcode::OpReturnValue, &[]),
+        ].into_iter().flatten().collect()),
+        num_locals: 2,
+        num_parameters: 1,
+        rest: true,
+        name: None,
+        lines: vec![],
+        entries: vec![0],
+        locals: vec![],
+        free: vec![],
+    }))], vec![
+        make(Opcode::OpClosure, &[0, 0]),
+        make(Opcode::OpPop, &[]),
+    ])]
     fn test_functions(
         #[case] input: &str,
         #[case] constants: Vec<Object>,
@@ -724,7 +1643,7 @@ pub mod test {
     }
 
     #[rstest]
-    #[case("fn() {24}();", vec![Object::Integer(24), Object::CompiledFunction(Instructions(vec![
+    #[case("fn() {24}();", vec![Object::Integer(24), compiled_function(Instructions(vec![
         make(Opcode::OpConstant, &[0]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 0, 0)], vec![
@@ -733,7 +1652,7 @@ pub mod test {
         make(Opcode::OpPop, &[]),
     ])]
     #[case("let noArg = fn() {24};
-noArg();", vec![Object::Integer(24), Object::CompiledFunction(Instructions(vec![
+noArg();", vec![Object::Integer(24), compiled_function(Instructions(vec![
         make(Opcode::OpConstant, &[0]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 0, 0)], vec![
@@ -744,7 +1663,7 @@ noArg();", vec![Object::Integer(24), Object::CompiledFunction(Instructions(vec![
         make(Opcode::OpPop, &[]),
     ])]
     #[case("let oneArg = fn(a) { a }; 
-oneArg(24);", vec![Object::CompiledFunction(Instructions(vec![
+oneArg(24);", vec![compiled_function(Instructions(vec![
         make(Opcode::OpGetLocal, &[0]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 1, 1), Object::Integer(24)], vec![
@@ -756,7 +1675,7 @@ oneArg(24);", vec![Object::CompiledFunction(Instructions(vec![
         make(Opcode::OpPop, &[]),
     ])]
     #[case("let manyArg = fn(a, b, c) {a; b; c;};
-manyArg(24,25,26);", vec![Object::CompiledFunction(Instructions(vec![
+manyArg(24,25,26);", vec![compiled_function(Instructions(vec![
            make(Opcode::OpGetLocal, &[0]),
            make(Opcode::OpPop, &[]),
            make(Opcode::OpGetLocal, &[1]),
@@ -794,7 +1713,7 @@ manyArg(24,25,26);", vec![Object::CompiledFunction(Instructions(vec![
         let mut parser = Parser::new(input.into());
         let (program, errors) = parser.parse_program();
 
-        assert_eq!(errors, Vec::<String>::new());
+        assert_eq!(errors, Vec::<ParseError>::new());
 
         let mut compiler = Compiler::new();
         compiler
@@ -845,7 +1764,7 @@ manyArg(24,25,26);", vec![Object::CompiledFunction(Instructions(vec![
     #[case("
 let num = 55;
 fn() {num}
-", vec![Object::Integer(55), Object::CompiledFunction(Instructions(vec![
+", vec![Object::Integer(55), compiled_function(Instructions(vec![
         make(Opcode::OpGetGlobal, &[0]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 0, 0)], vec![
@@ -859,7 +1778,7 @@ fn() {
     let num = 55;
     num
 }
-", vec![Object::Integer(55), Object::CompiledFunction(Instructions(vec![
+", vec![Object::Integer(55), compiled_function(Instructions(vec![
         make(Opcode::OpConstant, &[0]),
         make(Opcode::OpSetLocal, &[0]),
         make(Opcode::OpGetLocal, &[0]),
@@ -874,7 +1793,7 @@ fn() {
     let b = 77;
     a + b
 }
-", vec![Object::Integer(55),Object::Integer(77), Object::CompiledFunction(Instructions(vec![
+", vec![Object::Integer(55),Object::Integer(77), compiled_function(Instructions(vec![
         make(Opcode::OpConstant, &[0]),
         make(Opcode::OpSetLocal, &[0]),
         make(Opcode::OpConstant, &[1]),
@@ -912,10 +1831,10 @@ push([], 1);
     ])]
     #[case("
 fn() { len([]) }
-", vec![Object::CompiledFunction(Instructions(vec![
+", vec![compiled_function(Instructions(vec![
         make(Opcode::OpGetBuiltin, &[0]),
         make(Opcode::OpArray, &[0]),
-        make(Opcode::OpCall, &[1]),
+        make(Opcode::OpTailCall, &[1]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 0, 0)], vec![
         make(Opcode::OpClosure, &[0, 0]),
@@ -937,14 +1856,14 @@ fn (a) {
     }
 }
 ", vec![
-    Object::CompiledFunction(Instructions(vec![
+    compiled_function(Instructions(vec![
         make(Opcode::OpGetFree, &[0]),
         make(Opcode::OpGetLocal, &[0]),
         make(Opcode::OpAdd, &[]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 1, 1),
-    Object::CompiledFunction(Instructions(vec![
-        make(Opcode::OpGetLocal, &[0]),
+    compiled_function(Instructions(vec![
+        make(Opcode::OpCaptureLocal, &[0]),
         make(Opcode::OpClosure, &[0, 1]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 1, 1)
@@ -962,7 +1881,7 @@ fn(a) {
     }
 }
 ", vec![
-    Object::CompiledFunction(Instructions(vec![
+    compiled_function(Instructions(vec![
         make(Opcode::OpGetFree, &[0]),
         make(Opcode::OpGetFree, &[1]),
         make(Opcode::OpAdd, &[]),
@@ -970,14 +1889,36 @@ fn(a) {
         make(Opcode::OpAdd, &[]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 1, 1),
-    Object::CompiledFunction(Instructions(vec![
-        make(Opcode::OpGetFree, &[0]),
-        make(Opcode::OpGetLocal, &[0]),
+    compiled_function(Instructions(vec![
+        make(Opcode::OpCaptureFree, &[0]),
+        make(Opcode::OpCaptureLocal, &[0]),
         make(Opcode::OpClosure, &[0, 2]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 1, 1),
-    Object::CompiledFunction(Instructions(vec![
-        make(Opcode::OpGetLocal, &[0]),
+    compiled_function(Instructions(vec![
+        make(Opcode::OpCaptureLocal, &[0]),
+        make(Opcode::OpClosure, &[1, 1]),
+        make(Opcode::OpReturnValue, &[]),
+    ].into_iter().flatten().collect()), 1, 1)
+    ],
+    vec![
+        make(Opcode::OpClosure, &[2, 0]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("
+fn(a) {
+    fn() { a = 1 }
+}
+", vec![
+    Object::Integer(1),
+    compiled_function(Instructions(vec![
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpSetFree, &[0]),
+        make(Opcode::OpGetFree, &[0]),
+        make(Opcode::OpReturnValue, &[]),
+    ].into_iter().flatten().collect()), 0, 0),
+    compiled_function(Instructions(vec![
+        make(Opcode::OpCaptureLocal, &[0]),
         make(Opcode::OpClosure, &[1, 1]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 1, 1)
@@ -1000,12 +1941,12 @@ let countDown = fn(x) { countDown(x - 1); };
 countDown(1);
 ", vec![
     Object::Integer(1),
-    Object::CompiledFunction(Instructions(vec![
+    compiled_function(Instructions(vec![
         make(Opcode::OpCurrentClosure, &[]),
         make(Opcode::OpGetLocal, &[0]),
         make(Opcode::OpConstant, &[0]),
         make(Opcode::OpSub, &[]),
-        make(Opcode::OpCall, &[1]),
+        make(Opcode::OpTailCall, &[1]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 1, 1),
     Object::Integer(1),
@@ -1026,21 +1967,21 @@ let wrapper = fn() {
 wrapper();
 ", vec![
     Object::Integer(1),
-    Object::CompiledFunction(Instructions(vec![
+    compiled_function(Instructions(vec![
         make(Opcode::OpCurrentClosure, &[]),
         make(Opcode::OpGetLocal, &[0]),
         make(Opcode::OpConstant, &[0]),
         make(Opcode::OpSub, &[]),
-        make(Opcode::OpCall, &[1]),
+        make(Opcode::OpTailCall, &[1]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 1, 1),
     Object::Integer(1),
-    Object::CompiledFunction(Instructions(vec![
+    compiled_function(Instructions(vec![
         make(Opcode::OpClosure, &[1, 0]),
         make(Opcode::OpSetLocal, &[0]),
         make(Opcode::OpGetLocal, &[0]),
         make(Opcode::OpConstant, &[2]),
-        make(Opcode::OpCall, &[1]),
+        make(Opcode::OpTailCall, &[1]),
         make(Opcode::OpReturnValue, &[]),
     ].into_iter().flatten().collect()), 1, 0),
     ],
@@ -1058,4 +1999,83 @@ wrapper();
     ) {
         test_compiler(input, constants, instructions)
     }
+
+    #[test]
+    fn test_line_table() {
+        let input = "let add = fn(a, b) {
+    let c = a +
+        b;
+    c
+};
+add(1, 2);";
+        let mut parser = Parser::new(input.into());
+        let (program, errors) = parser.parse_program();
+        assert_eq!(errors, Vec::<ParseError>::new());
+
+        let mut compiler = Compiler::new();
+        compiler
+            .compile((&program).into())
+            .expect("Failed to compile program");
+        let bytecode = compiler.bytecode();
+
+        // OpClosure and OpSetGlobal, then the call and its arguments
+        assert_eq!(bytecode.lines, vec![(0, 1), (7, 6)]);
+
+        let Object::CompiledFunction(function) = &bytecode.constants[0] else {
+            panic!("expected a compiled function");
+        };
+        assert_eq!(function.name.as_deref(), Some("add"));
+        // `b` sits on its own line inside `a + b`, OpAdd belongs to the infix
+        // expression starting on line 2
+        assert_eq!(function.lines, vec![(0, 2), (2, 3), (4, 2), (7, 4)]);
+        assert_eq!(function.line(5), Some(2));
+        assert_eq!(function.line(100), Some(4));
+    }
+
+    #[rstest]
+    #[case("
+fn(f) { if (true) { f() } else { f() + 1 } }
+", vec![
+    Object::Integer(1),
+    compiled_function(Instructions(vec![
+        make(Opcode::OpTrue, &[]),
+        make(Opcode::OpJumpNotTruthy, &[11]),
+        make(Opcode::OpGetLocal, &[0]),
+        make(Opcode::OpTailCall, &[0]),
+        make(Opcode::OpJump, &[19]),
+        make(Opcode::OpGetLocal, &[0]),
+        make(Opcode::OpCall, &[0]),
+        make(Opcode::OpConstant, &[0]),
+        make(Opcode::OpAdd, &[]),
+        make(Opcode::OpReturnValue, &[]),
+    ].into_iter().flatten().collect()), 1, 1),
+    ],
+    vec![
+        make(Opcode::OpClosure, &[1, 0]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    #[case("
+fn(f) { let x = f(); return f(x); }
+", vec![
+    compiled_function(Instructions(vec![
+        make(Opcode::OpGetLocal, &[0]),
+        make(Opcode::OpCall, &[0]),
+        make(Opcode::OpSetLocal, &[1]),
+        make(Opcode::OpGetLocal, &[0]),
+        make(Opcode::OpGetLocal, &[1]),
+        make(Opcode::OpTailCall, &[1]),
+        make(Opcode::OpReturnValue, &[]),
+    ].into_iter().flatten().collect()), 2, 1),
+    ],
+    vec![
+        make(Opcode::OpClosure, &[0, 0]),
+        make(Opcode::OpPop, &[]),
+    ])]
+    fn test_tail_calls(
+        #[case] input: &str,
+        #[case] constants: Vec<Object>,
+        #[case] instructions: Vec<Vec<u8>>,
+    ) {
+        test_compiler(input, constants, instructions)
+    }
 }
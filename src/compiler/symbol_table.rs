@@ -35,6 +35,8 @@ pub struct SymbolScope {
     map: HashMap<String, Symbol>,
     pub count: usize,
     pub free_symbols: Vec<Symbol>,
+    // the name of every symbol defined here by index, shadowed ones included
+    pub names: Vec<String>,
 }
 
 impl SymbolTable {
@@ -71,9 +73,31 @@ impl SymbolTable {
         let symbol = Symbol::new(name.into(), scope, current.count);
         current.count += 1;
 
+        current.names.push(name.into());
         current.map.insert(name.into(), symbol);
     }
 
+    // the names the current scope resolves, to put back with `restore_names`
+    // once a block defining names of its own is done
+    pub fn scope_names(&self) -> HashMap<String, Symbol> {
+        self.current.lock().unwrap().map.clone()
+    }
+
+    // forgets the names defined since `scope_names`, free variables resolved in
+    // the meantime stay as they still refer to the same captured values
+    pub fn restore_names(&mut self, names: HashMap<String, Symbol>) {
+        let mut current = self.current.lock().unwrap();
+
+        current
+            .map
+            .retain(|name, symbol| symbol.scope == Scope::Free || names.contains_key(name));
+        current.map.extend(names);
+    }
+
+    pub fn global_names(&self) -> Vec<String> {
+        self.stack[0].lock().unwrap().names.clone()
+    }
+
     pub fn define_builtin(&mut self, index: usize, name: &str) {
         let mut current = self.current.lock().unwrap();
         let symbol = Symbol::new(name.into(), Scope::Builtin, index);
@@ -92,6 +116,26 @@ impl SymbolTable {
         let mut current = self.current.lock().unwrap();
         current.resolve(name)
     }
+
+    // follows a free variable out to the scope that defines it
+    pub fn original(&self, symbol: &Symbol) -> Symbol {
+        let mut scope = self.current.clone();
+        let mut symbol = symbol.clone();
+
+        while symbol.scope == Scope::Free {
+            let outer = {
+                let current = scope.lock().unwrap();
+                symbol = current.free_symbols[symbol.index].clone();
+                current
+                    .outer
+                    .clone()
+                    .expect("free variables have an outer scope")
+            };
+            scope = outer;
+        }
+
+        symbol
+    }
 }
 
 impl SymbolScope {
@@ -101,6 +145,7 @@ impl SymbolScope {
             map: HashMap::new(),
             count: 0,
             free_symbols: vec![],
+            names: vec![],
         }))
     }
 
@@ -109,15 +154,11 @@ impl SymbolScope {
             return Some(s.clone());
         }
 
-        let Some(outer) = self.outer.clone() else {
-            return None;
-        };
+        let outer = self.outer.clone()?;
 
         let mut outer = outer.lock().unwrap();
 
-        let Some(symbol) = outer.resolve(name) else {
-            return None;
-        };
+        let symbol = outer.resolve(name)?;
 
         if matches!(symbol.scope, Scope::Global | Scope::Builtin) {
             return Some(symbol.clone());
@@ -0,0 +1,278 @@
+// programs that have to give the same result, or fail with the same error, in
+// the tree-walking evaluator and in the vm
+use rstest::rstest;
+
+use crate::{compile, evaluate, parse, run};
+
+fn evaluated(input: &str) -> String {
+    let program = parse(input).expect("failed to parse");
+
+    evaluate(&program).to_string()
+}
+
+fn executed(input: &str) -> String {
+    let program = parse(input).expect("failed to parse");
+
+    match compile(&program).and_then(|bytecode| run(bytecode).map_err(|e| e.to_string())) {
+        Ok(result) => result.to_string(),
+        Err(e) => format!("ERROR: {}", e),
+    }
+}
+
+#[rstest]
+// array indexing
+#[case("[1, 2, 3][0]", "1")]
+#[case("[1, 2, 3][-1]", "3")]
+#[case("[1, 2, 3][-3]", "1")]
+#[case("[1, 2, 3][3]", "null")]
+#[case("[1, 2, 3][-4]", "null")]
+#[case("[][0]", "null")]
+#[case("let a = [[1, 2], [3]]; a[-2][-1]", "2")]
+// string indexing
+#[case("\"abc\"[0]", "a")]
+#[case("\"héllo\"[1]", "é")]
+#[case("\"abc\"[-1]", "c")]
+#[case("\"abc\"[3]", "null")]
+#[case("\"abc\"[-4]", "null")]
+#[case("\"abc\"[1] == \"b\"", "true")]
+#[case("\"abc\"[1] != \"b\"", "false")]
+#[case("len(\"abc\"[2])", "1")]
+#[case("len(\"héllo\")", "5")]
+#[case("let s = \"héllo\"; s[len(s) - 1]", "o")]
+#[case("let s = \"héllo\"; len(s[0:len(s)])", "5")]
+// ranges and slices
+#[case("(5..8)[0]", "5")]
+#[case("(5..8)[-1]", "7")]
+#[case("(5..8)[3]", "null")]
+#[case("\"hello\"[1:-1]", "ell")]
+#[case("[1, 2, 3][-2:][0]", "2")]
+// hashes
+#[case("{\"a\": 1}[\"a\"]", "1")]
+#[case("{\"a\": 1}[\"b\"]", "null")]
+#[case("{1: true}[1]", "true")]
+// assignment through negative indices
+#[case("let a = [1, 2, 3]; a[-1] = 9; a", "[1, 2, 9]")]
+#[case("let a = [[1], [2]]; a[-1][-1] += 5; a[1][0]", "7")]
+// match
+#[case("match (1.0) { 1 => \"one\", _ => \"other\" }", "one")]
+#[case("match (\"1\") { 1 => \"one\", _ => \"other\" }", "other")]
+#[case("match ([1, 2, 3]) { [a, b] => a + b, [a, _, c] => a + c }", "4")]
+#[case(
+    "match ({\"a\": 1, \"b\": 2}) { {\"a\": a, \"c\": c} => c, {\"b\": b} => b }",
+    "2"
+)]
+#[case("match (5) { [x] => x }", "null")]
+#[case("match (3) { n => n }", "3")]
+// a failed arm binds nothing and bindings stay inside their arm
+#[case("let a = 1; match (5) { [a] => 0, _ => a }", "1")]
+#[case("let a = 1; match ([5, 6]) { [a, 7] => 0, _ => a }", "1")]
+#[case(
+    "let a = 1; match ({\"x\": 5}) { {\"x\": a, \"y\": 6} => 0, _ => a }",
+    "1"
+)]
+#[case(
+    "let a = 1; let f = fn() { match ([5, 6]) { [a, 7] => 0, _ => a } }; f()",
+    "1"
+)]
+#[case(
+    "let f = fn() { let a = 1; match ({\"x\": 5}) { {\"x\": a, \"y\": 6} => 0, _ => a } }; f()",
+    "1"
+)]
+#[case("let a = 1; match (5) { a => a }; a", "1")]
+#[case("let m = 0; match (5) { n => { let m = n; m } }; m", "0")]
+#[case("let m = 0; match (5) { n => { m = n } }; m", "5")]
+#[case("let f = match (5) { a => fn() { a } }; f()", "5")]
+#[case("let f = fn(x) { match (x) { a => fn() { a } } }; f(5)()", "5")]
+#[case("match ([1, 2, 3]) { [h, ...t] => t }", "[2, 3]")]
+#[case("match ([]) { [h, ..._] => h, _ => 0 }", "0")]
+// destructuring
+#[case("let [a, b] = [1, 2]; a + b", "3")]
+#[case("let [first, ...rest] = [1, 2, 3]; rest", "[2, 3]")]
+#[case("let [x, ...y] = [1]; y", "[]")]
+#[case(
+    "let {\"name\": n, \"age\": a} = {\"name\": \"ann\", \"age\": 30}; n",
+    "ann"
+)]
+#[case("let [a, {\"k\": [b, _]}] = [1, {\"k\": [2, 3]}]; a + b", "3")]
+#[case(
+    "let f = fn(p) { let [x, y] = p; let {1: z} = {1: x * y}; z }; f([3, 4])",
+    "12"
+)]
+#[case("let [a, b] = [1, 2]; let [a, b] = [b, a]; [a, b]", "[2, 1]")]
+// default and rest parameters
+#[case("let f = fn(x, y = 10) { x + y }; f(1)", "11")]
+#[case("let f = fn(x, y = 10) { x + y }; f(1, 2)", "3")]
+#[case(
+    "let f = fn(x = 1, y = x * 2) { [x, y] }; [f(), f(5)]",
+    "[[1, 2], [5, 10]]"
+)]
+#[case(
+    "let f = fn(first, ...others) { others }; [f(1, 2, 3), f(1)]",
+    "[[2, 3], []]"
+)]
+#[case(
+    "let f = fn(a, b = 2, ...c) { [a, b, c] }; [f(1), f(1, 5, 6, 7)]",
+    "[[1, 2, []], [1, 5, [6, 7]]]"
+)]
+#[case(
+    "let f = fn(a, b = 2, ...c) { [a, b, c] }; let g = fn(n) { if (n > 0) { f(n, 5, 6, 7) } else { f(n) } }; [g(0), g(1)]",
+    "[[0, 2, []], [1, 5, [6, 7]]]"
+)]
+#[case("let x = 5; let f = fn(x = x) { x }; f()", "5")]
+#[case("let n = 3; let f = fn(m) { fn(x = n + m) { x } }; f(1)()", "4")]
+#[case("let sum = fn(xs, acc = 0) { if (len(xs) == 0) { acc } else { sum(rest(xs), acc + first(xs)) } }; sum([1, 2, 3])", "6")]
+#[case(
+    "let f = fn(...xs) { let s = 0; for (x in xs) { s += x }; s }; f(1, 2, 3, 4)",
+    "10"
+)]
+// captured variables are shared
+#[case("let make = fn() { let c = 0; [fn() { c += 1; c }, fn() { c }] }; let fs = make(); fs[0](); fs[0](); fs[1]()", "2")]
+#[case("let f = fn() { let x = 1; let g = fn() { x }; x = 5; g() }; f()", "5")]
+#[case(
+    "let f = fn() { let x = 0; let inc = fn() { x += 1 }; inc(); inc(); x }; f()",
+    "2"
+)]
+#[case(
+    "let f = fn() { let n = 0; fn() { fn() { n += 1; n } } }; let inc = f()(); inc(); inc()",
+    "2"
+)]
+#[case("let f = fn() { let n = 0; [fn() { fn() { n += 10 } }, fn() { n }] }; let fs = f(); fs[0]()(); fs[1]()", "10")]
+#[case("let make = fn() { let c = 0; fn() { c += 1; c } }; let a = make(); let b = make(); a(); a(); [a(), b()]", "[3, 1]")]
+#[case(
+    "let adder = fn(n) { fn() { n += 1; n } }; let a = adder(10); a(); a()",
+    "12"
+)]
+#[case(
+    "let f = fn() { let fs = []; for (i in 0..3) { fs = push(fs, fn() { i }) }; fs[0]() }; f()",
+    "2"
+)]
+#[case(
+    "let f = fn(n) { let g = fn() { if (n > 0) { n -= 1; g() } else { n } }; g() }; f(3)",
+    "0"
+)]
+#[case(
+    "let f = fn() { let g = fn(n) { if (n == 0) { 0 } else { fn() { g(n - 1) }() } }; g(3) }; f()",
+    "0"
+)]
+// values are shared until one copy changes
+#[case("let a = [1, 2]; let b = a; b[0] = 5; [a, b]", "[[1, 2], [5, 2]]")]
+#[case("let a = [1]; let b = push(a, 2); [a, b]", "[[1], [1, 2]]")]
+#[case(
+    "let h = {\"k\": [1]}; let g = h; g[\"k\"][0] = 2; [h[\"k\"], g[\"k\"]]",
+    "[[1], [2]]"
+)]
+#[case("let f = fn(a) { a[0] = 9; a }; let a = [1]; [f(a), a]", "[[9], [1]]")]
+#[case("let a = [1, 2, 3]; a[len(a) - 1] = a[0]; a", "[1, 2, 1]")]
+#[case("let f = fn() { let a = [1, 2]; a[1] += a[0]; a }; f()", "[1, 3]")]
+#[case(
+    "let f = fn() { let h = {\"n\": 1}; let g = fn(k) { h[k] = h[\"n\"] + 1 }; g(\"a\"); h[\"a\"] }; f()",
+    "2"
+)]
+// logical operators on any operands
+#[case("1 && 2", "true")]
+#[case("1.5 || 0", "true")]
+#[case("2.5 && false", "false")]
+#[case("false || 3", "true")]
+// comparisons evaluate their operands left to right
+#[case(
+    "let log = []; let a = fn() { log = push(log, 1); 1 }; let b = fn() { log = push(log, 2); 2 }; [a() <= b(), a() < b(), log]",
+    "[true, true, [1, 2, 1, 2]]"
+)]
+// loop control and return leave the expressions they are in
+#[case(
+    "let f = fn() { let i = 0; while (i < 3000) { i += 1; let a = [1, if (true) { continue } else { 3 }]; }; i }; f()",
+    "3000"
+)]
+#[case(
+    "let r = []; for (i in 0..4) { r = push(r, [i, if (i == 2) { continue } else { 0 }]) }; r",
+    "[[0, 0], [1, 0], [3, 0]]"
+)]
+#[case(
+    "let r = 0; for (x in [1, 2, 3]) { r = r + (if (x == 2) { break } else { x }) }; r",
+    "1"
+)]
+#[case(
+    "let f = fn(a, b) { a + b }; let s = 0; for (x in [1, 2, 3]) { s += f(x, if (x == 2) { continue } else { 10 }) }; s",
+    "24"
+)]
+#[case(
+    "let s = 0; for (x in [1, 2]) { s += {x: if (x == 1) { continue } else { 5 }}[x] }; s",
+    "5"
+)]
+#[case(
+    "let n = 0; while (n < 5) { n += 1; [7, 8][if (n == 3) { break } else { 0 }] }; n",
+    "3"
+)]
+#[case(
+    "let f = fn() { let a = [1, if (true) { return 5 } else { 0 }]; 9 }; f()",
+    "5"
+)]
+// errors
+#[case("1[0]", "ERROR: index operator not supported: INTEGER[INTEGER]")]
+#[case("[1][\"a\"]", "ERROR: index operator not supported: ARRAY[STRING]")]
+#[case("\"a\"[true]", "ERROR: index operator not supported: STRING[BOOLEAN]")]
+#[case("{}[[]]", "ERROR: unusable as hash key: ARRAY")]
+#[case("{[]: 1}", "ERROR: unusable as hash key: ARRAY")]
+#[case("let a = [1]; a[-2] = 0", "ERROR: index out of bounds: -2")]
+#[case("fn(x) { x }(1, 2)", "ERROR: wrong number of arguments: want=1, got=2")]
+#[case(
+    "fn(x, y = 1) { x }()",
+    "ERROR: wrong number of arguments: want=1 to 2, got=0"
+)]
+#[case(
+    "fn(x, y = 1) { x }(1, 2, 3)",
+    "ERROR: wrong number of arguments: want=1 to 2, got=3"
+)]
+#[case(
+    "fn(x, ...y) { x }()",
+    "ERROR: wrong number of arguments: want=at least 1, got=0"
+)]
+#[case("fn(x = 1 / 0) { x }()", "ERROR: division by zero")]
+#[case(
+    "9223372036854775807 + 1",
+    "ERROR: integer overflow: 9223372036854775807 + 1"
+)]
+#[case(
+    "let a = -9223372036854775807 - 1; a % -1",
+    "ERROR: integer overflow: -9223372036854775808 % -1"
+)]
+#[case(
+    "let a = [4611686018427387904]; a[0] *= 2",
+    "ERROR: integer overflow: 4611686018427387904 * 2"
+)]
+#[case(
+    "let a = -9223372036854775807 - 1; -a",
+    "ERROR: integer overflow: -(-9223372036854775808)"
+)]
+#[case("let [a] = \"a\"", "ERROR: cannot destructure STRING as ARRAY")]
+#[case("let [a, b] = [1]", "ERROR: expected 2 elements, got 1")]
+#[case("let [a, b, ...c] = [1]", "ERROR: expected at least 2 elements, got 1")]
+#[case("let {\"x\": x} = {}", "ERROR: key not found: x")]
+#[case(
+    "let {\"x\": [x]} = {\"x\": 1}",
+    "ERROR: cannot destructure INTEGER as ARRAY"
+)]
+#[case("let {1: x} = [1]", "ERROR: cannot destructure ARRAY as HASH")]
+#[case(
+    "let f = fn() { let [a, b] = [1, 2, 3]; a }; f()",
+    "ERROR: expected 2 elements, got 3"
+)]
+#[case(
+    "let f = fn() { f = 1 }; f()",
+    "ERROR: cannot assign to function f inside its own body"
+)]
+#[case(
+    "let f = fn() { let g = fn() { f = 1 }; g(); f }; f()",
+    "ERROR: cannot assign to function f inside its own body"
+)]
+#[case("let f = fn(f) { f = 1; f }; f(2)", "1")]
+#[case("let f = fn() { let f = 2; f += 1; f }; f()", "3")]
+#[case("let f = fn() { 1 }; let g = fn() { f = 2 }; g(); f", "2")]
+#[case(
+    "let f = fn(n) { if (n > 0) { f(n - 1) } else { n } }; let g = f; f = 1; g(3)",
+    "0"
+)]
+fn test_engines_agree(#[case] input: &str, #[case] expected: &str) {
+    assert_eq!(evaluated(input), expected, "evaluator: {}", input);
+    assert_eq!(executed(input), expected, "vm: {}", input);
+}
@@ -0,0 +1,25 @@
+use crate::{parser::parse_error::ParseError, vm::runtime_error::RuntimeError};
+
+// why a chunk of source given to the engine failed, kept structured so
+// callers decide how to show it
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineError {
+    Parse(Vec<ParseError>),
+    Compile(String),
+    Runtime(RuntimeError),
+}
+
+impl EngineError {
+    // `source` is the chunk that failed, parser errors point into it
+    pub fn render(&self, source: &str) -> String {
+        match self {
+            EngineError::Parse(errors) => errors
+                .iter()
+                .map(|e| e.render(source))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            EngineError::Compile(message) => format!("error: {}", message),
+            EngineError::Runtime(error) => error.render(),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::mem;
+
+use crate::{compiler::Compiler, object::Object, parser::Parser, vm::Vm};
+
+use self::engine_error::EngineError;
+
+pub mod engine_error;
+
+/// Compiles and runs Monkey source on a persistent [`Vm`].
+///
+/// Globals and constants defined by one call to [`Engine::run`] stay visible to
+/// the next, which is what the REPL and embedders evaluating a script in
+/// several chunks want.
+pub struct Engine {
+    compiler: Compiler,
+    vm: Vm,
+}
+
+impl Engine {
+    pub fn new() -> Engine {
+        Engine {
+            compiler: Compiler::new(),
+            vm: Vm::new(),
+        }
+    }
+
+    pub fn run(&mut self, source: &str) -> Result<Object, EngineError> {
+        let mut parser = Parser::new(source.into());
+
+        let (program, errors) = parser.parse_program();
+
+        if !errors.is_empty() {
+            return Err(EngineError::Parse(errors));
+        }
+
+        self.compiler = mem::take(&mut self.compiler).new_from();
+        self.compiler
+            .compile((&program).into())
+            .map_err(EngineError::Compile)?;
+
+        self.vm.with_bytecode(self.compiler.bytecode());
+
+        self.vm.run().map_err(EngineError::Runtime)?;
+
+        Ok(self.vm.last_popped().from_ref())
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::{
+        object::test::test_object,
+        vm::runtime_error::{RuntimeErrorKind, TraceFrame},
+    };
+
+    use super::{engine_error::EngineError, Engine};
+
+    #[rstest]
+    #[case(vec!["1 + 2"], 3)]
+    #[case(vec!["let a = 5;", "a * 2"], 10)]
+    #[case(vec!["let double = fn(x) { x * 2 };", "let a = 4;", "double(a)"], 8)]
+    #[case(vec!["let a = [1];", "a[5] = 0", "a[0]"], 1)]
+    #[case(vec!["let a = [1];", "a[0] += \"x\"", "a[0]"], 1)]
+    fn test_engine_keeps_state(#[case] chunks: Vec<&str>, #[case] expected: i64) {
+        let mut engine = Engine::new();
+
+        let mut result = None;
+        // failing chunks leave the state as it was
+        for chunk in chunks {
+            result = engine.run(chunk).ok().or(result);
+        }
+
+        test_object(&result.unwrap(), &expected);
+    }
+
+    #[test]
+    fn test_engine_reports_errors() {
+        let mut engine = Engine::new();
+
+        assert_eq!(
+            engine.run("let a = 1;\na + foo"),
+            Err(EngineError::Compile("undefined variable foo at 2:5".into()))
+        );
+
+        let Err(EngineError::Parse(errors)) = engine.run("let = 1;") else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(errors[0].message(), "expected identifier, found '='");
+
+        let Err(EngineError::Runtime(error)) = engine.run("let f = fn() { 1 + true };\nf()") else {
+            panic!("expected a runtime error");
+        };
+        assert_eq!(
+            error.kind,
+            RuntimeErrorKind::TypeMismatch("INTEGER", "BOOLEAN")
+        );
+        assert_eq!(
+            error.frames.first(),
+            Some(&TraceFrame {
+                function: "<main>".into(),
+                offset: 10,
+                line: Some(2),
+            })
+        );
+    }
+}
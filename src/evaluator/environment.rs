@@ -6,6 +6,9 @@ use crate::object::Object;
 pub struct Environment {
     hm: HashMap<String, Object>,
     outer: Option<Rc<Mutex<Environment>>>,
+    // the name a function defined by `let` sees itself by, which its body
+    // cannot assign to, the same as in the compiler
+    function: Option<String>,
 }
 
 impl<'a> Environment {
@@ -14,9 +17,37 @@ impl<'a> Environment {
         Rc::new(Mutex::new(Environment {
             hm: HashMap::new(),
             outer: None,
+            function: None,
         }))
     }
 
+    // a scope holding only `function` under its own name
+    pub fn function(
+        outer: &Rc<Mutex<Environment>>,
+        name: &str,
+        function: impl FnOnce(Rc<Mutex<Environment>>) -> Object,
+    ) -> Object {
+        let env = outer.enclose();
+
+        let function = function(env.clone());
+        let mut inner = env.lock().unwrap();
+        inner.hm.insert(name.to_string(), function.clone());
+        inner.function = Some(name.to_string());
+
+        function
+    }
+
+    // whether `name` refers to the function whose body is running
+    pub fn is_function_name(&self, name: &str) -> bool {
+        if self.hm.contains_key(name) {
+            return self.function.as_deref() == Some(name);
+        }
+
+        self.outer
+            .as_ref()
+            .is_some_and(|outer| outer.lock().unwrap().is_function_name(name))
+    }
+
     pub fn get(&self, name: &'a str) -> Option<Object> {
         self.hm
             .get(name)
@@ -27,6 +58,30 @@ impl<'a> Environment {
     pub fn set(&mut self, name: String, value: Object) {
         self.hm.insert(name, value);
     }
+
+    // moves a variable out of the closest scope that defines it, leaving null
+    pub fn take(&mut self, name: &str) -> Option<Object> {
+        if let Some(slot) = self.hm.get_mut(name) {
+            return Some(std::mem::replace(slot, Object::Null));
+        }
+
+        self.outer
+            .as_ref()
+            .and_then(|outer| outer.lock().unwrap().take(name))
+    }
+
+    // rebinds an existing variable in the closest scope that defines it
+    pub fn assign(&mut self, name: &str, value: Object) -> Result<(), String> {
+        if let Some(slot) = self.hm.get_mut(name) {
+            *slot = value;
+            return Ok(());
+        }
+
+        match &self.outer {
+            Some(outer) => outer.lock().unwrap().assign(name, value),
+            None => Err(format!("identifier not found: {}", name)),
+        }
+    }
 }
 
 pub trait Enclose {
@@ -38,6 +93,7 @@ impl Enclose for Rc<Mutex<Environment>> {
         Rc::new(Mutex::new(Environment {
             hm: HashMap::new(),
             outer: Some(self.clone()),
+            function: None,
         }))
     }
 }
@@ -85,6 +141,28 @@ mod test {
         assert!(env.lock().unwrap().get("test").is_some())
     }
 
+    #[test]
+    fn test_assign_updates_defining_scope() {
+        let env = Environment::new();
+
+        env.lock()
+            .unwrap()
+            .set("test".to_string(), Object::Integer(5));
+
+        let inner = env.enclose();
+
+        assert!(inner
+            .lock()
+            .unwrap()
+            .assign("test", Object::Integer(6))
+            .is_ok());
+        assert_eq!(env.lock().unwrap().get("test"), Some(Object::Integer(6)));
+        assert_eq!(
+            inner.lock().unwrap().assign("other", Object::Null),
+            Err("identifier not found: other".to_string())
+        );
+    }
+
     #[test]
     fn test_enclose_orignal_env_is_unaffected() {
         let env = Environment::new();
@@ -2,10 +2,13 @@ use std::{collections::HashMap, rc::Rc, sync::Mutex};
 
 use crate::{
     ast::{
-        hash_literal::HashLiteral, if_expression::IfExpression, ExpressionNode, Node, StatementNode,
+        assign_expression::AssignExpression, for_expression::ForExpression,
+        hash_literal::HashLiteral, if_expression::IfExpression, match_expression::MatchExpression,
+        pattern::Pattern, while_statement::WhileStatement, AstNode, ExpressionNode, Node,
+        StatementNode,
     },
     builtin::get_builtin_by_name,
-    object::Object,
+    object::{check_arity, iterator::ObjectIterator, Object},
     tokens::token::Token,
 };
 
@@ -25,17 +28,23 @@ fn eval_statement(env: &Rc<Mutex<Environment>>, statement: &StatementNode) -> Ob
     match statement {
         StatementNode::LetStatement(statement) => {
             let value = eval(env, (&statement.value).into());
-            if value.is_error() {
+            if value.interrupts() {
                 return value;
             }
-            env.lock()
-                .unwrap()
-                .set(statement.identifier.value.to_string(), value);
+
+            let mut bindings = vec![];
+            if let Err(e) = destructure(env, &statement.pattern, value, &mut bindings) {
+                return Object::Error(e);
+            }
+
+            for (name, value) in bindings {
+                env.lock().unwrap().set(name, value);
+            }
             Object::Null
         }
         StatementNode::ReturnStatement(statement) => {
             let value = eval(env, (&statement.return_value).into());
-            if value.is_error() {
+            if value.interrupts() {
                 return value;
             }
 
@@ -45,9 +54,57 @@ fn eval_statement(env: &Rc<Mutex<Environment>>, statement: &StatementNode) -> Ob
             eval_expression(env, &expression.expression)
         }
         StatementNode::BlockStatement(block) => eval_statements(env, &block.statements),
+        StatementNode::WhileStatement(statement) => eval_while_statement(env, statement),
+        StatementNode::BreakStatement(_) => Object::Break,
+        StatementNode::ContinueStatement(_) => Object::Continue,
     }
 }
 
+fn eval_while_statement(env: &Rc<Mutex<Environment>>, statement: &WhileStatement) -> Object {
+    loop {
+        let condition = eval_expression(env, &statement.condition);
+        if condition.interrupts() {
+            return condition;
+        }
+
+        if !condition.is_truthy() {
+            return Object::Null;
+        }
+
+        match eval_statements(env, &statement.body.statements) {
+            Object::Break => return Object::Null,
+            result if result.is_return() || result.is_error() => return result,
+            _ => {}
+        }
+    }
+}
+
+fn eval_for_expression(env: &Rc<Mutex<Environment>>, expression: &ForExpression) -> Object {
+    let iterable = eval_expression(env, &expression.iterable);
+    if iterable.interrupts() {
+        return iterable;
+    }
+
+    let mut iterator = match ObjectIterator::new(iterable) {
+        Ok(iterator) => iterator,
+        Err(e) => return Object::Error(e),
+    };
+
+    while let Some(values) = iterator.next_bindings(expression.variables.len()) {
+        for (variable, value) in expression.variables.iter().zip(values) {
+            env.lock().unwrap().set(variable.value.to_string(), value);
+        }
+
+        match eval_statements(env, &expression.body.statements) {
+            Object::Break => break,
+            result if result.is_return() || result.is_error() => return result,
+            _ => {}
+        }
+    }
+
+    Object::Null
+}
+
 fn eval_expression(env: &Rc<Mutex<Environment>>, expression: &ExpressionNode) -> Object {
     match expression {
         ExpressionNode::Identifier(i) => {
@@ -62,11 +119,12 @@ fn eval_expression(env: &Rc<Mutex<Environment>>, expression: &ExpressionNode) ->
             Object::Error(format!("identifier not found: {}", i.value))
         }
         ExpressionNode::IntegerLiteral(i) => i.value.into(),
+        ExpressionNode::FloatLiteral(f) => f.value.into(),
         ExpressionNode::BooleanLiteral(i) => i.value.into(),
         ExpressionNode::StringLiteral(i) => (&i.value).into(),
         ExpressionNode::PrefixExpression(i) => {
             let right = eval(env, i.right.as_ref().into());
-            if right.is_error() {
+            if right.interrupts() {
                 return right;
             }
 
@@ -74,57 +132,153 @@ fn eval_expression(env: &Rc<Mutex<Environment>>, expression: &ExpressionNode) ->
         }
         ExpressionNode::InfixExpression(i) => {
             let left = eval(env, i.left.as_ref().into());
-            if left.is_error() {
+            if left.interrupts() {
                 return left;
             }
 
+            if let Some(result) = eval_short_circuit(&i.operator, &left) {
+                return result;
+            }
+
             let right = eval(env, i.right.as_ref().into());
-            if right.is_error() {
+            if right.interrupts() {
                 return right;
             }
 
             eval_infix(&i.operator, left, right)
         }
         ExpressionNode::IfExpression(expression) => eval_if_expression(env, expression),
-        ExpressionNode::FunctionExpression(expression) => Object::Function(
-            expression.parameters.clone(),
-            expression.body.clone(),
-            env.clone(),
-        ),
+        ExpressionNode::MatchExpression(expression) => eval_match_expression(env, expression),
+        ExpressionNode::ForExpression(expression) => eval_for_expression(env, expression),
+        ExpressionNode::FunctionExpression(expression) => {
+            let function =
+                |env| Object::Function(expression.parameters.clone(), expression.body.clone(), env);
+
+            match &expression.name {
+                Some(name) => Environment::function(env, name, function),
+                None => function(env.clone()),
+            }
+        }
         ExpressionNode::CallExpression(expression) => {
             let function = eval_expression(env, &expression.function);
-            if function.is_error() {
+            if function.interrupts() {
                 return function;
             }
 
             let arguments = eval_expressions(env, &expression.arguments);
-            if let Some(Object::Error(e)) = arguments.first() {
-                return Object::Error(e.to_string());
+            if let Some(interrupt) = arguments.first().filter(|a| a.interrupts()) {
+                return interrupt.clone();
             }
 
             call_function(function, arguments)
         }
         ExpressionNode::ArrayLiteral(array) => {
             let arguments = eval_expressions(env, &array.expressions);
-            if let Some(Object::Error(e)) = arguments.first() {
-                return Object::Error(e.to_string());
+            if let Some(interrupt) = arguments.first().filter(|a| a.interrupts()) {
+                return interrupt.clone();
             }
 
-            Object::Array(arguments)
+            arguments.into()
         }
         ExpressionNode::IndexExpresssion(expression) => {
             let left = eval_expression(env, &expression.left);
-            if left.is_error() {
+            if left.interrupts() {
                 return left;
             }
             let right = eval_expression(env, &expression.right);
-            if right.is_error() {
+            if right.interrupts() {
                 return right;
             }
 
             eval_index(left, right)
         }
+        ExpressionNode::SliceExpression(expression) => {
+            let mut operands = vec![];
+            for operand in [
+                Some(&expression.left),
+                expression.start.as_ref(),
+                expression.end.as_ref(),
+            ] {
+                let value = operand.map_or(Object::Null, |o| eval_expression(env, o));
+                if value.interrupts() {
+                    return value;
+                }
+                operands.push(value);
+            }
+
+            eval_slice(&operands[0], &operands[1], &operands[2])
+        }
         ExpressionNode::HashLiteral(expression) => eval_hash_literal(env, expression),
+        ExpressionNode::AssignExpression(expression) => eval_assign(env, expression),
+    }
+}
+
+fn eval_assign(env: &Rc<Mutex<Environment>>, expression: &AssignExpression) -> Object {
+    let (target, keys) = expression.path();
+    let ExpressionNode::Identifier(identifier) = target else {
+        return Object::Error("invalid assignment target".to_string());
+    };
+
+    if env.lock().unwrap().get(&identifier.value).is_none() {
+        if get_builtin_by_name(&identifier.value).is_some() {
+            return Object::Error(format!("cannot assign to builtin {}", identifier.value));
+        }
+        return Object::Error(format!("identifier not found: {}", identifier.value));
+    }
+    if env.lock().unwrap().is_function_name(&identifier.value) {
+        return Object::Error(format!(
+            "cannot assign to function {} inside its own body",
+            identifier.value
+        ));
+    }
+
+    let mut path = vec![];
+    for key in keys {
+        let key = eval_expression(env, key);
+        if key.interrupts() {
+            return key;
+        }
+        path.push(key);
+    }
+
+    let value = eval_expression(env, &expression.value);
+    if value.interrupts() {
+        return value;
+    }
+
+    // the variable is moved out for the update so a value nothing else
+    // shares is changed in place instead of copied
+    let mut root = env.lock().unwrap().take(&identifier.value).unwrap();
+    let result = update_path(&mut root, &path, expression.operator(), value);
+
+    match env.lock().unwrap().assign(&identifier.value, root) {
+        Ok(()) => result,
+        Err(e) => Object::Error(e),
+    }
+}
+
+// assigns `value` at `path` in `root`, combined with the value there first
+// for compound operators, and leaves `root` as it was on failure
+fn update_path(
+    root: &mut Object,
+    path: &[Object],
+    operator: Option<Token>,
+    value: Object,
+) -> Object {
+    let value = match operator {
+        Some(operator) => match root.get_path(path) {
+            Ok(current) => eval_infix(&operator, current.clone(), value),
+            Err(e) => return e.into(),
+        },
+        None => value,
+    };
+    if value.is_error() {
+        return value;
+    }
+
+    match root.set_path(path, value.clone()) {
+        Ok(()) => value,
+        Err(e) => e.into(),
     }
 }
 
@@ -133,49 +287,31 @@ fn eval_hash_literal(env: &Rc<Mutex<Environment>>, expression: &HashLiteral) ->
 
     for (key, value) in expression.map.iter() {
         let key = eval_expression(env, key);
-        if key.is_error() {
+        if key.interrupts() {
             return key;
         }
 
         if !key.hashable() {
-            return Object::Error(format!("unusable hash key: {}", key));
+            return Object::Error(format!("unusable as hash key: {}", key.type_str()));
         }
 
         let value = eval_expression(env, value);
-        if value.is_error() {
+        if value.interrupts() {
             return value;
         }
 
         hm.insert(key, value);
     }
 
-    Object::Hash(hm)
+    hm.into()
 }
 
 fn eval_index(left: Object, right: Object) -> Object {
-    match (left, right) {
-        (Object::Array(array), Object::Integer(index)) => {
-            let Some(index) = usize::try_from(index).ok() else {
-                return Object::Null;
-            };
-            array.into_iter().nth(index).unwrap_or(Object::Null)
-        }
-        (Object::Hash(hash), right) => {
-            if !right.hashable() {
-                return Object::Error(format!("unusable as hash key: {}", right.type_str()));
-            }
+    left.index(&right).unwrap_or_else(Object::from)
+}
 
-            hash.get(&right).unwrap_or(&Object::Null).to_owned()
-        }
-        (Object::Array(_), right) => Object::Error(format!(
-            "index operator not supported with: {}",
-            right.type_str()
-        )),
-        (left, _) => Object::Error(format!(
-            "index operator not supported on: {}",
-            left.type_str()
-        )),
-    }
+fn eval_slice(left: &Object, start: &Object, end: &Object) -> Object {
+    left.slice(start, end).unwrap_or_else(Object::Error)
 }
 
 fn call_function(function: Object, args: Vec<Object>) -> Object {
@@ -183,14 +319,44 @@ fn call_function(function: Object, args: Vec<Object>) -> Object {
         return builtin.0(args);
     }
 
-    let Object::Function(identifiers, body, env) = function else {
+    let Object::Function(parameters, body, env) = function else {
         return Object::Error(format!("not a function: {}", function.type_str()));
     };
 
+    let required = parameters
+        .iter()
+        .filter(|p| !p.rest && p.default.is_none())
+        .count();
+    let max = match parameters.last() {
+        Some(p) if p.rest => None,
+        _ => Some(parameters.len()),
+    };
+    if let Err(e) = check_arity(required, max, args.len()) {
+        return e.into();
+    }
+
     let env = env.enclose();
 
-    for (identifier, value) in identifiers.iter().zip(args) {
-        env.lock().unwrap().set(identifier.value.to_string(), value);
+    // defaults are evaluated in the call, after the parameters before them
+    let mut args = args.into_iter();
+    for parameter in &parameters {
+        let value = match (parameter.rest, args.next(), &parameter.default) {
+            (true, first, _) => first
+                .into_iter()
+                .chain(args.by_ref())
+                .collect::<Vec<_>>()
+                .into(),
+            (false, Some(arg), _) => arg,
+            (false, None, Some(default)) => eval_expression(&env, default),
+            (false, None, None) => unreachable!(),
+        };
+        if value.interrupts() {
+            return value;
+        }
+
+        env.lock()
+            .unwrap()
+            .set(parameter.ident.value.to_string(), value);
     }
 
     let result = eval_statements(&env, &body.statements);
@@ -202,7 +368,7 @@ fn eval_expressions(env: &Rc<Mutex<Environment>>, expressions: &[ExpressionNode]
 
     for exp in expressions {
         let result = eval_expression(env, exp);
-        if result.is_error() {
+        if result.interrupts() {
             return vec![result];
         }
         results.push(result)
@@ -213,7 +379,7 @@ fn eval_expressions(env: &Rc<Mutex<Environment>>, expressions: &[ExpressionNode]
 
 fn eval_if_expression(env: &Rc<Mutex<Environment>>, if_expression: &IfExpression) -> Object {
     let condition = eval(env, if_expression.condition.as_ref().into());
-    if condition.is_error() {
+    if condition.interrupts() {
         return condition;
     }
 
@@ -228,26 +394,192 @@ fn eval_if_expression(env: &Rc<Mutex<Environment>>, if_expression: &IfExpression
     Object::Null
 }
 
+fn eval_match_expression(env: &Rc<Mutex<Environment>>, expression: &MatchExpression) -> Object {
+    let subject = eval(env, expression.subject.as_ref().into());
+    if subject.interrupts() {
+        return subject;
+    }
+
+    for arm in &expression.arms {
+        let mut bindings = vec![];
+        if !match_pattern(env, &arm.pattern, &subject, &mut bindings) {
+            continue;
+        }
+
+        // the bindings, and anything else the arm defines, stay inside it
+        let env = env.enclose();
+        for (name, value) in bindings {
+            env.lock().unwrap().set(name, value);
+        }
+
+        return eval_statements(&env, &arm.body.statements);
+    }
+
+    Object::Null
+}
+
+// checks `value` against `pattern`, collecting the values it binds
+fn match_pattern(
+    env: &Rc<Mutex<Environment>>,
+    pattern: &Pattern,
+    value: &Object,
+    bindings: &mut Vec<(String, Object)>,
+) -> bool {
+    match (pattern, value) {
+        (Pattern::Wildcard(_), _) => true,
+        (Pattern::Binding(ident), value) => {
+            bindings.push((ident.value.to_string(), value.clone()));
+            true
+        }
+        // numbers compare the way `==` does, anything else has to be equal
+        (Pattern::Literal(literal), value) => match (eval_expression(env, literal), value) {
+            (literal @ Object::Integer(_), Object::Float(_))
+            | (literal @ Object::Float(_), Object::Integer(_)) => {
+                literal.to_float() == value.to_float()
+            }
+            (literal, value) => &literal == value,
+        },
+        (Pattern::Array(elements, rest, _), Object::Array(_)) => {
+            let Ok(values) = value.unpack_array(elements.len(), rest.is_some()) else {
+                return false;
+            };
+
+            elements
+                .iter()
+                .chain(rest.as_deref())
+                .zip(&values)
+                .all(|(element, item)| match_pattern(env, element, item, bindings))
+        }
+        (Pattern::Hash(entries, _), Object::Hash(hash)) => entries.iter().all(|(key, entry)| {
+            hash.get(&eval_expression(env, key))
+                .is_some_and(|item| match_pattern(env, entry, item, bindings))
+        }),
+        _ => false,
+    }
+}
+
+// binds every name in a `let` pattern, failing when the value has another shape
+fn destructure(
+    env: &Rc<Mutex<Environment>>,
+    pattern: &Pattern,
+    value: Object,
+    bindings: &mut Vec<(String, Object)>,
+) -> Result<(), String> {
+    let (patterns, values) = match pattern {
+        Pattern::Wildcard(_) => return Ok(()),
+        Pattern::Binding(ident) => {
+            bindings.push((ident.value.to_string(), value));
+            return Ok(());
+        }
+        Pattern::Literal(literal) => {
+            return Err(format!("unsupported pattern in let: {}", literal.string()))
+        }
+        Pattern::Array(elements, rest, _) => (
+            elements.iter().chain(rest.as_deref()).collect::<Vec<_>>(),
+            value.unpack_array(elements.len(), rest.is_some())?,
+        ),
+        Pattern::Hash(entries, _) => {
+            let keys = entries
+                .iter()
+                .map(|(key, _)| eval_expression(env, key))
+                .collect::<Vec<_>>();
+
+            (
+                entries.iter().map(|(_, entry)| entry).collect(),
+                value.unpack_hash(&keys)?,
+            )
+        }
+    };
+
+    for (pattern, value) in patterns.into_iter().zip(values) {
+        destructure(env, pattern, value, bindings)?;
+    }
+
+    Ok(())
+}
+
+fn eval_short_circuit(operator: &Token, left: &Object) -> Option<Object> {
+    match operator {
+        Token::AND if !left.is_truthy() => Some(false.into()),
+        Token::OR if left.is_truthy() => Some(true.into()),
+        _ => None,
+    }
+}
+
 fn eval_integer_infix(operator: &Token, left: i64, right: i64) -> Object {
+    let checked = match operator {
+        Token::PLUS => left.checked_add(right),
+        Token::MINUS => left.checked_sub(right),
+        Token::ASTERISK => left.checked_mul(right),
+        Token::SLASH if right == 0 => return Object::Error("division by zero".into()),
+        Token::SLASH => left.checked_div(right),
+        Token::PERCENT if right == 0 => return Object::Error("modulo by zero".into()),
+        Token::PERCENT => left.checked_rem(right),
+        _ => return eval_integer_comparison(operator, left, right),
+    };
+
+    match checked {
+        Some(result) => result.into(),
+        None => Object::Error(format!(
+            "integer overflow: {} {} {}",
+            left,
+            operator.literal(),
+            right
+        )),
+    }
+}
+
+fn eval_integer_comparison(operator: &Token, left: i64, right: i64) -> Object {
+    match operator {
+        Token::GT => (left > right).into(),
+        Token::LT => (left < right).into(),
+        Token::GT_EQ => (left >= right).into(),
+        Token::LT_EQ => (left <= right).into(),
+        Token::EQ => (left == right).into(),
+        Token::NOT_EQ => (left != right).into(),
+        _ => Object::Error(format!("unknown operator: INTEGER {:?} INTEGER", operator)),
+    }
+}
+
+fn eval_float_infix(operator: &Token, left: f64, right: f64) -> Object {
     match operator {
         Token::PLUS => (left + right).into(),
         Token::MINUS => (left - right).into(),
         Token::ASTERISK => (left * right).into(),
         Token::SLASH => (left / right).into(),
+        Token::PERCENT => (left % right).into(),
         Token::GT => (left > right).into(),
         Token::LT => (left < right).into(),
+        Token::GT_EQ => (left >= right).into(),
+        Token::LT_EQ => (left <= right).into(),
         Token::EQ => (left == right).into(),
         Token::NOT_EQ => (left != right).into(),
-        _ => Object::Error(format!("unknown operator: INTEGER {:?} INTEGER", operator)),
+        _ => Object::Error(format!("unknown operator: FLOAT {:?} FLOAT", operator)),
     }
 }
 
 fn eval_infix(operator: &Token, left: Object, right: Object) -> Object {
     match (left, operator, right) {
+        (left, Token::DOT_DOT | Token::DOT_DOT_EQ, right) => eval_range(operator, left, right),
+        // the left side was already checked by eval_short_circuit
+        (_, Token::AND | Token::OR, right) => right.is_truthy().into(),
         (Object::Integer(left), _, Object::Integer(right)) => {
             eval_integer_infix(operator, left, right)
         }
-        (Object::String(left), Token::PLUS, Object::String(right)) => (left + &right).into(),
+        (
+            left @ (Object::Integer(_) | Object::Float(_)),
+            _,
+            right @ (Object::Integer(_) | Object::Float(_)),
+        ) => eval_float_infix(
+            operator,
+            left.to_float().unwrap(),
+            right.to_float().unwrap(),
+        ),
+        (Object::String(left), Token::PLUS, Object::String(right)) => {
+            format!("{}{}", left, right).into()
+        }
+        (Object::String(left), Token::EQ, Object::String(right)) => (left == right).into(),
+        (Object::String(left), Token::NOT_EQ, Object::String(right)) => (left != right).into(),
         (Object::Boolean(left), Token::EQ, Object::Boolean(right)) => (left == right).into(),
         (Object::Boolean(left), Token::NOT_EQ, Object::Boolean(right)) => (left != right).into(),
         (left, operator, right) if !left.is(&right) => Object::Error(format!(
@@ -265,6 +597,18 @@ fn eval_infix(operator: &Token, left: Object, right: Object) -> Object {
     }
 }
 
+fn eval_range(operator: &Token, left: Object, right: Object) -> Object {
+    let (Object::Integer(start), Object::Integer(end)) = (&left, &right) else {
+        return Object::Error(format!(
+            "range bounds must be INTEGER, got {} and {}",
+            left.type_str(),
+            right.type_str()
+        ));
+    };
+
+    Object::range(*start, *end, operator.is(&Token::DOT_DOT_EQ)).unwrap_or_else(Object::from)
+}
+
 fn eval_prefix(operator: &Token, right: Object) -> Object {
     match operator {
         Token::BANG => eval_bang(right),
@@ -275,7 +619,11 @@ fn eval_prefix(operator: &Token, right: Object) -> Object {
 
 fn eval_minus(right: Object) -> Object {
     match right {
-        Object::Integer(i) => (-i).into(),
+        Object::Integer(i) => match i.checked_neg() {
+            Some(i) => i.into(),
+            None => Object::Error(format!("integer overflow: -({})", i)),
+        },
+        Object::Float(f) => (-f).into(),
         _ => Object::Error(format!("unknown operator: MINUS {}", right.type_str())),
     }
 }
@@ -293,7 +641,7 @@ fn eval_statements(env: &Rc<Mutex<Environment>>, statements: &Vec<StatementNode>
 
     for statement in statements {
         result = eval_statement(env, statement);
-        if result.is_return() || result.is_error() {
+        if result.interrupts() {
             return result;
         }
     }
@@ -313,7 +661,7 @@ pub mod test {
             test::{test_error, test_null, test_object},
             Object,
         },
-        parser::Parser,
+        parser::{parse_error::ParseError, Parser},
     };
 
     pub fn test_eval(input: &str) -> Object {
@@ -321,7 +669,7 @@ pub mod test {
 
         let (program, errors) = parser.parse_program();
 
-        let empty: Vec<String> = vec![];
+        let empty: Vec<ParseError> = vec![];
         assert_eq!(errors, empty);
 
         let env = Environment::new();
@@ -410,6 +758,99 @@ pub mod test {
     #[case("let add = fn(x, y) { x + y; }; add(5, 5);", 10)]
     #[case("let add = fn(x, y) { x + y; }; add(5 + 5, add(5, 5));", 20)]
     #[case("fn(x) { x; }(5)", 5)]
+    // floats
+    #[case("1.5", 1.5)]
+    #[case("-2.5", -2.5)]
+    #[case("1.5 + 1", 2.5)]
+    #[case("10 / 4.0", 2.5)]
+    #[case("2 * 0.25 - 1", -0.5)]
+    #[case("1e3 / 8", 125.0)]
+    #[case("1.5 > 1", true)]
+    #[case("2 < 1.5", false)]
+    #[case("1 == 1.0", true)]
+    #[case("0.5 != 0.5", false)]
+    #[case("5.5 % 2", 1.5)]
+    // comparison and logical operators
+    #[case("7 % 3", 1)]
+    #[case("1 + 10 % 4 * 2", 5)]
+    #[case("1 <= 1", true)]
+    #[case("2 <= 1", false)]
+    #[case("1 >= 1", true)]
+    #[case("1.5 >= 2", false)]
+    #[case("true && false", false)]
+    #[case("false || true", true)]
+    #[case("1 && \"a\"", true)]
+    #[case("1 < 2 && 2 < 3 || false", true)]
+    #[case("false && 1 / 0", false)]
+    #[case("true || 1 / 0", true)]
+    #[case("let f = fn(n) { n > 0 && f(n - 1) }; f(3)", false)]
+    // assignment
+    #[case("let x = 1; x = 2; x", 2)]
+    #[case("let x = 1; x = x + 1", 2)]
+    #[case("let x = 5; x += 2; x -= 1; x *= 3; x /= 2; x %= 5; x", 4)]
+    #[case("let a = 1; let b = 2; a = b = 7; a + b", 14)]
+    #[case("let x = 1; let f = fn() { x = 10 }; f(); x", 10)]
+    #[case("let x = 1; let f = fn() { let x = 2; x = 3 }; f(); x", 1)]
+    #[case("let f = fn() { let c = 0; fn() { c += 1 } }(); f(); f(); f()", 3)]
+    #[case("let a = [1, 2, 3]; a[1] = 5; a[1] + a[2]", 8)]
+    #[case("let a = [[1, 2], [3]]; a[0][1] += 40; a[0][1]", 42)]
+    #[case("let h = {\"a\": 1}; h[\"b\"] = 2; h[\"a\"] + h[\"b\"]", 3)]
+    #[case("let h = {\"a\": [1]}; h[\"a\"][0] = \"x\"; h[\"a\"][0]", "x")]
+    #[case("let a = [1]; let b = a; b[0] = 2; a[0]", 1)]
+    // while
+    #[case("let i = 0; while (i < 10) { i += 1 }; i", 10)]
+    #[case(
+        "let i = 0; let s = 0; while (i < 10) { i += 1; if (i % 2 == 0) { continue } s += i }; s",
+        25
+    )]
+    #[case("let i = 0; while (true) { if (i == 7) { break; } i += 1 }; i", 7)]
+    #[case("let i = 0; let n = 0; while (i < 3) { i += 1; let j = 0; while (true) { j += 1; if (j > 4) { break } n += 1 } }; n", 12)]
+    #[case(
+        "let f = fn(n) { let i = 0; while (true) { if (i == n) { return i * 2 } i += 1 } }; f(4)",
+        8
+    )]
+    #[case("let i = 0; while (i < 100000) { i += 1 }; i", 100000)]
+    // for
+    #[case("let s = 0; for (x in [1, 2, 3]) { s += x }; s", 6)]
+    #[case("let s = 0; for (i, x in [5, 6, 7]) { s += i * x }; s", 20)]
+    #[case("let s = 0; for (k in {1: 10, 2: 20}) { s += k }; s", 3)]
+    #[case("let s = 0; for (k, v in {1: 10, 2: 20}) { s += k * v }; s", 50)]
+    #[case("let n = 0; for (c in \"héllo\") { n += 1 }; n", 5)]
+    #[case(
+        "let s = \"\"; for (k in {\"b\": 1, \"a\": 2}) { s = s + k }; len(s)",
+        2
+    )]
+    #[case(
+        "let s = 0; for (i in 0..10) { if (i % 2 == 0) { continue } s += i }; s",
+        25
+    )]
+    #[case(
+        "let s = 0; for (i in 2..100000) { if (i > 5) { break } s += i }; s",
+        14
+    )]
+    #[case("let n = 0; for (i in 0..3) { for (j in 0..4) { n += 1 } }; n", 12)]
+    #[case(
+        "let f = fn(xs) { for (x in xs) { if (x > 2) { return x } } }; f([1, 3, 5])",
+        3
+    )]
+    #[case(
+        "let f = fn(xs) { let s = 0; for (x in xs) { s += x }; s }; f(0..5)",
+        10
+    )]
+    #[case("let x = 10; let s = 0; for (x in [1, 2]) { s += x }; s + x", 5)]
+    // range
+    #[case("len(0..5)", 5)]
+    #[case("len(0..=5)", 6)]
+    #[case("len(5..0)", 0)]
+    #[case("len(-3..3)", 6)]
+    #[case("(2..10)[3]", 5)]
+    #[case("(1..=3)[2]", 3)]
+    #[case("let n = 4; (0..n * 2)[7]", 7)]
+    #[case("let s = 0; for (i in 1..=100) { s += i }; s", 5050)]
+    #[case("let s = 0; for (i, x in 10..13) { s += i * x }; s", 35)]
+    #[case("let n = 0; for (i in 3..3) { n += 1 }; n", 0)]
+    #[case("1..4", Object::Range(1, 4))]
+    #[case("1..=3", Object::Range(1, 4))]
     // string
     #[case("\"Hello world!\"", "Hello world!")]
     #[case("\"Hello\" + \" \" + \"world!\"", "Hello world!")]
@@ -420,9 +861,60 @@ pub mod test {
         test_object(&result, &value);
     }
 
+    #[rstest]
+    #[case("[1, 2, 3, 4][1:3]", "[2, 3]")]
+    #[case("[1, 2, 3, 4][:2]", "[1, 2]")]
+    #[case("[1, 2, 3, 4][2:]", "[3, 4]")]
+    #[case("[1, 2, 3, 4][:]", "[1, 2, 3, 4]")]
+    #[case("[1, 2, 3, 4][-3:-1]", "[2, 3]")]
+    #[case("[1, 2, 3, 4][-10:10]", "[1, 2, 3, 4]")]
+    #[case("[1, 2, 3, 4][3:1]", "[]")]
+    #[case("let a = [1, 2, 3]; let n = 1; a[n + 1:][0]", "3")]
+    #[case("\"héllo\"[1:4]", "éll")]
+    #[case("\"hello\"[-3:]", "llo")]
+    #[case("\"hello\"[5:]", "")]
+    #[case("(10..20)[2:5]", "12..15")]
+    #[case("(0..=9)[-2:]", "8..10")]
+    #[case("let a = [1, 2]; let b = a[:]; b[0] = 9; a[0]", "1")]
+    fn test_slice(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(test_eval(input).to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("let x = 5; if (x < 3) { 1 } else if (x < 6) { 2 } else { 3 }", "2")]
+    #[case("if (false) { 1 } else if (false) { 2 }", "null")]
+    #[case("match (2) { 1 => \"one\", 2 => \"two\", _ => \"many\" }", "two")]
+    #[case("match (2.0) { 2 => \"int\", _ => \"other\" }", "int")]
+    #[case("match (-1) { -1 => true, _ => false }", "true")]
+    #[case("match (\"a\") { 1 => 1 }", "null")]
+    #[case("match ([1, [2, 3]]) { [a, [b, c]] => a + b + c }", "6")]
+    #[case("match ([1, 2]) { [a] => a, [a, b, c] => c, [_, b] => b }", "2")]
+    #[case(
+        "match ({\"x\": 1, \"y\": 2}) { {\"z\": z} => z, {\"y\": y} => y }",
+        "2"
+    )]
+    #[case("match ({1: [true]}) { {1: [false]} => 0, {1: [true]} => 1 }", "1")]
+    #[case("match ([]) { {} => \"hash\", [] => \"array\" }", "array")]
+    #[case("match (5) { n => { let m = n * 2; m } }", "10")]
+    #[case("match (1) { _ => {} }", "null")]
+    #[case(
+        "let f = fn(x) { match (x) { [h, t] => h + f(t), _ => 0 } }; f([1, [2, [3, 0]]])",
+        "6"
+    )]
+    #[case("let s = 0; for (p in [[1, 2], 3, [4]]) { s += match (p) { [a, b] => a * b, [a] => a, _ => 0 } }; s", "6")]
+    fn test_match(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(test_eval(input).to_string(), expected);
+    }
+
     #[rstest]
     #[case("if (1 > 2) { 10 }")]
     #[case("if (false) { 10 }")]
+    #[case("while (false) { 10 }")]
+    #[case("for (x in []) { 10 }")]
+    #[case("(0..3)[3]")]
+    #[case("(0..3)[-4]")]
+    #[case("for (x in [1, 2]) { x }")]
+    #[case("let i = 0; while (true) { i += 1; if (i > 2) { break } }")]
     fn test_nullable(#[case] input: &str) {
         println!("{}", input);
         let result = test_eval(input);
@@ -454,6 +946,39 @@ if (10 > 1) {
     )]
     #[case("foobar;", "identifier not found: foobar")]
     #[case("\"Hello\" - \"Hello\"", "unknown operator: STRING MINUS STRING")]
+    #[case("1.5 + true", "type mismatch: FLOAT PLUS BOOLEAN")]
+    #[case("1 / 0", "division by zero")]
+    #[case("1 % 0", "modulo by zero")]
+    #[case("y = 1", "identifier not found: y")]
+    #[case("len = 1", "cannot assign to builtin len")]
+    #[case(
+        "let s = \"ab\"; s[0] = 1",
+        "index assignment not supported: STRING[INTEGER]"
+    )]
+    #[case("let a = [1]; a[1] = 2", "index out of bounds: 1")]
+    #[case("let h = {}; h[\"a\"][0] = 2", "key not found: a")]
+    #[case("let x = true; x += 1", "type mismatch: BOOLEAN PLUS INTEGER")]
+    #[case("true && 1 / 0", "division by zero")]
+    #[case("let f = fn(x) { 10 / x }; f(0); 5", "division by zero")]
+    #[case("9223372036854775807 + 1", "integer overflow: 9223372036854775807 + 1")]
+    #[case("4611686018427387904 * 2", "integer overflow: 4611686018427387904 * 2")]
+    #[case(
+        "let min = -9223372036854775807 - 1; min / -1",
+        "integer overflow: -9223372036854775808 / -1"
+    )]
+    #[case(
+        "let min = -9223372036854775807 - 1; -min",
+        "integer overflow: -(-9223372036854775808)"
+    )]
+    #[case("{1.5: 1}", "unusable as hash key: FLOAT")]
+    #[case("for (x in 1) { x }", "not iterable: INTEGER")]
+    #[case("1.5..2", "range bounds must be INTEGER, got FLOAT and INTEGER")]
+    #[case("1[0:1]", "slice operator not supported: INTEGER")]
+    #[case("{}[0:1]", "slice operator not supported: HASH")]
+    #[case("[1][\"a\":]", "slice bounds must be INTEGER, got STRING")]
+    #[case("[1][:1.5]", "slice bounds must be INTEGER, got FLOAT")]
+    #[case("0..=9223372036854775807", "integer overflow: 0..=9223372036854775807")]
+    #[case("for (x in [1]) { y }", "identifier not found: y")]
     fn test_errors(#[case] input: &str, #[case] error: &str) {
         println!("{}", input);
         let result = test_eval(input);
@@ -496,7 +1021,7 @@ if (10 > 1) {
 
     #[rstest]
     #[case("[1, 2, 3][3]")]
-    #[case("[1, 2, 3][-1]")]
+    #[case("[1, 2, 3][-4]")]
     #[case("{\"foo\": 5}[\"bar\"]")]
     #[case("{}[\"foo\"]")]
     fn test_index_null(#[case] input: &str) {
@@ -0,0 +1,68 @@
+pub mod ast;
+pub mod builtin;
+pub mod code;
+pub mod compiler;
+#[cfg(test)]
+mod conformance;
+pub mod engine;
+pub mod evaluator;
+pub mod object;
+pub mod parser;
+pub mod repl;
+pub mod tokens;
+pub mod vm;
+
+pub use ast::program::Program;
+pub use compiler::{Bytecode, Compiler};
+pub use engine::{engine_error::EngineError, Engine};
+pub use object::Object;
+pub use parser::{parse_error::ParseError, Parser};
+pub use vm::{runtime_error::RuntimeError, Vm};
+
+use vm::{runtime_error::RuntimeErrorKind, verifier::verify};
+
+use evaluator::environment::Environment;
+
+/// Parses `source` into a [`Program`], returning every parser error on failure.
+pub fn parse(source: &str) -> Result<Program, Vec<ParseError>> {
+    let mut parser = Parser::new(source.into());
+
+    let (program, errors) = parser.parse_program();
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(program)
+}
+
+/// Compiles a parsed [`Program`] into [`Bytecode`] for the [`Vm`].
+pub fn compile(program: &Program) -> Result<Bytecode, String> {
+    let mut compiler = Compiler::new();
+
+    compiler.compile(program.into())?;
+
+    Ok(compiler.bytecode())
+}
+
+/// Verifies `bytecode` and runs it on a fresh [`Vm`], returning the last popped
+/// value or the error that stopped it along with the calls active at that
+/// point.
+pub fn run(bytecode: Bytecode) -> Result<Object, RuntimeError> {
+    verify(&bytecode).map_err(|message| RuntimeError {
+        kind: RuntimeErrorKind::InvalidBytecode(message),
+        frames: vec![],
+    })?;
+
+    let mut vm = Vm::new();
+
+    vm.with_bytecode(bytecode);
+    vm.run()?;
+
+    Ok(vm.last_popped().from_ref())
+}
+
+/// Evaluates a parsed [`Program`] with the tree-walking evaluator.
+pub fn evaluate(program: &Program) -> Object {
+    evaluator::eval(&Environment::new(), program.into())
+}
@@ -1,51 +1,102 @@
-pub mod ast;
-pub mod builtin;
-pub mod code;
-pub mod compiler;
-pub mod evaluator;
-pub mod object;
-pub mod parser;
-pub mod repl;
-pub mod tokens;
-pub mod vm;
-
-use std::{env, fs, process::exit};
-
-use compiler::Compiler;
-use repl::repl_run;
-use vm::Vm;
-
-use crate::{
-    evaluator::{environment::Environment, eval},
-    parser::Parser,
+use std::{env, fs, path::Path, process::exit};
+
+use rust_monkeylang::{
+    compile, evaluate, parse, repl, vm::verifier::verify, Bytecode, Engine, Program,
 };
 
-fn run(file: &str) {
+fn parse_file(file: &str) -> Program {
     let content = fs::read_to_string(file).unwrap();
 
-    let mut parser = Parser::new(content);
-
-    let (program, errors) = parser.parse_program();
-
-    if !errors.is_empty() {
-        println!("ERRORS: {:?}", errors);
-        exit(1);
+    match parse(&content) {
+        Ok(program) => program,
+        Err(errors) => {
+            for error in errors {
+                println!("{}", error.render(&content));
+            }
+            exit(1);
+        }
     }
+}
 
-    let result = eval(&Environment::new(), (&program).into());
+fn run(file: &str) {
+    let program = parse_file(file);
+
+    let result = evaluate(&program);
     println!("{}", result);
 }
 
 fn compiled_run(file: &str) {
-    let mut compiler = Compiler::new();
-    let mut vm = Vm::new();
+    let mut engine = Engine::new();
 
     let content = fs::read_to_string(file).unwrap();
 
-    match repl_run(&mut compiler, &mut vm, content) {
+    match engine.run(&content) {
+        Ok(result) => println!("{}", result),
+        Err(err) => {
+            println!("{}", err.render(&content));
+            exit(1);
+        }
+    }
+}
+
+// compiles `file` to bytecode written next to it, or to `output`
+fn compile_file(file: &str, output: Option<&String>) {
+    let program = parse_file(file);
+
+    let bytes = compile(&program).and_then(|bytecode| bytecode.serialize());
+    let bytes = match bytes {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            println!("{}", err);
+            exit(1);
+        }
+    };
+
+    let output = match output {
+        Some(output) => output.into(),
+        None => Path::new(file).with_extension("mkc"),
+    };
+
+    fs::write(&output, bytes).unwrap();
+}
+
+fn bytecode_run(file: &str) {
+    let bytes = fs::read(file).unwrap();
+
+    let bytecode = match Bytecode::deserialize(&bytes) {
+        Ok(bytecode) => bytecode,
+        Err(err) => {
+            println!("{}: {}", file, err);
+            exit(1);
+        }
+    };
+
+    match rust_monkeylang::run(bytecode) {
         Ok(result) => println!("{}", result),
         Err(err) => {
-            println!("ERR: {}", err);
+            println!("{}", err.render());
+            exit(1);
+        }
+    }
+}
+
+// prints the bytecode of a source or `.mkc` file
+fn disassemble(file: &str) {
+    let bytecode = if file.ends_with(".mkc") {
+        let bytes = fs::read(file).unwrap();
+
+        Bytecode::deserialize(&bytes).and_then(|bytecode| {
+            verify(&bytecode)?;
+            Ok(bytecode)
+        })
+    } else {
+        compile(&parse_file(file))
+    };
+
+    match bytecode {
+        Ok(bytecode) => print!("{}", bytecode.disassemble()),
+        Err(err) => {
+            println!("{}: {}", file, err);
             exit(1);
         }
     }
@@ -53,13 +104,19 @@ fn compiled_run(file: &str) {
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() > 1 {
-        if args.get(1).unwrap() == "-c" {
-            compiled_run(args.get(2).unwrap())
-        } else {
-            run(args.get(1).unwrap())
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("-c") => compiled_run(args.get(2).unwrap()),
+        Some("compile") => {
+            let output = match args.get(3).map(|arg| arg.as_str()) {
+                Some("-o") => args.get(4),
+                _ => None,
+            };
+
+            compile_file(args.get(2).unwrap(), output)
         }
-    } else {
-        repl::start();
+        Some("disasm") => disassemble(args.get(2).unwrap()),
+        Some(file) if file.ends_with(".mkc") => bytecode_run(file),
+        Some(file) => run(file),
+        None => repl::start(),
     }
 }
@@ -0,0 +1,146 @@
+use std::{cmp::Ordering, rc::Rc};
+
+use super::Object;
+
+// walks a snapshot of an iterable, yielding the index or key of every element
+// along with the element itself
+#[derive(Debug, Clone)]
+pub enum ObjectIterator {
+    Array(Rc<Vec<Object>>, usize),
+    Hash(Vec<(Object, Object)>, usize),
+    String(Vec<char>, usize),
+    // next value, end, index
+    Range(i64, i64, usize),
+}
+
+impl ObjectIterator {
+    pub fn new(iterable: Object) -> Result<ObjectIterator, String> {
+        match iterable {
+            Object::Array(items) => Ok(ObjectIterator::Array(items, 0)),
+            Object::Hash(hash) => {
+                let mut entries = hash
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect::<Vec<_>>();
+                entries.sort_by(|a, b| key_order(&a.0, &b.0));
+
+                Ok(ObjectIterator::Hash(entries, 0))
+            }
+            Object::String(s) => Ok(ObjectIterator::String(s.chars().collect(), 0)),
+            Object::Range(start, end) => Ok(ObjectIterator::Range(start, end, 0)),
+            other => Err(format!("not iterable: {}", other.type_str())),
+        }
+    }
+
+    // a loop with a single variable takes the keys of a hash and the elements
+    // of everything else, with two it takes both
+    pub fn next_bindings(&mut self, count: usize) -> Option<Vec<Object>> {
+        let is_hash = matches!(self, ObjectIterator::Hash(_, _));
+        let (key, value) = self.next()?;
+
+        Some(match (count, is_hash) {
+            (1, true) => vec![key],
+            (1, false) => vec![value],
+            _ => vec![key, value],
+        })
+    }
+}
+
+impl Iterator for ObjectIterator {
+    type Item = (Object, Object);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ObjectIterator::Array(items, index) => {
+                let item = items.get(*index)?.clone();
+                *index += 1;
+
+                Some(((*index as i64 - 1).into(), item))
+            }
+            ObjectIterator::Hash(entries, index) => {
+                let entry = entries.get(*index)?.clone();
+                *index += 1;
+
+                Some(entry)
+            }
+            ObjectIterator::String(chars, index) => {
+                let c = chars.get(*index)?;
+                *index += 1;
+
+                Some(((*index as i64 - 1).into(), c.to_string().into()))
+            }
+            ObjectIterator::Range(next, end, index) => {
+                if next >= end {
+                    return None;
+                }
+
+                let value = *next;
+                *next += 1;
+                *index += 1;
+
+                Some(((*index as i64 - 1).into(), value.into()))
+            }
+        }
+    }
+}
+
+// hash keys come out in a stable order, integers and strings sorted by value
+// and grouped by type
+fn key_order(a: &Object, b: &Object) -> Ordering {
+    match (a, b) {
+        (Object::Integer(a), Object::Integer(b)) => a.cmp(b),
+        (Object::Boolean(a), Object::Boolean(b)) => a.cmp(b),
+        (Object::String(a), Object::String(b)) => a.cmp(b),
+        (a, b) => a.type_str().cmp(b.type_str()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::object::Object;
+
+    use super::ObjectIterator;
+
+    #[test]
+    fn test_iterator_bindings() {
+        let mut iterator = ObjectIterator::new(Object::String("añb".into())).unwrap();
+        assert_eq!(
+            iterator.next_bindings(1),
+            Some(vec!["a".to_string().into()])
+        );
+        assert_eq!(
+            iterator.next_bindings(2),
+            Some(vec![1.into(), "ñ".to_string().into()])
+        );
+
+        let mut iterator = ObjectIterator::new(Object::Range(3, 5)).unwrap();
+        assert_eq!(iterator.next_bindings(2), Some(vec![0.into(), 3.into()]));
+        assert_eq!(iterator.next_bindings(1), Some(vec![4.into()]));
+        assert_eq!(iterator.next_bindings(1), None);
+
+        assert_eq!(
+            ObjectIterator::new(Object::Integer(1)).err(),
+            Some("not iterable: INTEGER".into())
+        );
+    }
+
+    #[test]
+    fn test_hash_iteration_order() {
+        let hash = HashMap::from([
+            (Object::Integer(10), Object::Null),
+            (Object::String("b".into()), Object::Null),
+            (Object::Integer(2), Object::Null),
+            (Object::String("a".into()), Object::Null),
+            (Object::Boolean(true), Object::Null),
+        ]);
+
+        let keys = ObjectIterator::new(hash.into())
+            .unwrap()
+            .map(|(key, _)| key.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(keys, vec!["true", "2", "10", "a", "b"]);
+    }
+}
@@ -1,5 +1,6 @@
 use core::panic;
 use std::{
+    cell::RefCell,
     collections::HashMap,
     fmt::{Debug, Display},
     hash::{Hash, Hasher},
@@ -9,29 +10,110 @@ use std::{
 };
 
 use crate::{
-    ast::{block_statement::BlockStatement, identifier::Identifier, AstNode},
+    ast::{block_statement::BlockStatement, function_expression::Parameter, AstNode},
     builtin::BuiltinFunction,
     code::Instructions,
     evaluator::environment::Environment,
+    vm::runtime_error::RuntimeErrorKind,
 };
 
+use self::iterator::ObjectIterator;
+
+pub mod iterator;
+
 #[derive(Debug, Clone)]
 pub enum Object {
     Integer(i64),
+    Float(f64),
     Boolean(bool),
-    Function(Vec<Identifier>, BlockStatement, Rc<Mutex<Environment>>),
-    // Instructions, NumLocals, NumParemterers, frees
-    CompiledFunction(Instructions, usize, usize),
-    Closure(Instructions, usize, usize, Vec<Object>),
-    String(String),
-    Array(Vec<Object>),
-    Hash(HashMap<Object, Object>),
+    Function(Vec<Parameter>, BlockStatement, Rc<Mutex<Environment>>),
+    CompiledFunction(Rc<CompiledFunction>),
+    // function, frees
+    Closure(Rc<CompiledFunction>, Vec<Object>),
+    // a local captured by a closure, shared between the frame that defines it
+    // and every closure over it
+    Cell(Rc<RefCell<Object>>),
+    // strings, arrays and hashes are shared between every copy of a value,
+    // changing one goes through `Rc::make_mut` so the others never see it
+    String(Rc<str>),
+    Array(Rc<Vec<Object>>),
+    Hash(Rc<HashMap<Object, Object>>),
+    // start, exclusive end
+    Range(i64, i64),
+    Iterator(ObjectIterator),
     Builtin(BuiltinFunction),
     Null,
     Return(Box<Object>),
+    Break,
+    Continue,
     Error(String),
 }
 
+#[derive(Debug, Clone)]
+pub struct CompiledFunction {
+    pub instructions: Instructions,
+    pub num_locals: usize,
+    // every parameter but the rest one
+    pub num_parameters: usize,
+    pub rest: bool,
+    // the name of the let binding a function is defined by, if any
+    pub name: Option<String>,
+    // (offset, line) of every instruction starting a new source line
+    pub lines: Vec<(usize, usize)>,
+    // where a call starts for each number of missing defaults, the code at
+    // entries[i] fills in the i-th parameter with a default and falls through
+    // to the next, the last entry is the start of the body
+    pub entries: Vec<usize>,
+    // names of the locals and free values by index, for the disassembler
+    pub locals: Vec<String>,
+    pub free: Vec<String>,
+}
+
+impl CompiledFunction {
+    pub fn new(instructions: Instructions, num_locals: usize, num_parameters: usize) -> Self {
+        CompiledFunction {
+            instructions,
+            num_locals,
+            num_parameters,
+            rest: false,
+            name: None,
+            lines: vec![],
+            entries: vec![0],
+            locals: vec![],
+            free: vec![],
+        }
+    }
+
+    // the source line the instruction at `offset` was compiled from
+    pub fn line(&self, offset: usize) -> Option<usize> {
+        let next = self.lines.partition_point(|(start, _)| *start <= offset);
+
+        next.checked_sub(1).map(|i| self.lines[i].1)
+    }
+
+    pub fn min_arity(&self) -> usize {
+        self.num_parameters + 1 - self.entries.len()
+    }
+
+    pub fn max_arity(&self) -> Option<usize> {
+        (!self.rest).then_some(self.num_parameters)
+    }
+
+    pub fn entry(&self, num_args: usize) -> usize {
+        let missing = self.num_parameters.saturating_sub(num_args);
+        self.entries[self.entries.len() - 1 - missing]
+    }
+}
+
+// the argument count check of both engines
+pub fn check_arity(min: usize, max: Option<usize>, got: usize) -> Result<(), RuntimeErrorKind> {
+    if got >= min && max.is_none_or(|max| got <= max) {
+        return Ok(());
+    }
+
+    Err(RuntimeErrorKind::WrongArgumentCount { min, max, got })
+}
+
 impl TryFrom<Object> for i64 {
     type Error = String;
     fn try_from(value: Object) -> Result<Self, Self::Error> {
@@ -46,9 +128,11 @@ impl PartialEq for Object {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Integer(l0), Self::Integer(r0)) => l0 == r0,
+            (Self::Float(l0), Self::Float(r0)) => l0 == r0,
             (Self::Boolean(l0), Self::Boolean(r0)) => l0 == r0,
             (Self::String(l0), Self::String(r0)) => l0 == r0,
             (Self::Array(l0), Self::Array(r0)) => l0 == r0,
+            (Self::Range(l0, l1), Self::Range(r0, r1)) => l0 == r0 && l1 == r1,
             (Self::Error(l0), Self::Error(r0)) => l0 == r0,
             _ => false,
         }
@@ -75,6 +159,228 @@ impl Object {
         )
     }
 
+    pub fn get_path(&self, keys: &[Object]) -> Result<&Object, RuntimeErrorKind> {
+        let Some((key, rest)) = keys.split_first() else {
+            return Ok(self);
+        };
+
+        let item = match (self, key) {
+            (Object::Array(items), Object::Integer(i)) => resolve_index(*i, items.len())
+                .map(|index| &items[index])
+                .ok_or(RuntimeErrorKind::IndexOutOfBounds(*i))?,
+            (Object::Hash(hash), key) if key.hashable() => hash
+                .get(key)
+                .ok_or_else(|| RuntimeErrorKind::KeyNotFound(key.to_string()))?,
+            (Object::Hash(_), key) => {
+                return Err(RuntimeErrorKind::UnusableHashKey(key.type_str()))
+            }
+            (container, key) => {
+                return Err(RuntimeErrorKind::NotIndexable {
+                    container: container.type_str(),
+                    key: key.type_str(),
+                    assign: true,
+                })
+            }
+        };
+
+        item.get_path(rest)
+    }
+
+    // replaces the value found by following `keys` into nested arrays and
+    // hashes, the last key of a hash may be a new entry
+    pub fn set_path(&mut self, keys: &[Object], value: Object) -> Result<(), RuntimeErrorKind> {
+        let Some((key, rest)) = keys.split_first() else {
+            *self = value;
+            return Ok(());
+        };
+
+        let item = match (&mut *self, key) {
+            (Object::Array(items), Object::Integer(i)) => resolve_index(*i, items.len())
+                .map(|index| &mut Rc::make_mut(items)[index])
+                .ok_or(RuntimeErrorKind::IndexOutOfBounds(*i))?,
+            (Object::Hash(hash), key) if key.hashable() && rest.is_empty() => {
+                Rc::make_mut(hash).insert(key.clone(), value);
+                return Ok(());
+            }
+            (Object::Hash(hash), key) if key.hashable() => Rc::make_mut(hash)
+                .get_mut(key)
+                .ok_or_else(|| RuntimeErrorKind::KeyNotFound(key.to_string()))?,
+            (Object::Hash(_), key) => {
+                return Err(RuntimeErrorKind::UnusableHashKey(key.type_str()))
+            }
+            (container, key) => {
+                return Err(RuntimeErrorKind::NotIndexable {
+                    container: container.type_str(),
+                    key: key.type_str(),
+                    assign: true,
+                })
+            }
+        };
+
+        item.set_path(rest, value)
+    }
+
+    // `start..=end` is kept as the half-open `start..end + 1`
+    pub fn range(start: i64, end: i64, inclusive: bool) -> Result<Object, RuntimeErrorKind> {
+        if !inclusive {
+            return Ok(Object::Range(start, end));
+        }
+
+        end.checked_add(1)
+            .map(|end| Object::Range(start, end))
+            .ok_or_else(|| RuntimeErrorKind::IntegerOverflow(format!("{}..={}", start, end)))
+    }
+
+    pub fn range_len(start: i64, end: i64) -> i64 {
+        end.saturating_sub(start).max(0)
+    }
+
+    // the element lookup of both engines, negative indices count from the end
+    // and anything out of bounds or missing is null
+    pub fn index(&self, index: &Object) -> Result<Object, RuntimeErrorKind> {
+        let item = match (self, index) {
+            (Object::Array(items), Object::Integer(i)) => {
+                resolve_index(*i, items.len()).map(|i| items[i].clone())
+            }
+            (Object::String(s), Object::Integer(i)) => resolve_index(*i, s.chars().count())
+                .and_then(|i| s.chars().nth(i))
+                .map(|c| c.to_string().into()),
+            (Object::Range(start, end), Object::Integer(i)) => {
+                resolve_index(*i, Object::range_len(*start, *end) as usize)
+                    .map(|i| Object::Integer(start + i as i64))
+            }
+            (Object::Hash(hash), key) if key.hashable() => hash.get(key).cloned(),
+            (Object::Hash(_), key) => {
+                return Err(RuntimeErrorKind::UnusableHashKey(key.type_str()))
+            }
+            (container, index) => {
+                return Err(RuntimeErrorKind::NotIndexable {
+                    container: container.type_str(),
+                    key: index.type_str(),
+                    assign: false,
+                })
+            }
+        };
+
+        Ok(item.unwrap_or(Object::Null))
+    }
+
+    // bounds are integers or null for an open side, negative ones count from
+    // the end and anything out of bounds is clamped to the sliced value
+    pub fn slice(&self, start: &Object, end: &Object) -> Result<Object, String> {
+        let len = match self {
+            Object::Array(items) => items.len(),
+            Object::String(s) => s.chars().count(),
+            Object::Range(first, last) => Object::range_len(*first, *last) as usize,
+            other => {
+                return Err(format!(
+                    "slice operator not supported: {}",
+                    other.type_str()
+                ))
+            }
+        };
+
+        let start = slice_bound(start, len, 0)?;
+        let end = slice_bound(end, len, len)?.max(start);
+
+        Ok(match self {
+            Object::Array(items) => items[start..end].to_vec().into(),
+            Object::String(s) => s
+                .chars()
+                .skip(start)
+                .take(end - start)
+                .collect::<String>()
+                .into(),
+            Object::Range(first, _) => Object::Range(first + start as i64, first + end as i64),
+            _ => unreachable!(),
+        })
+    }
+
+    // the elements an array pattern of `len` elements binds, a rest binding
+    // takes whatever is left over as one more array
+    pub fn unpack_array(&self, len: usize, rest: bool) -> Result<Vec<Object>, String> {
+        let Object::Array(items) = self else {
+            return Err(format!("cannot destructure {} as ARRAY", self.type_str()));
+        };
+
+        if rest && items.len() < len {
+            return Err(format!(
+                "expected at least {} elements, got {}",
+                len,
+                items.len()
+            ));
+        }
+        if !rest && items.len() != len {
+            return Err(format!("expected {} elements, got {}", len, items.len()));
+        }
+
+        let mut values = items[..len].to_vec();
+        if rest {
+            values.push(items[len..].to_vec().into());
+        }
+
+        Ok(values)
+    }
+
+    // the values a hash pattern binds, every key has to be present
+    pub fn unpack_hash(&self, keys: &[Object]) -> Result<Vec<Object>, String> {
+        let Object::Hash(hash) = self else {
+            return Err(format!("cannot destructure {} as HASH", self.type_str()));
+        };
+
+        keys.iter()
+            .map(|key| {
+                hash.get(key)
+                    .cloned()
+                    .ok_or_else(|| format!("key not found: {}", key))
+            })
+            .collect()
+    }
+
+    // reads a variable slot, looking through the cell of a captured one
+    pub fn load(&self) -> Object {
+        match self {
+            Object::Cell(cell) => cell.borrow().from_ref(),
+            value => value.from_ref(),
+        }
+    }
+
+    // writes a variable slot, through the cell of a captured one so that
+    // every closure sees the new value
+    pub fn store(&mut self, value: Object) {
+        match self {
+            Object::Cell(cell) => *cell.borrow_mut() = value,
+            slot => *slot = value,
+        }
+    }
+
+    // moves the value out of a variable slot, leaving null until it is stored
+    // back
+    pub fn take(&mut self) -> Object {
+        match self {
+            Object::Cell(cell) => mem::replace(&mut *cell.borrow_mut(), Object::Null),
+            slot => mem::replace(slot, Object::Null),
+        }
+    }
+
+    // turns a variable slot into a cell the first time it is captured
+    pub fn capture(&mut self) -> Object {
+        if !matches!(self, Object::Cell(_)) {
+            let value = mem::replace(self, Object::Null);
+            *self = Object::Cell(Rc::new(RefCell::new(value)));
+        }
+
+        self.from_ref()
+    }
+
+    pub fn to_float(&self) -> Option<f64> {
+        match self {
+            Object::Integer(i) => Some(*i as f64),
+            Object::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
     pub fn is_truthy(&self) -> bool {
         match self {
             Object::Boolean(b) => *b,
@@ -89,9 +395,22 @@ impl Object {
         matches!(self, Object::Return(_))
     }
 
+    pub fn is_loop_control(&self) -> bool {
+        matches!(self, Object::Break | Object::Continue)
+    }
+
     pub fn is_error(&self) -> bool {
         matches!(self, Object::Error(_))
     }
+
+    // errors, returns and loop control skip the rest of every expression
+    // they happen in until something handles them
+    pub fn interrupts(&self) -> bool {
+        matches!(
+            self,
+            Object::Error(_) | Object::Return(_) | Object::Break | Object::Continue
+        )
+    }
     pub fn unwrap(self) -> Object {
         if let Object::Return(value) = self {
             return *value;
@@ -107,7 +426,10 @@ impl Object {
         match self {
             Object::Return(_) => todo!(),
             Object::Error(_) => todo!(),
+            Object::Break => "BREAK",
+            Object::Continue => "CONTINUE",
             Object::Integer(_) => "INTEGER",
+            Object::Float(_) => "FLOAT",
             Object::Boolean(_) => "BOOLEAN",
             Object::Null => "NULL",
             Object::Function(_, _, _) => "FUNCTION",
@@ -115,25 +437,28 @@ impl Object {
             Object::Builtin(_) => "BUILTIN",
             Object::Array(_) => "ARRAY",
             Object::Hash(_) => "HASH",
-            Object::CompiledFunction(_, _, _) => "COMPILED_FUNCTION",
-            Object::Closure(_, _, _, _) => "CLOSURE",
+            Object::Range(_, _) => "RANGE",
+            Object::Iterator(_) => "ITERATOR",
+            Object::CompiledFunction(_) => "COMPILED_FUNCTION",
+            Object::Closure(_, _) => "CLOSURE",
+            Object::Cell(_) => "CELL",
         }
     }
 
     pub fn from_ref(&self) -> Object {
         match self {
             Object::Integer(i) => Object::Integer(*i),
+            Object::Float(i) => Object::Float(*i),
             Object::Boolean(i) => Object::Boolean(*i),
-            Object::String(i) => Object::String(i.to_string()),
-            Object::Array(i) => Object::Array(i.iter().map(|i| i.from_ref()).collect()),
-            Object::Hash(i) => Object::Hash(
-                i.iter()
-                    .map(|(a, b)| (a.from_ref(), b.from_ref()))
-                    .collect(),
-            ),
+            Object::String(i) => Object::String(i.clone()),
+            Object::Array(i) => Object::Array(i.clone()),
+            Object::Hash(i) => Object::Hash(i.clone()),
 
-            Object::CompiledFunction(i, b, c) => Object::CompiledFunction(i.clone(), *b, *c),
-            Object::Closure(i, b, c, d) => Object::Closure(i.clone(), *b, *c, d.clone()),
+            Object::CompiledFunction(f) => Object::CompiledFunction(f.clone()),
+            Object::Closure(f, free) => Object::Closure(f.clone(), free.clone()),
+            Object::Cell(cell) => Object::Cell(cell.clone()),
+            Object::Range(a, b) => Object::Range(*a, *b),
+            Object::Iterator(i) => Object::Iterator(i.clone()),
             Object::Builtin(i) => Object::Builtin(*i),
             Object::Error(i) => Object::Error(i.to_string()),
             Object::Null => Object::Null,
@@ -142,12 +467,46 @@ impl Object {
     }
 }
 
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let index = match index < 0 {
+        true => len.checked_sub(index.unsigned_abs() as usize)?,
+        false => index as usize,
+    };
+
+    (index < len).then_some(index)
+}
+
+fn slice_bound(bound: &Object, len: usize, open: usize) -> Result<usize, String> {
+    match bound {
+        Object::Null => Ok(open),
+        Object::Integer(i) if *i < 0 => Ok(len.saturating_sub(i.unsigned_abs() as usize)),
+        Object::Integer(i) => Ok((*i as usize).min(len)),
+        other => Err(format!(
+            "slice bounds must be INTEGER, got {}",
+            other.type_str()
+        )),
+    }
+}
+
+// the evaluator reports the failures it shares with the vm as error values
+impl From<RuntimeErrorKind> for Object {
+    fn from(kind: RuntimeErrorKind) -> Self {
+        Object::Error(kind.to_string())
+    }
+}
+
 impl From<i64> for Object {
     fn from(val: i64) -> Self {
         Object::Integer(val)
     }
 }
 
+impl From<f64> for Object {
+    fn from(val: f64) -> Self {
+        Object::Float(val)
+    }
+}
+
 impl From<bool> for Object {
     fn from(val: bool) -> Self {
         Object::Boolean(val)
@@ -156,22 +515,37 @@ impl From<bool> for Object {
 
 impl From<&String> for Object {
     fn from(value: &String) -> Self {
-        Object::String(value.to_string())
+        Object::String(value.as_str().into())
     }
 }
 
 impl From<String> for Object {
     fn from(value: String) -> Self {
-        Object::String(value)
+        Object::String(value.into())
+    }
+}
+
+impl From<Vec<Object>> for Object {
+    fn from(value: Vec<Object>) -> Self {
+        Object::Array(Rc::new(value))
+    }
+}
+
+impl From<HashMap<Object, Object>> for Object {
+    fn from(value: HashMap<Object, Object>) -> Self {
+        Object::Hash(Rc::new(value))
     }
 }
 impl Display for Object {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Object::Integer(i) => write!(f, "{}", i),
+            Object::Float(x) => write!(f, "{:?}", x),
             Object::Boolean(b) => write!(f, "{}", b),
             Object::Null => write!(f, "null"),
             Object::Return(i) => write!(f, "{}", i),
+            Object::Break => write!(f, "break"),
+            Object::Continue => write!(f, "continue"),
             Object::Function(arguments, block, _) => write!(
                 f,
                 "fn ({}) {{\n{}\n}}",
@@ -203,12 +577,15 @@ impl Display for Object {
                         .join(", ")
                 )
             }
-            Object::CompiledFunction(instructions, _, _) => {
-                write!(f, "CompiledFunction[{}]", instructions)
+            Object::Range(start, end) => write!(f, "{}..{}", start, end),
+            Object::Iterator(_) => write!(f, "iterator"),
+            Object::CompiledFunction(function) => {
+                write!(f, "CompiledFunction[{}]", function.instructions)
             }
-            Object::Closure(instructions, _, _, _) => {
-                write!(f, "Closure[{}]", instructions)
+            Object::Closure(function, _) => {
+                write!(f, "Closure[{}]", function.instructions)
             }
+            Object::Cell(cell) => write!(f, "{}", cell.borrow()),
         }
     }
 }
@@ -226,13 +603,15 @@ pub mod test {
 
         if let Some(obj) = value_any.downcast_ref::<Object>() {
             match (object, obj) {
-                (Object::CompiledFunction(a, a2, a3), Object::CompiledFunction(b, b2, b3)) => {
-                    if a.0 != b.0 {
-                        assert_eq!(a, b);
+                (Object::CompiledFunction(a), Object::CompiledFunction(b)) => {
+                    if a.instructions.0 != b.instructions.0 {
+                        assert_eq!(a.instructions, b.instructions);
                     }
 
-                    assert_eq!(a2, b2);
-                    assert_eq!(a3, b3);
+                    assert_eq!(a.num_locals, b.num_locals);
+                    assert_eq!(a.num_parameters, b.num_parameters);
+                    assert_eq!(a.rest, b.rest);
+                    assert_eq!(a.entries, b.entries);
                 }
                 (a, b) => assert_eq!(a, b),
             }
@@ -248,11 +627,14 @@ pub mod test {
                     .unwrap();
                 assert_eq!(&val, i)
             }
+            Object::Float(f) => {
+                assert_eq!(value_any.downcast_ref::<f64>().unwrap(), f)
+            }
             Object::Boolean(i) => {
                 assert_eq!(value_any.downcast_ref::<bool>().unwrap(), i)
             }
             Object::String(v) => {
-                assert_eq!(&v, value_any.downcast_ref::<&str>().unwrap())
+                assert_eq!(&&**v, value_any.downcast_ref::<&str>().unwrap())
             }
             Object::Function(_, _, _) => todo!(),
             Object::Null => panic!("called test_object on null object, use test_null if expected"),
@@ -271,6 +653,14 @@ pub mod test {
         assert_eq!(e, error)
     }
 
+    #[test]
+    fn test_float_display() {
+        assert_eq!(Object::Float(2.0).to_string(), "2.0");
+        assert_eq!(Object::Float(0.25).to_string(), "0.25");
+        assert_eq!(Object::Float(-1.5).to_string(), "-1.5");
+        assert_eq!(test_eval("10 / 4.0").to_string(), "2.5");
+    }
+
     #[test]
     fn test_function_object() {
         let input = "fn(x) { x + 2 };";
@@ -1,56 +1,111 @@
 use crate::{
     ast::{
-        array_literal::ArrayLiteral, block_statement::BlockStatement,
-        boolean_literal::BooleanLiteral, call_expression::CallExpression,
-        expression_statement::ExpressionStatement, function_expression::FunctionExpression,
-        grouped_expression::GroupedExpression, hash_literal::HashLiteral, identifier::Identifier,
-        if_expression::IfExpression, index_expression::IndexExpression,
-        infix_expression::InfixExpression, integer_literal::IntegerLiteral,
-        let_statement::LetStatement, prefix_expression::PrefixExpression, program::Program,
-        return_statement::ReturnStatement, string_literal::StringLiteral, ExpressionNode,
-        ParsableResult, ParseInfix, ParsePrefix, ParseStatement, PrefixParser, StatementNode,
+        array_literal::ArrayLiteral, assign_expression::AssignExpression,
+        block_statement::BlockStatement, boolean_literal::BooleanLiteral,
+        break_statement::BreakStatement, call_expression::CallExpression,
+        continue_statement::ContinueStatement, expression_statement::ExpressionStatement,
+        float_literal::FloatLiteral, for_expression::ForExpression,
+        function_expression::FunctionExpression, grouped_expression::GroupedExpression,
+        hash_literal::HashLiteral, identifier::Identifier, if_expression::IfExpression,
+        index_expression::IndexExpression, infix_expression::InfixExpression,
+        integer_literal::IntegerLiteral, let_statement::LetStatement,
+        match_expression::MatchExpression, prefix_expression::PrefixExpression, program::Program,
+        return_statement::ReturnStatement, string_literal::StringLiteral,
+        while_statement::WhileStatement, ExpressionNode, ParsableResult, ParseInfix, ParsePrefix,
+        ParseStatement, PrefixParser, StatementNode,
     },
-    tokens::{lexer::Lexer, token::Token},
+    tokens::{lexer::Lexer, span::Span, token::Token},
 };
 
-use self::precedence::{IntoPrecedence, Precedence};
+use self::{
+    parse_error::{ParseError, ParseErrorKind},
+    precedence::{IntoPrecedence, Precedence},
+};
 
+pub mod parse_error;
 pub mod precedence;
 
 pub struct Parser {
     lexer: Lexer,
     pub current_token: Token,
     pub peek_token: Token,
+    pub current_span: Span,
+    pub peek_span: Span,
+    pub current_doc: Option<String>,
+    pub peek_doc: Option<String>,
+    pub loop_depth: usize,
+    errors: Vec<ParseError>,
 }
 
 impl Parser {
     pub fn new(input: String) -> Parser {
         let mut lexer = Lexer::new(input);
 
-        let current_token = lexer.next_token();
-        let next_token = lexer.next_token();
+        let current = lexer.next_spanned_token();
+        let next = lexer.next_spanned_token();
 
         Parser {
             lexer,
-            current_token,
-            peek_token: next_token,
+            current_token: current.token,
+            peek_token: next.token,
+            current_span: current.span,
+            peek_span: next.span,
+            current_doc: current.doc,
+            peek_doc: next.doc,
+            loop_depth: 0,
+            errors: vec![],
         }
     }
 
     pub fn next_token(&mut self) {
-        self.current_token = self.peek_token.clone();
-        self.peek_token = self.lexer.next_token();
+        let next = self.lexer.next_spanned_token();
+
+        self.current_token = std::mem::replace(&mut self.peek_token, next.token);
+        self.current_span = std::mem::replace(&mut self.peek_span, next.span);
+        self.current_doc = std::mem::replace(&mut self.peek_doc, next.doc);
     }
 
-    pub fn expect_token(&mut self, token: Token) -> Result<(), String> {
+    pub fn span_from(&self, start: Span) -> Span {
+        start.to(&self.current_span)
+    }
+
+    pub fn current_error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError::new(kind, self.current_token.clone(), self.current_span)
+    }
+
+    pub fn peek_error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError::new(kind, self.peek_token.clone(), self.peek_span)
+    }
+
+    pub fn expect_token(&mut self, token: Token) -> ParsableResult<()> {
         if self.peek_token.is(&token) {
             self.next_token();
             Ok(())
         } else {
-            Err(format!(
-                "invalid token, expected '{:?}' got '{:?}'",
-                token, self.peek_token,
-            ))
+            Err(self.peek_error(ParseErrorKind::UnexpectedToken { expected: token }))
+        }
+    }
+
+    // skip to the end of the broken statement, stepping over any nested blocks
+    // and leaving the closing brace of the enclosing block to its parser
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+
+        loop {
+            match self.current_token {
+                Token::LBRACE => depth += 1,
+                Token::RBRACE if depth > 0 => depth -= 1,
+                Token::RBRACE | Token::EOF => return,
+                Token::SEMICOLON if depth == 0 => return,
+                _ => {}
+            }
+
+            if depth == 0 && (self.peek_token.is(&Token::RBRACE) || self.peek_token.is(&Token::EOF))
+            {
+                return;
+            }
+
+            self.next_token();
         }
     }
 
@@ -58,6 +113,9 @@ impl Parser {
         match self.current_token {
             Token::LET => Some(LetStatement::parse(self)),
             Token::RETURN => Some(ReturnStatement::parse(self)),
+            Token::WHILE => Some(WhileStatement::parse(self)),
+            Token::BREAK => Some(BreakStatement::parse(self)),
+            Token::CONTINUE => Some(ContinueStatement::parse(self)),
             _ => Some(ExpressionStatement::parse(self)),
         }
     }
@@ -66,15 +124,20 @@ impl Parser {
         match self.current_token.clone() {
             Token::IDENT(_) => Identifier::parse_prefix(self),
             Token::INT(_) => IntegerLiteral::parse_prefix(self),
+            Token::FLOAT(_) => FloatLiteral::parse_prefix(self),
             Token::TRUE | Token::FALSE => BooleanLiteral::parse_prefix(self),
             Token::BANG | Token::MINUS => PrefixExpression::parse_prefix(self),
             Token::LPAREN => GroupedExpression::parse_prefix(self),
             Token::IF => IfExpression::parse_prefix(self),
+            Token::FOR => ForExpression::parse_prefix(self),
+            Token::MATCH => MatchExpression::parse_prefix(self),
             Token::FUNCTION => FunctionExpression::parse_prefix(self),
             Token::STRING(_) => StringLiteral::parse_prefix(self),
             Token::LBRACKET => ArrayLiteral::parse_prefix(self),
             Token::LBRACE => HashLiteral::parse_prefix(self),
-            e => Err(format!("Invalid prefix token {:?}", e)),
+            Token::ILLEGAL => Err(self.current_error(ParseErrorKind::IllegalToken)),
+            Token::ERROR(_) => Err(self.current_error(ParseErrorKind::InvalidToken)),
+            _ => Err(self.current_error(ParseErrorKind::ExpectedExpression)),
         }
     }
 
@@ -84,10 +147,23 @@ impl Parser {
             | Token::MINUS
             | Token::SLASH
             | Token::ASTERISK
+            | Token::PERCENT
             | Token::EQ
             | Token::NOT_EQ
             | Token::LT
-            | Token::GT => Some(InfixExpression::parse_infix),
+            | Token::GT
+            | Token::LT_EQ
+            | Token::GT_EQ
+            | Token::AND
+            | Token::OR
+            | Token::DOT_DOT
+            | Token::DOT_DOT_EQ => Some(InfixExpression::parse_infix),
+            Token::ASSIGN
+            | Token::PLUS_ASSIGN
+            | Token::MINUS_ASSIGN
+            | Token::ASTERISK_ASSIGN
+            | Token::SLASH_ASSIGN
+            | Token::PERCENT_ASSIGN => Some(AssignExpression::parse_infix),
             Token::LPAREN => Some(CallExpression::parse_infix),
             Token::LBRACKET => Some(IndexExpression::parse_infix),
             _ => None,
@@ -139,46 +215,53 @@ impl Parser {
         Ok(left)
     }
 
-    pub fn parse_program(&mut self) -> (Program, Vec<String>) {
+    pub fn parse_program(&mut self) -> (Program, Vec<ParseError>) {
         let mut program = Program { statements: vec![] };
-        let mut errors = vec![];
 
         while self.current_token != Token::EOF {
             if let Some(result) = self.parse_statement() {
                 match result {
                     Ok(statement) => program.statements.push(statement),
-                    Err(e) => errors.push(e),
+                    Err(e) => {
+                        self.errors.push(e);
+                        self.synchronize();
+                    }
                 }
             }
             self.next_token();
         }
 
-        (program, errors)
+        (program, std::mem::take(&mut self.errors))
     }
 
     pub fn parse_block(&mut self) -> ParsableResult<BlockStatement> {
         let mut block = BlockStatement {
             token: self.current_token.clone(),
             statements: vec![],
+            span: self.current_span,
         };
-        let mut errors = vec![];
         self.next_token();
 
         while !self.current_token.is(&Token::EOF) && !self.current_token.is(&Token::RBRACE) {
             if let Some(result) = self.parse_statement() {
                 match result {
                     Ok(statement) => block.statements.push(statement),
-                    Err(e) => errors.push(e),
+                    Err(e) => {
+                        self.errors.push(e);
+                        self.synchronize();
+
+                        if self.current_token.is(&Token::RBRACE) {
+                            break;
+                        }
+                    }
                 }
             }
             self.next_token();
         }
 
-        if let Some(error) = errors.into_iter().next() {
-            Err(error)
-        } else {
-            Ok(block)
-        }
+        block.span = self.span_from(block.span);
+
+        Ok(block)
     }
 }
 
@@ -186,10 +269,11 @@ impl Parser {
 mod test {
     use crate::{
         ast::{
-            identifier::Identifier, let_statement::LetStatement, program::Program, AstNode,
-            ExpressionNode, StatementNode,
+            identifier::Identifier, let_statement::LetStatement, pattern::Pattern,
+            program::Program, AstNode, ExpressionNode, StatementNode,
         },
-        tokens::token::Token,
+        parser::{parse_error::ParseError, Parser},
+        tokens::{span::Span, token::Token},
     };
 
     #[test]
@@ -197,17 +281,50 @@ mod test {
         let program = Program {
             statements: vec![StatementNode::LetStatement(LetStatement {
                 token: Token::LET,
-                identifier: Identifier {
+                pattern: Pattern::Binding(Identifier {
                     token: Token::IDENT("myVar".into()),
                     value: "myVar".into(),
-                },
+                    span: Span::default(),
+                }),
                 value: ExpressionNode::Identifier(Identifier {
                     token: Token::IDENT("anotherVar".into()),
                     value: "anotherVar".into(),
+                    span: Span::default(),
                 }),
+                span: Span::default(),
+                doc: None,
             })],
         };
 
         assert_eq!(program.string(), "let myVar = anotherVar;")
     }
+
+    #[test]
+    fn test_node_spans() {
+        let input = "let x = 1 + add(2, 3);\nreturn [x][0];";
+        let mut parser = Parser::new(input.into());
+
+        let (program, errors) = parser.parse_program();
+        assert_eq!(errors, Vec::<ParseError>::new());
+
+        let StatementNode::LetStatement(statement) = &program.statements[0] else {
+            panic!("expected LetStatement, got {:?}", program.statements[0]);
+        };
+        assert_eq!(statement.span, Span::new(0, 22, 1, 1));
+        assert_eq!(statement.pattern.span(), Span::new(4, 5, 1, 5));
+
+        let ExpressionNode::InfixExpression(infix) = &statement.value else {
+            panic!("expected InfixExpression, got {:?}", statement.value);
+        };
+        assert_eq!(infix.span, Span::new(8, 21, 1, 9));
+        assert_eq!(infix.right.span(), Span::new(12, 21, 1, 13));
+
+        let StatementNode::ReturnStatement(statement) = &program.statements[1] else {
+            panic!("expected ReturnStatement, got {:?}", program.statements[1]);
+        };
+        assert_eq!(statement.span, Span::new(23, 37, 2, 1));
+        assert_eq!(statement.return_value.span(), Span::new(30, 36, 2, 8));
+
+        assert_eq!(program.span(), Span::new(0, 37, 1, 1));
+    }
 }
@@ -0,0 +1,230 @@
+use std::fmt::Display;
+
+use crate::tokens::{span::Span, token::Token};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedToken { expected: Token },
+    ExpectedIdentifier,
+    ExpectedExpression,
+    ExpectedPattern,
+    IllegalToken,
+    InvalidToken,
+    InvalidAssignmentTarget,
+    OutsideLoop,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub found: Token,
+    pub span: Span,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, found: Token, span: Span) -> ParseError {
+        ParseError { kind, found, span }
+    }
+
+    pub fn message(&self) -> String {
+        match &self.kind {
+            ParseErrorKind::UnexpectedToken { expected } => {
+                format!("expected {}, found {}", expected, self.found)
+            }
+            ParseErrorKind::ExpectedIdentifier => {
+                format!("expected identifier, found {}", self.found)
+            }
+            ParseErrorKind::ExpectedExpression => {
+                format!("expected expression, found {}", self.found)
+            }
+            ParseErrorKind::ExpectedPattern => {
+                format!("expected pattern, found {}", self.found)
+            }
+            ParseErrorKind::IllegalToken => "illegal character".to_string(),
+            ParseErrorKind::InvalidToken => self.found.to_string(),
+            ParseErrorKind::InvalidAssignmentTarget => "invalid assignment target".to_string(),
+            ParseErrorKind::OutsideLoop => format!("{} outside of a loop", self.found),
+        }
+    }
+
+    pub fn render(&self, source: &str) -> String {
+        let line = source.lines().nth(self.span.line - 1).unwrap_or("");
+        let gutter = " ".repeat(self.span.line.to_string().len());
+
+        let width = source
+            .get(self.span.start..self.span.end)
+            .map(|s| s.chars().take_while(|c| *c != '\n').count())
+            .unwrap_or(0)
+            .max(1);
+
+        format!(
+            "error: {}\n{gutter}--> {}\n{gutter} |\n{} | {}\n{gutter} | {}{}",
+            self.message(),
+            self.span,
+            self.span.line,
+            line,
+            " ".repeat(self.span.column - 1),
+            "^".repeat(width),
+        )
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.message(), self.span)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::{
+        parser::{parse_error::ParseErrorKind, Parser},
+        tokens::{span::Span, token::Token},
+    };
+
+    use super::ParseError;
+
+    fn parse_errors(input: &str) -> Vec<ParseError> {
+        let mut parser = Parser::new(input.into());
+        let (_, errors) = parser.parse_program();
+        errors
+    }
+
+    #[rstest]
+    #[case("let x = (1;", "expected ')', found ';'")]
+    #[case("let = 5;", "expected identifier, found '='")]
+    #[case("let x 5;", "expected '=', found integer `5`")]
+    #[case("1 + ;", "expected expression, found ';'")]
+    #[case("fn(1) {}", "expected identifier, found integer `1`")]
+    #[case("[1, 2", "expected ']', found end of input")]
+    #[case("let x = @;", "illegal character")]
+    #[case("let x = \"abc", "unterminated string")]
+    #[case("let x = 1; /* never closed", "unterminated block comment")]
+    #[case(
+        "1 + 9223372036854775808",
+        "integer literal `9223372036854775808` is too large"
+    )]
+    #[case("let x = \"a\\qb\";", "unknown escape sequence '\\q'")]
+    #[case("let x = 1e;", "exponent of `1e` has no digits")]
+    #[case(
+        "puts(1 \"\\u{110000}\")",
+        "expected ')', found invalid unicode escape '\\u{110000}'"
+    )]
+    fn test_error_message(#[case] input: &str, #[case] expected: &str) {
+        let errors = parse_errors(input);
+
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].message(), expected);
+    }
+
+    #[test]
+    fn test_reports_all_errors() {
+        let input = "let x = (1;
+let y = 2;
+let = 5;
+let f = fn(a) {
+  a +;
+  let b 3;
+  a
+};
+f(y);";
+        let mut parser = Parser::new(input.into());
+        let (program, errors) = parser.parse_program();
+
+        assert_eq!(
+            errors,
+            vec![
+                ParseError::new(
+                    ParseErrorKind::UnexpectedToken {
+                        expected: Token::RPAREN
+                    },
+                    Token::SEMICOLON,
+                    Span::new(10, 11, 1, 11)
+                ),
+                ParseError::new(
+                    ParseErrorKind::ExpectedIdentifier,
+                    Token::ASSIGN,
+                    Span::new(27, 28, 3, 5)
+                ),
+                ParseError::new(
+                    ParseErrorKind::ExpectedExpression,
+                    Token::SEMICOLON,
+                    Span::new(53, 54, 5, 6)
+                ),
+                ParseError::new(
+                    ParseErrorKind::UnexpectedToken {
+                        expected: Token::ASSIGN
+                    },
+                    Token::INT(3),
+                    Span::new(63, 64, 6, 9)
+                ),
+            ]
+        );
+
+        // statements around the broken ones are still parsed
+        assert_eq!(program.statements.len(), 3);
+    }
+
+    #[test]
+    fn test_render() {
+        let input = "let a = 1;\nlet b = (a + 2;\n";
+        let errors = parse_errors(input);
+
+        assert_eq!(
+            errors[0].render(input),
+            "error: expected ')', found ';'
+ --> 2:15
+  |
+2 | let b = (a + 2;
+  |               ^"
+        );
+    }
+
+    #[test]
+    fn test_render_wide_token() {
+        let input = "let x = 1 + if;";
+        let errors = parse_errors(input);
+
+        assert_eq!(
+            errors[0].render(input),
+            "error: expected '(', found ';'
+ --> 1:15
+  |
+1 | let x = 1 + if;
+  |               ^"
+        );
+
+        let input = "let 12345 = 1;";
+        let errors = parse_errors(input);
+
+        assert_eq!(
+            errors[0].render(input),
+            "error: expected identifier, found integer `12345`
+ --> 1:5
+  |
+1 | let 12345 = 1;
+  |     ^^^^^"
+        );
+    }
+
+    #[test]
+    fn test_render_unicode() {
+        let input = "let ééé = ;";
+        let errors = parse_errors(input);
+
+        assert_eq!(
+            errors[0].to_string(),
+            "expected expression, found ';' at 1:11"
+        );
+        assert_eq!(
+            errors[0].render(input),
+            "error: expected expression, found ';'
+ --> 1:11
+  |
+1 | let ééé = ;
+  |           ^"
+        );
+    }
+}
@@ -1,8 +1,13 @@
 use crate::tokens::token::Token;
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
+#[allow(non_camel_case_types)]
 pub enum Precedence {
     LOWEST,
+    ASSIGN,
+    RANGE,
+    LOGICAL_OR,
+    LOGICAL_AND,
     EQUALS,
     LESSGREATER,
     SUM,
@@ -15,14 +20,26 @@ pub enum Precedence {
 impl From<&Token> for Precedence {
     fn from(value: &Token) -> Self {
         match value {
+            Token::ASSIGN
+            | Token::PLUS_ASSIGN
+            | Token::MINUS_ASSIGN
+            | Token::ASTERISK_ASSIGN
+            | Token::SLASH_ASSIGN
+            | Token::PERCENT_ASSIGN => Precedence::ASSIGN,
+            Token::DOT_DOT | Token::DOT_DOT_EQ => Precedence::RANGE,
+            Token::OR => Precedence::LOGICAL_OR,
+            Token::AND => Precedence::LOGICAL_AND,
             Token::EQ => Precedence::EQUALS,
             Token::NOT_EQ => Precedence::EQUALS,
             Token::LT => Precedence::LESSGREATER,
             Token::GT => Precedence::LESSGREATER,
+            Token::LT_EQ => Precedence::LESSGREATER,
+            Token::GT_EQ => Precedence::LESSGREATER,
             Token::PLUS => Precedence::SUM,
             Token::MINUS => Precedence::SUM,
             Token::SLASH => Precedence::PRODUCT,
             Token::ASTERISK => Precedence::PRODUCT,
+            Token::PERCENT => Precedence::PRODUCT,
             Token::LPAREN => Precedence::CALL,
             Token::LBRACKET => Precedence::INDEX,
             _ => Precedence::LOWEST,
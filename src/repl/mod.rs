@@ -1,3 +1,2 @@
 mod repl_start;
-pub use repl_start::repl_run;
 pub use repl_start::start;
@@ -1,44 +1,23 @@
 use std::io::{self, stdout, BufRead, Write};
 
-use crate::{compiler::Compiler, parser::Parser, vm::Vm};
+use crate::engine::Engine;
 
 const PROMPT: &str = ">>";
 
-pub fn repl_run(compiler: &mut Compiler, vm: &mut Vm, line: String) -> Result<String, String> {
-    let mut parser = Parser::new(line);
-
-    let (program, errors) = parser.parse_program();
-
-    if !errors.is_empty() {
-        Err(errors.join("\n").to_string())?;
-    }
-
-    compiler.compile((&program).into())?;
-
-    vm.with_bytecode(compiler.bytecode());
-
-    vm.run()?;
-
-    let result = vm.last_popped();
-    Ok(format!("{}", result))
-}
-
 pub fn start() {
     let stdin = io::stdin();
 
     print!("{}", PROMPT);
     stdout().flush().expect("failed to flush stdout");
 
-    let mut compiler = Compiler::new();
-    let mut vm = Vm::new();
+    let mut engine = Engine::new();
 
     for line in stdin.lock().lines() {
         let line = line.expect("failed to read line from stdin");
-        compiler = compiler.new_from();
 
-        match repl_run(&mut compiler, &mut vm, line) {
+        match engine.run(&line) {
             Ok(result) => println!("{result}"),
-            Err(e) => println!("{e}"),
+            Err(e) => println!("{}", e.render(&line)),
         }
 
         print!("{}", PROMPT);
@@ -1,10 +1,15 @@
-use crate::tokens::token::Token;
+use crate::tokens::{
+    span::Span,
+    token::{SpannedToken, Token},
+};
 
 pub struct Lexer {
     input: String,
     position: usize,
     read_position: usize,
-    ch: u8,
+    ch: char,
+    line: usize,
+    line_start: usize,
 }
 
 impl Lexer {
@@ -13,93 +18,339 @@ impl Lexer {
             input,
             position: 0,
             read_position: 0,
-            ch: 0,
+            ch: '\0',
+            line: 1,
+            line_start: 0,
         };
         lexer.read_char();
         lexer
     }
 
     fn read_char(&mut self) {
-        if self.read_position >= self.input.len() {
-            self.ch = 0;
-        } else {
-            self.ch = self.input.as_bytes()[self.read_position]
+        if self.ch == '\n' {
+            self.line += 1;
+            self.line_start = self.read_position;
         }
 
+        self.ch = self.peek_char();
         self.position = self.read_position;
-        self.read_position += 1;
+        self.read_position += self.ch.len_utf8();
     }
 
-    fn peek_char(&mut self) -> u8 {
-        if self.read_position > self.input.len() {
-            0
-        } else {
-            self.input.as_bytes()[self.read_position]
-        }
+    fn peek_char(&self) -> char {
+        self.input
+            .get(self.read_position..)
+            .and_then(|rest| rest.chars().next())
+            .unwrap_or('\0')
     }
 
     fn skip_whitespace(&mut self) {
-        while self.ch.is_ascii_whitespace() {
+        while self.ch.is_whitespace() {
             self.read_char()
         }
     }
 
+    // skips whitespace and comments, returning the text of any `///` doc
+    // comments so they can be attached to the next token, or an error token
+    // spanning a block comment that is never closed
+    fn skip_trivia(&mut self) -> Result<Option<String>, SpannedToken> {
+        let mut doc = vec![];
+
+        loop {
+            self.skip_whitespace();
+
+            match (self.ch, self.peek_char()) {
+                ('/', '/') => {
+                    let comment = self.read_line_comment();
+
+                    if let Some(text) = comment.strip_prefix('/') {
+                        if !text.starts_with('/') {
+                            doc.push(text.strip_prefix(' ').unwrap_or(text).to_string());
+                        }
+                    }
+                }
+                ('/', '*') => {
+                    let start = self.position;
+                    let line = self.line;
+                    let column = self.column(start);
+
+                    if !self.skip_block_comment() {
+                        return Err(SpannedToken {
+                            token: Token::ERROR("unterminated block comment".into()),
+                            span: Span::new(start, self.input.len(), line, column),
+                            doc: None,
+                        });
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        Ok((!doc.is_empty()).then(|| doc.join("\n")))
+    }
+
+    fn read_line_comment(&mut self) -> String {
+        let start = self.position + 2;
+
+        while self.ch != '\n' && self.ch != '\0' {
+            self.read_char();
+        }
+
+        self.input[start..self.position].to_string()
+    }
+
+    // false when the input ends before the comment is closed
+    fn skip_block_comment(&mut self) -> bool {
+        let mut depth = 0;
+
+        loop {
+            match (self.ch, self.peek_char()) {
+                ('\0', _) if self.position >= self.input.len() => return false,
+                ('/', '*') => {
+                    depth += 1;
+                    self.read_char();
+                }
+                ('*', '/') => {
+                    depth -= 1;
+                    self.read_char();
+
+                    if depth == 0 {
+                        self.read_char();
+                        return true;
+                    }
+                }
+                _ => {}
+            }
+
+            self.read_char();
+        }
+    }
+
     fn read_identifier(&mut self) -> String {
         let start = self.position;
-        while self.ch.is_ascii_alphanumeric() || self.ch as char == '_' {
+        while self.ch.is_alphanumeric() || self.ch == '_' {
             self.read_char();
         }
         self.input[start..self.position].into()
     }
 
-    fn read_number(&mut self) -> i64 {
+    fn read_digits(&mut self) {
+        while self.ch.is_ascii_digit() {
+            self.read_char();
+        }
+    }
+
+    // a fraction is only consumed when digits follow it, so `1.` still lexes
+    // as an integer followed by another token, but an exponent always needs
+    // its digits
+    fn read_number(&mut self) -> Token {
         let start = self.position;
-        while (self.ch as char).is_numeric() {
+        let mut float = false;
+
+        self.read_digits();
+
+        if self.ch == '.' && self.peek_char().is_ascii_digit() {
+            float = true;
+            self.read_char();
+            self.read_digits();
+        }
+
+        if matches!(self.ch, 'e' | 'E') {
+            float = true;
             self.read_char();
+            if matches!(self.ch, '+' | '-') {
+                self.read_char();
+            }
+
+            if !self.ch.is_ascii_digit() {
+                return Token::ERROR(format!(
+                    "exponent of `{}` has no digits",
+                    &self.input[start..self.position]
+                ));
+            }
+            self.read_digits();
+        }
+
+        let literal = &self.input[start..self.position];
+
+        if float {
+            Token::FLOAT(literal.parse().expect("parsing of float failed"))
+        } else {
+            match literal.parse() {
+                Ok(value) => Token::INT(value),
+                Err(_) => Token::ERROR(format!("integer literal `{}` is too large", literal)),
+            }
         }
-        self.input[start..self.position]
-            .parse()
-            .expect("parsing of number failed")
     }
 
-    fn read_string(&mut self) -> String {
-        let position = self.position + 1;
+    fn read_string(&mut self) -> Token {
+        let mut string = String::new();
+        let mut error = None;
+
         loop {
             self.read_char();
-            if self.ch as char == '"' || self.ch == 0 {
-                break;
+
+            match self.ch {
+                '"' => break,
+                '\0' if self.position >= self.input.len() => {
+                    return Token::ERROR("unterminated string".into())
+                }
+                '\\' => {
+                    self.read_char();
+
+                    match self.read_escape() {
+                        Ok(c) => string.push(c),
+                        Err(e) => error = error.or(Some(e)),
+                    }
+                }
+                c => string.push(c),
             }
         }
 
-        self.input[position..self.position].to_string()
+        match error {
+            Some(e) => Token::ERROR(e),
+            None => Token::STRING(string),
+        }
+    }
+
+    fn read_escape(&mut self) -> Result<char, String> {
+        match self.ch {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            'u' => self.read_unicode_escape(),
+            '\0' if self.position >= self.input.len() => Err("unterminated string".into()),
+            c => Err(format!("unknown escape sequence '\\{}'", c)),
+        }
+    }
+
+    fn read_unicode_escape(&mut self) -> Result<char, String> {
+        if self.peek_char() != '{' {
+            return Err("expected '{' after '\\u'".into());
+        }
+        self.read_char();
+
+        let start = self.read_position;
+        while self.peek_char().is_ascii_hexdigit() {
+            self.read_char();
+        }
+        let digits = self.input[start..self.read_position].to_string();
+
+        if self.peek_char() != '}' {
+            return Err("unterminated unicode escape".into());
+        }
+        self.read_char();
+
+        if digits.is_empty() || digits.len() > 6 {
+            return Err(format!("invalid unicode escape '\\u{{{}}}'", digits));
+        }
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("invalid unicode escape '\\u{{{}}}'", digits))
+    }
+
+    fn read_compound(&mut self, operator: Token, assign: Token) -> Token {
+        if self.peek_char() == '=' {
+            self.read_char();
+            assign
+        } else {
+            operator
+        }
     }
 
     pub fn next_token(&mut self) -> Token {
-        self.skip_whitespace();
+        self.next_spanned_token().token
+    }
+
+    // columns count chars, so they match what an editor shows for the line
+    fn column(&self, position: usize) -> usize {
+        self.input[self.line_start..position].chars().count() + 1
+    }
+
+    pub fn next_spanned_token(&mut self) -> SpannedToken {
+        let doc = match self.skip_trivia() {
+            Ok(doc) => doc,
+            Err(error) => return error,
+        };
+
+        let start = self.position.min(self.input.len());
+        let line = self.line;
+        let column = self.column(start);
 
-        let token = match self.ch as char {
+        let token = self.read_token();
+
+        let end = self.position.min(self.input.len());
+
+        SpannedToken {
+            token,
+            span: Span::new(start, end, line, column),
+            doc,
+        }
+    }
+
+    fn read_token(&mut self) -> Token {
+        let token = match self.ch {
             // operators
-            '=' => match self.peek_char() as char {
+            '=' => match self.peek_char() {
                 '=' => {
                     self.read_char();
                     Token::EQ
                 }
+                '>' => {
+                    self.read_char();
+                    Token::FAT_ARROW
+                }
                 _ => Token::ASSIGN,
             },
-            '!' => match self.peek_char() as char {
+            '!' => match self.peek_char() {
                 '=' => {
                     self.read_char();
                     Token::NOT_EQ
                 }
                 _ => Token::BANG,
             },
-            '+' => Token::PLUS,
-            '-' => Token::MINUS,
-            '*' => Token::ASTERISK,
-            '/' => Token::SLASH,
+            '+' => self.read_compound(Token::PLUS, Token::PLUS_ASSIGN),
+            '-' => self.read_compound(Token::MINUS, Token::MINUS_ASSIGN),
+            '*' => self.read_compound(Token::ASTERISK, Token::ASTERISK_ASSIGN),
+            '/' => self.read_compound(Token::SLASH, Token::SLASH_ASSIGN),
+            '%' => self.read_compound(Token::PERCENT, Token::PERCENT_ASSIGN),
 
-            '<' => Token::LT,
-            '>' => Token::GT,
+            '<' => match self.peek_char() {
+                '=' => {
+                    self.read_char();
+                    Token::LT_EQ
+                }
+                _ => Token::LT,
+            },
+            '>' => match self.peek_char() {
+                '=' => {
+                    self.read_char();
+                    Token::GT_EQ
+                }
+                _ => Token::GT,
+            },
+            '&' if self.peek_char() == '&' => {
+                self.read_char();
+                Token::AND
+            }
+            '|' if self.peek_char() == '|' => {
+                self.read_char();
+                Token::OR
+            }
+            '.' if self.peek_char() == '.' => {
+                self.read_char();
+                if self.peek_char() == '.' {
+                    self.read_char();
+                    Token::ELLIPSIS
+                } else {
+                    self.read_compound(Token::DOT_DOT, Token::DOT_DOT_EQ)
+                }
+            }
 
             // delmiters
             ',' => Token::COMMA,
@@ -114,9 +365,9 @@ impl Lexer {
             ']' => Token::RBRACKET,
 
             '\0' => Token::EOF,
-            '"' => Token::STRING(self.read_string()),
-            c if c.is_ascii_alphabetic() => return Token::from_ident(self.read_identifier()),
-            c if c.is_numeric() => return Token::INT(self.read_number()),
+            '"' => self.read_string(),
+            c if c.is_alphabetic() || c == '_' => return Token::from_ident(self.read_identifier()),
+            c if c.is_ascii_digit() => return self.read_number(),
             _ => Token::ILLEGAL,
         };
 
@@ -126,6 +377,348 @@ impl Lexer {
     }
 }
 
+#[test]
+fn test_token_spans() {
+    let input = "let five = 5;\n  five + \"ten\";";
+    let mut lexer = Lexer::new(input.into());
+
+    let expected = [
+        (Token::LET, Span::new(0, 3, 1, 1)),
+        (Token::IDENT("five".into()), Span::new(4, 8, 1, 5)),
+        (Token::ASSIGN, Span::new(9, 10, 1, 10)),
+        (Token::INT(5), Span::new(11, 12, 1, 12)),
+        (Token::SEMICOLON, Span::new(12, 13, 1, 13)),
+        (Token::IDENT("five".into()), Span::new(16, 20, 2, 3)),
+        (Token::PLUS, Span::new(21, 22, 2, 8)),
+        (Token::STRING("ten".into()), Span::new(23, 28, 2, 10)),
+        (Token::SEMICOLON, Span::new(28, 29, 2, 15)),
+        (Token::EOF, Span::new(29, 29, 2, 16)),
+    ];
+
+    for (token, span) in expected {
+        assert_eq!(
+            lexer.next_spanned_token(),
+            SpannedToken {
+                token,
+                span,
+                doc: None
+            }
+        );
+    }
+}
+
+#[test]
+fn test_string_escapes() {
+    let input = r#""a\nb" "\t\r\0" "\\\"" "\u{1F600}\u{e9}" "café 😀""#;
+    let mut lexer = Lexer::new(input.into());
+
+    assert_eq!(lexer.next_token(), Token::STRING("a\nb".into()));
+    assert_eq!(lexer.next_token(), Token::STRING("\t\r\0".into()));
+    assert_eq!(lexer.next_token(), Token::STRING("\\\"".into()));
+    assert_eq!(lexer.next_token(), Token::STRING("\u{1F600}\u{e9}".into()));
+    assert_eq!(lexer.next_token(), Token::STRING("café 😀".into()));
+    assert_eq!(lexer.next_token(), Token::EOF);
+}
+
+#[test]
+fn test_string_errors() {
+    let input = r#""\x" "\u{}" "\u{d800}" "\u1F600" "ok" "open"#;
+    let mut lexer = Lexer::new(input.into());
+
+    assert_eq!(
+        lexer.next_token(),
+        Token::ERROR("unknown escape sequence '\\x'".into())
+    );
+    assert_eq!(
+        lexer.next_token(),
+        Token::ERROR("invalid unicode escape '\\u{}'".into())
+    );
+    assert_eq!(
+        lexer.next_token(),
+        Token::ERROR("invalid unicode escape '\\u{d800}'".into())
+    );
+    assert_eq!(
+        lexer.next_token(),
+        Token::ERROR("expected '{' after '\\u'".into())
+    );
+    assert_eq!(lexer.next_token(), Token::STRING("ok".into()));
+    assert_eq!(
+        lexer.next_token(),
+        Token::ERROR("unterminated string".into())
+    );
+    assert_eq!(lexer.next_token(), Token::EOF);
+}
+
+#[test]
+fn test_unicode_source() {
+    let input = "let größe = \"😀\";\n_naïve ü";
+    let mut lexer = Lexer::new(input.into());
+
+    let expected = [
+        (Token::LET, Span::new(0, 3, 1, 1)),
+        (Token::IDENT("größe".into()), Span::new(4, 11, 1, 5)),
+        (Token::ASSIGN, Span::new(12, 13, 1, 11)),
+        (Token::STRING("😀".into()), Span::new(14, 20, 1, 13)),
+        (Token::SEMICOLON, Span::new(20, 21, 1, 16)),
+        (Token::IDENT("_naïve".into()), Span::new(22, 29, 2, 1)),
+        (Token::IDENT("ü".into()), Span::new(30, 32, 2, 8)),
+        (Token::EOF, Span::new(32, 32, 2, 9)),
+    ];
+
+    for (token, span) in expected {
+        let spanned = lexer.next_spanned_token();
+        assert_eq!((spanned.token, spanned.span), (token, span));
+    }
+}
+
+#[test]
+fn test_numbers() {
+    let input = "5 1.5 0.25 2e3 1.5E-2 7e+1 1. x";
+    let mut lexer = Lexer::new(input.into());
+
+    assert_eq!(lexer.next_token(), Token::INT(5));
+    assert_eq!(lexer.next_token(), Token::FLOAT(1.5));
+    assert_eq!(lexer.next_token(), Token::FLOAT(0.25));
+    assert_eq!(lexer.next_token(), Token::FLOAT(2000.0));
+    assert_eq!(lexer.next_token(), Token::FLOAT(0.015));
+    assert_eq!(lexer.next_token(), Token::FLOAT(70.0));
+    assert_eq!(lexer.next_token(), Token::INT(1));
+    assert_eq!(lexer.next_token(), Token::ILLEGAL);
+    assert_eq!(lexer.next_token(), Token::IDENT("x".into()));
+    assert_eq!(lexer.next_token(), Token::EOF);
+}
+
+#[test]
+fn test_exponent_without_digits() {
+    let input = "1e 1e+ 1.5E 2e-x 3";
+    let mut lexer = Lexer::new(input.into());
+
+    let expected = [
+        (
+            Token::ERROR("exponent of `1e` has no digits".into()),
+            Span::new(0, 2, 1, 1),
+        ),
+        (
+            Token::ERROR("exponent of `1e+` has no digits".into()),
+            Span::new(3, 6, 1, 4),
+        ),
+        (
+            Token::ERROR("exponent of `1.5E` has no digits".into()),
+            Span::new(7, 11, 1, 8),
+        ),
+        (
+            Token::ERROR("exponent of `2e-` has no digits".into()),
+            Span::new(12, 15, 1, 13),
+        ),
+        (Token::IDENT("x".into()), Span::new(15, 16, 1, 16)),
+        (Token::INT(3), Span::new(17, 18, 1, 18)),
+        (Token::EOF, Span::new(18, 18, 1, 19)),
+    ];
+
+    for (token, span) in expected {
+        let spanned = lexer.next_spanned_token();
+        assert_eq!((spanned.token, spanned.span), (token, span));
+    }
+}
+
+#[test]
+fn test_comparison_and_logical_operators() {
+    let input = "a <= b >= c % d && e || f < g & h |";
+    let mut lexer = Lexer::new(input.into());
+
+    let expected = [
+        Token::IDENT("a".into()),
+        Token::LT_EQ,
+        Token::IDENT("b".into()),
+        Token::GT_EQ,
+        Token::IDENT("c".into()),
+        Token::PERCENT,
+        Token::IDENT("d".into()),
+        Token::AND,
+        Token::IDENT("e".into()),
+        Token::OR,
+        Token::IDENT("f".into()),
+        Token::LT,
+        Token::IDENT("g".into()),
+        Token::ILLEGAL,
+        Token::IDENT("h".into()),
+        Token::ILLEGAL,
+        Token::EOF,
+    ];
+
+    for token in expected {
+        assert_eq!(lexer.next_token(), token);
+    }
+}
+
+#[test]
+fn test_assignment_operators() {
+    let input = "x = 1; x += 2; x -= 3; x *= 4; x /= 5; x %= 6; x == 7";
+    let mut lexer = Lexer::new(input.into());
+
+    let operators = [
+        Token::ASSIGN,
+        Token::PLUS_ASSIGN,
+        Token::MINUS_ASSIGN,
+        Token::ASTERISK_ASSIGN,
+        Token::SLASH_ASSIGN,
+        Token::PERCENT_ASSIGN,
+        Token::EQ,
+    ];
+
+    for (i, operator) in operators.into_iter().enumerate() {
+        assert_eq!(lexer.next_token(), Token::IDENT("x".into()));
+        assert_eq!(lexer.next_token(), operator);
+        assert_eq!(lexer.next_token(), Token::INT(i as i64 + 1));
+        if i < 6 {
+            assert_eq!(lexer.next_token(), Token::SEMICOLON);
+        }
+    }
+    assert_eq!(lexer.next_token(), Token::EOF);
+}
+
+#[test]
+fn test_ranges() {
+    let input = "0..10 1..=n 1.5..2 a.b [...xs]";
+    let mut lexer = Lexer::new(input.into());
+
+    let expected = [
+        Token::INT(0),
+        Token::DOT_DOT,
+        Token::INT(10),
+        Token::INT(1),
+        Token::DOT_DOT_EQ,
+        Token::IDENT("n".into()),
+        Token::FLOAT(1.5),
+        Token::DOT_DOT,
+        Token::INT(2),
+        Token::IDENT("a".into()),
+        Token::ILLEGAL,
+        Token::IDENT("b".into()),
+        Token::LBRACKET,
+        Token::ELLIPSIS,
+        Token::IDENT("xs".into()),
+        Token::RBRACKET,
+        Token::EOF,
+    ];
+
+    for token in expected {
+        assert_eq!(lexer.next_token(), token);
+    }
+}
+
+#[test]
+fn test_loop_keywords() {
+    let input = "while for in break continue index";
+    let mut lexer = Lexer::new(input.into());
+
+    let expected = [
+        Token::WHILE,
+        Token::FOR,
+        Token::IN,
+        Token::BREAK,
+        Token::CONTINUE,
+        Token::IDENT("index".into()),
+        Token::EOF,
+    ];
+
+    for token in expected {
+        assert_eq!(lexer.next_token(), token);
+    }
+}
+
+#[test]
+fn test_match_tokens() {
+    let input = "match (x) { 1 => a, _ => b } == >=";
+    let mut lexer = Lexer::new(input.into());
+
+    let expected = [
+        Token::MATCH,
+        Token::LPAREN,
+        Token::IDENT("x".into()),
+        Token::RPAREN,
+        Token::LBRACE,
+        Token::INT(1),
+        Token::FAT_ARROW,
+        Token::IDENT("a".into()),
+        Token::COMMA,
+        Token::IDENT("_".into()),
+        Token::FAT_ARROW,
+        Token::IDENT("b".into()),
+        Token::RBRACE,
+        Token::EQ,
+        Token::GT_EQ,
+        Token::EOF,
+    ];
+
+    for token in expected {
+        assert_eq!(lexer.next_token(), token);
+    }
+}
+
+#[test]
+fn test_comments() {
+    let input = "// leading comment
+let a = 10 / 2; // trailing comment
+/* block /* nested */ still
+   a comment */ a
+/**/ 1 /";
+    let mut lexer = Lexer::new(input.into());
+
+    assert_eq!(lexer.next_token(), Token::LET);
+    assert_eq!(lexer.next_token(), Token::IDENT("a".into()));
+    assert_eq!(lexer.next_token(), Token::ASSIGN);
+    assert_eq!(lexer.next_token(), Token::INT(10));
+    assert_eq!(lexer.next_token(), Token::SLASH);
+    assert_eq!(lexer.next_token(), Token::INT(2));
+    assert_eq!(lexer.next_token(), Token::SEMICOLON);
+    assert_eq!(lexer.next_token(), Token::IDENT("a".into()));
+    assert_eq!(lexer.next_token(), Token::INT(1));
+    assert_eq!(lexer.next_token(), Token::SLASH);
+    assert_eq!(lexer.next_token(), Token::EOF);
+}
+
+#[test]
+fn test_unterminated_block_comment() {
+    let mut lexer = Lexer::new("1 /* /* */\n2".into());
+
+    assert_eq!(lexer.next_token(), Token::INT(1));
+    assert_eq!(
+        lexer.next_spanned_token(),
+        SpannedToken {
+            token: Token::ERROR("unterminated block comment".into()),
+            span: Span::new(2, 12, 1, 3),
+            doc: None
+        }
+    );
+    assert_eq!(lexer.next_token(), Token::EOF);
+}
+
+#[test]
+fn test_doc_comments() {
+    let input = "/// Adds two numbers.
+///
+///Returns their sum.
+// not part of the docs
+//// not a doc comment either
+let add = fn(a, b) { a + b };
+let b = 1;";
+    let mut lexer = Lexer::new(input.into());
+
+    let token = lexer.next_spanned_token();
+    assert_eq!(token.token, Token::LET);
+    assert_eq!(
+        token.doc,
+        Some("Adds two numbers.\n\nReturns their sum.".into())
+    );
+
+    while lexer.next_spanned_token().token != Token::SEMICOLON {}
+
+    let token = lexer.next_spanned_token();
+    assert_eq!(token.token, Token::LET);
+    assert_eq!(token.doc, None);
+}
+
 #[test]
 fn test_basic_tokens() {
     let input = "=+(){},;";
@@ -152,7 +745,7 @@ let add = fn(x, y) {
 };
 
 let result = add(five, ten);
-!-/*5;
+!-/ *5;
 5 < 10 > 5;
 
 if (5 < 10) {
@@ -264,7 +857,7 @@ let add = fn(x, y) {
 };
 
 let result = add(five, ten);
-!-/*5;
+!-/ *5;
 5 < 10 > 5;
 
 if (5 < 10) {
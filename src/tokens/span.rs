@@ -0,0 +1,49 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    // byte offsets into the source, end is exclusive
+    pub start: usize,
+    pub end: usize,
+    // 1-based position of `start`, the column counted in chars
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Span {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    pub fn to(&self, other: &Span) -> Span {
+        Span {
+            end: other.end.max(self.end),
+            ..*self
+        }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Span;
+
+    #[test]
+    fn test_span_to() {
+        let a = Span::new(0, 3, 1, 1);
+        let b = Span::new(6, 9, 2, 3);
+
+        assert_eq!(a.to(&b), Span::new(0, 9, 1, 1));
+        assert_eq!(a.to(&b).to_string(), "1:1");
+    }
+}
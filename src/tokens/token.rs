@@ -1,30 +1,53 @@
-use std::mem::{self};
+use std::{
+    fmt::Display,
+    mem::{self},
+};
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+use super::span::Span;
+
+#[derive(PartialEq, Debug, Clone)]
 #[allow(non_camel_case_types)]
 pub enum Token {
     ILLEGAL,
     EOF,
+    ERROR(String),
 
     // identifier + literals
     IDENT(String),
     INT(i64),
+    FLOAT(f64),
     STRING(String),
 
     // operators
     ASSIGN,
+    FAT_ARROW,
+    PLUS_ASSIGN,
+    MINUS_ASSIGN,
+    ASTERISK_ASSIGN,
+    SLASH_ASSIGN,
+    PERCENT_ASSIGN,
     BANG,
     PLUS,
     MINUS,
     ASTERISK,
     SLASH,
+    PERCENT,
 
     LT,
     GT,
+    LT_EQ,
+    GT_EQ,
 
     EQ,
     NOT_EQ,
 
+    AND,
+    OR,
+
+    DOT_DOT,
+    DOT_DOT_EQ,
+    ELLIPSIS,
+
     // delmiters
     COMMA,
     SEMICOLON,
@@ -44,7 +67,13 @@ pub enum Token {
     FALSE,
     IF,
     ELSE,
+    MATCH,
     RETURN,
+    WHILE,
+    FOR,
+    IN,
+    BREAK,
+    CONTINUE,
 }
 
 impl Token {
@@ -56,7 +85,13 @@ impl Token {
             "false" => Token::FALSE,
             "if" => Token::IF,
             "else" => Token::ELSE,
+            "match" => Token::MATCH,
             "return" => Token::RETURN,
+            "while" => Token::WHILE,
+            "for" => Token::FOR,
+            "in" => Token::IN,
+            "break" => Token::BREAK,
+            "continue" => Token::CONTINUE,
 
             _ => Token::IDENT(ident),
         }
@@ -65,4 +100,92 @@ impl Token {
     pub fn is(&self, other: &Token) -> bool {
         mem::discriminant(self) == mem::discriminant(other)
     }
+
+    pub fn literal(&self) -> &'static str {
+        match self {
+            Token::ILLEGAL => "illegal character",
+            Token::EOF => "end of input",
+            Token::ERROR(_) => "invalid token",
+
+            Token::IDENT(_) => "identifier",
+            Token::INT(_) => "integer",
+            Token::FLOAT(_) => "float",
+            Token::STRING(_) => "string",
+
+            Token::ASSIGN => "=",
+            Token::FAT_ARROW => "=>",
+            Token::PLUS_ASSIGN => "+=",
+            Token::MINUS_ASSIGN => "-=",
+            Token::ASTERISK_ASSIGN => "*=",
+            Token::SLASH_ASSIGN => "/=",
+            Token::PERCENT_ASSIGN => "%=",
+            Token::BANG => "!",
+            Token::PLUS => "+",
+            Token::MINUS => "-",
+            Token::ASTERISK => "*",
+            Token::SLASH => "/",
+            Token::PERCENT => "%",
+
+            Token::LT => "<",
+            Token::GT => ">",
+            Token::LT_EQ => "<=",
+            Token::GT_EQ => ">=",
+
+            Token::EQ => "==",
+            Token::NOT_EQ => "!=",
+
+            Token::AND => "&&",
+            Token::OR => "||",
+
+            Token::DOT_DOT => "..",
+            Token::DOT_DOT_EQ => "..=",
+            Token::ELLIPSIS => "...",
+
+            Token::COMMA => ",",
+            Token::SEMICOLON => ";",
+            Token::COLON => ":",
+
+            Token::LPAREN => "(",
+            Token::RPAREN => ")",
+            Token::LBRACE => "{",
+            Token::RBRACE => "}",
+            Token::LBRACKET => "[",
+            Token::RBRACKET => "]",
+
+            Token::FUNCTION => "fn",
+            Token::LET => "let",
+            Token::TRUE => "true",
+            Token::FALSE => "false",
+            Token::IF => "if",
+            Token::ELSE => "else",
+            Token::MATCH => "match",
+            Token::RETURN => "return",
+            Token::WHILE => "while",
+            Token::FOR => "for",
+            Token::IN => "in",
+            Token::BREAK => "break",
+            Token::CONTINUE => "continue",
+        }
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::ILLEGAL | Token::EOF => write!(f, "{}", self.literal()),
+            Token::ERROR(message) => write!(f, "{}", message),
+            Token::IDENT(ident) => write!(f, "identifier `{}`", ident),
+            Token::INT(i) => write!(f, "integer `{}`", i),
+            Token::FLOAT(x) => write!(f, "float `{:?}`", x),
+            Token::STRING(s) => write!(f, "string \"{}\"", s),
+            token => write!(f, "'{}'", token.literal()),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+    pub doc: Option<String>,
 }
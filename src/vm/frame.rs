@@ -1,11 +1,11 @@
-use crate::code::Instructions;
+use std::rc::Rc;
+
+use crate::object::CompiledFunction;
 use crate::vm::Object;
 
 pub struct Frame {
-    pub instructions: Instructions,
+    pub function: Rc<CompiledFunction>,
     pub ip: usize,
     pub base_poiner: usize,
     pub free: Vec<Object>,
-    pub num_locals: usize,
-    pub num_parameters: usize,
 }
@@ -1,22 +1,27 @@
 mod frame;
+pub mod runtime_error;
+pub mod verifier;
 
 use core::panic;
-use std::{collections::HashMap, usize};
+use std::{collections::HashMap, mem, rc::Rc};
 
 use crate::{
     builtin::{BuiltinFunction, BUILTINS},
     code::{
         read_operands::{read_u16, read_u8},
-        Instructions, Opcode,
+        Opcode,
     },
     compiler::Bytecode,
-    object::Object,
+    object::{check_arity, iterator::ObjectIterator, CompiledFunction, Object},
 };
 
-use self::frame::Frame;
+use self::{
+    frame::Frame,
+    runtime_error::{RuntimeError, RuntimeErrorKind, TraceFrame},
+};
 
 const STACK_SIZE: usize = 2048;
-const GLOBALS_SIZE: usize = 65536;
+pub(crate) const GLOBALS_SIZE: usize = 65536;
 
 pub struct Vm {
     constants: Vec<Object>,
@@ -28,7 +33,7 @@ pub struct Vm {
     frames: Vec<Frame>,
 }
 
-type R = Result<(), String>;
+type R = Result<(), RuntimeErrorKind>;
 
 impl Vm {
     pub fn new() -> Vm {
@@ -61,13 +66,15 @@ impl Vm {
     }
 
     pub fn with_bytecode(&mut self, bytecode: Bytecode) {
+        let mut main = CompiledFunction::new(bytecode.instructions, 0, 0);
+        main.name = Some("<main>".to_string());
+        main.lines = bytecode.lines;
+
         let frame = Frame {
-            instructions: bytecode.instructions,
+            function: Rc::new(main),
             base_poiner: 0,
             ip: usize::MAX,
             free: vec![],
-            num_locals: 0,
-            num_parameters: 0,
         };
 
         self.constants = bytecode.constants;
@@ -75,6 +82,8 @@ impl Vm {
         self.stack = std::array::from_fn(|_| Object::Null);
         self.sp = 0;
 
+        // a failed run leaves its frames behind
+        self.frames.clear();
         self.push_frame(frame);
     }
 
@@ -87,25 +96,57 @@ impl Vm {
     }
 
     fn exec_binary_integer_op(&mut self, op: Opcode, left: i64, right: i64) -> R {
+        let result = match op {
+            Opcode::OpAdd => left.checked_add(right),
+            Opcode::OpMul => left.checked_mul(right),
+            Opcode::OpDiv if right == 0 => return Err(RuntimeErrorKind::DivisionByZero),
+            Opcode::OpDiv => left.checked_div(right),
+            Opcode::OpMod if right == 0 => return Err(RuntimeErrorKind::ModuloByZero),
+            Opcode::OpMod => left.checked_rem(right),
+            Opcode::OpSub => left.checked_sub(right),
+
+            op => return Err(format!("unkown Integer operation {:?}", op).into()),
+        };
+
+        let Some(result) = result else {
+            let symbol = match op {
+                Opcode::OpAdd => "+",
+                Opcode::OpSub => "-",
+                Opcode::OpMul => "*",
+                Opcode::OpDiv => "/",
+                _ => "%",
+            };
+
+            return Err(RuntimeErrorKind::IntegerOverflow(format!(
+                "{} {} {}",
+                left, symbol, right
+            )));
+        };
+
+        self.push(Object::Integer(result))
+    }
+
+    fn exec_binary_float_op(&mut self, op: Opcode, left: f64, right: f64) -> R {
         let result = match op {
             Opcode::OpAdd => left + right,
             Opcode::OpMul => left * right,
             Opcode::OpDiv => left / right,
+            Opcode::OpMod => left % right,
             Opcode::OpSub => left - right,
 
-            op => return Err(format!("unkown Integer operation {:?}", op)),
+            op => return Err(format!("unkown Float operation {:?}", op).into()),
         };
 
-        self.push(Object::Integer(result))
+        self.push(Object::Float(result))
     }
 
-    fn exec_binary_string_op(&mut self, op: Opcode, left: String, right: String) -> R {
+    fn exec_binary_string_op(&mut self, op: Opcode, left: &str, right: &str) -> R {
         let result = match op {
-            Opcode::OpAdd => left + &right,
-            op => return Err(format!("unkown String operation {:?}", op)),
+            Opcode::OpAdd => format!("{}{}", left, right),
+            _ => return Err(RuntimeErrorKind::TypeMismatch("STRING", "STRING")),
         };
 
-        self.push(Object::String(result))
+        self.push(result.into())
     }
 
     fn exec_binary_op(&mut self, op: Opcode) -> R {
@@ -117,30 +158,60 @@ impl Vm {
                 self.exec_binary_integer_op(op, left, right)
             }
             (Object::String(left), Object::String(right)) => {
-                self.exec_binary_string_op(op, left, right)
+                self.exec_binary_string_op(op, &left, &right)
             }
-            (left, right) => Err(format!(
-                "unsupported types for binary op {} {}",
+            (
+                left @ (Object::Integer(_) | Object::Float(_)),
+                right @ (Object::Integer(_) | Object::Float(_)),
+            ) => self.exec_binary_float_op(op, left.to_float().unwrap(), right.to_float().unwrap()),
+            (left, right) => Err(RuntimeErrorKind::TypeMismatch(
                 left.type_str(),
-                right.type_str()
+                right.type_str(),
             )),
         }
     }
 
+    fn exec_float_comparison(&mut self, op: Opcode, left: f64, right: f64) -> R {
+        let result = match op {
+            Opcode::OpEqual => left == right,
+            Opcode::OpNotEqual => left != right,
+            Opcode::OpGreaterThan => left > right,
+            Opcode::OpGreaterEqual => left >= right,
+            Opcode::OpLessThan => left < right,
+            Opcode::OpLessEqual => left <= right,
+            op => return Err(format!("unsupported operation for comparison op {:?}", op).into()),
+        };
+
+        self.push(Object::Boolean(result))
+    }
+
     fn exec_comparison(&mut self, op: Opcode) -> R {
         let right = self.pop();
         let left = self.pop();
 
+        if matches!(
+            (&left, &right),
+            (Object::Float(_), Object::Integer(_) | Object::Float(_))
+                | (Object::Integer(_), Object::Float(_))
+        ) {
+            let (left, right) = (left.to_float().unwrap(), right.to_float().unwrap());
+            return self.exec_float_comparison(op, left, right);
+        }
+
+        let ordered = match (&left, &right) {
+            (Object::Integer(left), Object::Integer(right)) => Some(left.cmp(right)),
+            _ => None,
+        };
+        let mismatch = || RuntimeErrorKind::TypeMismatch(left.type_str(), right.type_str());
+
         let result = match op {
             Opcode::OpEqual => left == right,
             Opcode::OpNotEqual => left != right,
-            Opcode::OpGreaterThan => {
-                let left: i64 = left.try_into()?;
-                let right: i64 = right.try_into()?;
-
-                left > right
-            }
-            op => return Err(format!("unsupported operation for comparison op {:?}", op)),
+            Opcode::OpGreaterThan => ordered.ok_or_else(mismatch)?.is_gt(),
+            Opcode::OpGreaterEqual => ordered.ok_or_else(mismatch)?.is_ge(),
+            Opcode::OpLessThan => ordered.ok_or_else(mismatch)?.is_lt(),
+            Opcode::OpLessEqual => ordered.ok_or_else(mismatch)?.is_le(),
+            op => return Err(format!("unsupported operation for comparison op {:?}", op).into()),
         };
 
         self.push(Object::Boolean(result))
@@ -159,25 +230,51 @@ impl Vm {
     fn exec_minus(&mut self) -> R {
         let operand = self.pop();
 
-        let Object::Integer(val) = operand else {
-            return Err(format!(
-                "unsupported type for negation: {}",
-                operand.type_str()
-            ));
-        };
+        match operand {
+            Object::Integer(val) => match val.checked_neg() {
+                Some(val) => self.push(Object::Integer(val)),
+                None => Err(RuntimeErrorKind::IntegerOverflow(format!("-({})", val))),
+            },
+            Object::Float(val) => self.push(Object::Float(-val)),
+            _ => Err(format!("unsupported type for negation: {}", operand.type_str()).into()),
+        }
+    }
 
-        self.push(Object::Integer(-val))
+    pub fn run(&mut self) -> Result<(), RuntimeError> {
+        self.execute().map_err(|kind| RuntimeError {
+            kind,
+            frames: self.trace(),
+        })
     }
 
-    pub fn run(&mut self) -> R {
-        while self.frame().ip < self.frame().instructions.0.len() - 1
-            || self.frame().ip == usize::MAX
-        {
+    fn trace(&self) -> Vec<TraceFrame> {
+        self.frames
+            .iter()
+            .map(|frame| {
+                let offset = frame.function.instructions.instruction_start(frame.ip);
+
+                TraceFrame {
+                    function: frame
+                        .function
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| "<anonymous>".to_string()),
+                    offset,
+                    line: frame.function.line(offset),
+                }
+            })
+            .collect()
+    }
+
+    fn execute(&mut self) -> R {
+        // ip starts out at usize::MAX, before the first instruction
+        while self.frame().ip.wrapping_add(1) < self.frame().function.instructions.0.len() {
             self.frame_mut().ip = self.frame().ip.wrapping_add(1);
 
-            let instructions = &self.frame().instructions.0;
+            let instructions = &self.frame().function.instructions.0;
             let ip = self.frame().ip;
-            let op: Opcode = instructions[ip].into();
+            let op =
+                Opcode::try_from(instructions[ip]).map_err(RuntimeErrorKind::InvalidBytecode)?;
 
             match op {
                 Opcode::OpNoop => {}
@@ -187,19 +284,27 @@ impl Vm {
 
                     self.push(self.constants[const_index].from_ref())?;
                 }
-                Opcode::OpAdd | Opcode::OpMul | Opcode::OpDiv | Opcode::OpSub => {
+                Opcode::OpAdd | Opcode::OpMul | Opcode::OpDiv | Opcode::OpMod | Opcode::OpSub => {
                     self.exec_binary_op(op)?;
                 }
                 Opcode::OpPop => {
                     self.pop();
                 }
+                Opcode::OpDup => {
+                    self.push(self.stack_top().from_ref())?;
+                }
                 Opcode::OpTrue => {
                     self.push(Object::Boolean(true))?;
                 }
                 Opcode::OpFalse => {
                     self.push(Object::Boolean(false))?;
                 }
-                Opcode::OpEqual | Opcode::OpNotEqual | Opcode::OpGreaterThan => {
+                Opcode::OpEqual
+                | Opcode::OpNotEqual
+                | Opcode::OpGreaterThan
+                | Opcode::OpGreaterEqual
+                | Opcode::OpLessThan
+                | Opcode::OpLessEqual => {
                     self.exec_comparison(op)?;
                 }
                 Opcode::OpBang => {
@@ -214,12 +319,70 @@ impl Vm {
 
                     let condition = self.pop();
                     if !condition.is_truthy() {
-                        self.frame_mut().ip = pos - 1;
+                        self.frame_mut().ip = pos.wrapping_sub(1);
                     }
                 }
                 Opcode::OpJump => {
                     let pos = read_u16(&instructions[ip + 1..]);
-                    self.frame_mut().ip = pos - 1;
+                    self.frame_mut().ip = pos.wrapping_sub(1);
+                }
+                Opcode::OpRange => {
+                    let inclusive = read_u8(&instructions[ip + 1..]) == 1;
+                    self.frame_mut().ip += 1;
+
+                    self.exec_range(inclusive)?;
+                }
+                Opcode::OpIterInit => {
+                    let iterable = self.pop();
+                    self.push(Object::Iterator(ObjectIterator::new(iterable)?))?;
+                }
+                Opcode::OpIterNext => {
+                    let end = read_u16(&instructions[ip + 1..]);
+                    let count = read_u8(&instructions[ip + 3..]);
+                    self.frame_mut().ip += 3;
+
+                    self.exec_iter_next(end, count)?;
+                }
+                Opcode::OpMatchArray => {
+                    let len = read_u8(&instructions[ip + 1..]);
+                    let rest = read_u8(&instructions[ip + 2..]) == 1;
+                    self.frame_mut().ip += 2;
+
+                    let matches = self.stack_top().unpack_array(len, rest).is_ok();
+                    self.push(Object::Boolean(matches))?;
+                }
+                Opcode::OpMatchHash => {
+                    let matches = matches!(self.stack_top(), Object::Hash(_));
+                    self.push(Object::Boolean(matches))?;
+                }
+                Opcode::OpHasKey => {
+                    let key = self.pop();
+                    let hash = self.pop();
+
+                    let found = matches!(&hash, Object::Hash(hash) if hash.contains_key(&key));
+                    self.push(Object::Boolean(found))?;
+                }
+                Opcode::OpUnpackArray => {
+                    let len = read_u8(&instructions[ip + 1..]);
+                    let rest = read_u8(&instructions[ip + 2..]) == 1;
+                    self.frame_mut().ip += 2;
+
+                    let values = self.pop().unpack_array(len, rest)?;
+                    for value in values.into_iter().rev() {
+                        self.push(value)?;
+                    }
+                }
+                Opcode::OpUnpackHash => {
+                    let count = read_u8(&instructions[ip + 1..]);
+                    self.frame_mut().ip += 1;
+
+                    let keys = self.stack[self.sp - count..self.sp].to_vec();
+                    self.sp -= count;
+
+                    let values = self.pop().unpack_hash(&keys)?;
+                    for value in values.into_iter().rev() {
+                        self.push(value)?;
+                    }
                 }
                 Opcode::OpNull => {
                     self.push(Object::Null)?;
@@ -263,12 +426,25 @@ impl Vm {
 
                     self.exec_index(left, index)?;
                 }
+                Opcode::OpSlice => {
+                    let end = self.pop();
+                    let start = self.pop();
+                    let left = self.pop();
+
+                    self.push(left.slice(&start, &end)?)?;
+                }
                 Opcode::OpCall => {
                     let num_args = read_u8(&instructions[ip + 1..]);
                     self.frame_mut().ip += 1;
 
                     self.exec_call(num_args)?;
                 }
+                Opcode::OpTailCall => {
+                    let num_args = read_u8(&instructions[ip + 1..]);
+                    self.frame_mut().ip += 1;
+
+                    self.exec_tail_call(num_args)?;
+                }
                 Opcode::OpReturnValue => {
                     let value = self.pop();
 
@@ -288,14 +464,15 @@ impl Vm {
 
                     self.frame_mut().ip += 1;
 
-                    self.stack[self.frame().base_poiner + local_index] = self.pop();
+                    let value = self.pop();
+                    self.stack[self.frame().base_poiner + local_index].store(value);
                 }
                 Opcode::OpGetLocal => {
                     let local_index = read_u8(&instructions[ip + 1..]);
 
                     self.frame_mut().ip += 1;
 
-                    let o = self.stack[self.frame().base_poiner + local_index].from_ref();
+                    let o = self.stack[self.frame().base_poiner + local_index].load();
                     self.push(o)?;
                 }
                 Opcode::OpGetBuiltin => {
@@ -317,20 +494,70 @@ impl Vm {
                     let free_index = read_u8(&instructions[ip + 1..]);
                     self.frame_mut().ip += 1;
 
-                    let object = self.frame().free[free_index].from_ref();
+                    let object = self.frame().free[free_index].load();
                     self.push(object)?;
                 }
+                Opcode::OpSetFree => {
+                    let free_index = read_u8(&instructions[ip + 1..]);
+                    self.frame_mut().ip += 1;
+
+                    let object = self.pop();
+                    self.frame_mut().free[free_index].store(object);
+                }
+                Opcode::OpCaptureLocal => {
+                    let local_index = read_u8(&instructions[ip + 1..]);
+                    self.frame_mut().ip += 1;
+
+                    let cell = self.stack[self.frame().base_poiner + local_index].capture();
+                    self.push(cell)?;
+                }
+                Opcode::OpCaptureFree => {
+                    let free_index = read_u8(&instructions[ip + 1..]);
+                    self.frame_mut().ip += 1;
+
+                    let cell = self.frame_mut().free[free_index].capture();
+                    self.push(cell)?;
+                }
+                Opcode::OpSetIndexGlobal => {
+                    let index = read_u16(&instructions[ip + 1..]);
+                    let num_keys = read_u8(&instructions[ip + 3..]);
+                    let operator = Opcode::try_from(instructions[ip + 4])
+                        .map_err(RuntimeErrorKind::InvalidBytecode)?;
+                    self.frame_mut().ip += 4;
+
+                    let mut root = self.globals[index].take();
+                    let result = self.exec_set_index(&mut root, num_keys, operator);
+                    self.globals[index] = root;
+                    result?;
+                }
+                Opcode::OpSetIndexLocal => {
+                    let index = self.frame().base_poiner + read_u8(&instructions[ip + 1..]);
+                    let num_keys = read_u8(&instructions[ip + 2..]);
+                    let operator = Opcode::try_from(instructions[ip + 3])
+                        .map_err(RuntimeErrorKind::InvalidBytecode)?;
+                    self.frame_mut().ip += 3;
+
+                    let mut root = self.stack[index].take();
+                    let result = self.exec_set_index(&mut root, num_keys, operator);
+                    self.stack[index].store(root);
+                    result?;
+                }
+                Opcode::OpSetIndexFree => {
+                    let index = read_u8(&instructions[ip + 1..]);
+                    let num_keys = read_u8(&instructions[ip + 2..]);
+                    let operator = Opcode::try_from(instructions[ip + 3])
+                        .map_err(RuntimeErrorKind::InvalidBytecode)?;
+                    self.frame_mut().ip += 3;
+
+                    let mut root = self.frame_mut().free[index].take();
+                    let result = self.exec_set_index(&mut root, num_keys, operator);
+                    self.frame_mut().free[index].store(root);
+                    result?;
+                }
                 Opcode::OpCurrentClosure => {
                     let frame = self.frame();
 
-                    // Instructions, NumLocals, NumParemterers, frees
-
-                    let closure = Object::Closure(
-                        frame.instructions.clone(),
-                        frame.num_locals,
-                        frame.num_parameters,
-                        frame.free.clone(),
-                    );
+                    let closure = Object::Closure(frame.function.clone(), frame.free.clone());
 
                     self.push(closure)?;
                 }
@@ -340,9 +567,70 @@ impl Vm {
         Ok(())
     }
 
+    fn exec_range(&mut self, inclusive: bool) -> R {
+        let end = self.pop();
+        let start = self.pop();
+
+        let (Object::Integer(start), Object::Integer(end)) = (&start, &end) else {
+            return Err(format!(
+                "range bounds must be INTEGER, got {} and {}",
+                start.type_str(),
+                end.type_str()
+            )
+            .into());
+        };
+
+        self.push(Object::range(*start, *end, inclusive)?)
+    }
+
+    // advances the iterator on top of the stack, pushing its next values or
+    // jumping to `end` once it is exhausted
+    fn exec_iter_next(&mut self, end: usize, count: usize) -> R {
+        let Object::Iterator(iterator) = &mut self.stack[self.sp - 1] else {
+            return Err(format!(
+                "expected iterator, got {}",
+                self.stack[self.sp - 1].type_str()
+            )
+            .into());
+        };
+
+        match iterator.next_bindings(count) {
+            Some(values) => {
+                for value in values {
+                    self.push(value)?;
+                }
+            }
+            None => self.frame_mut().ip = end.wrapping_sub(1),
+        }
+
+        Ok(())
+    }
+
+    // `root` is left as it was when the update fails
+    fn exec_set_index(&mut self, root: &mut Object, num_keys: usize, operator: Opcode) -> R {
+        let mut value = self.pop();
+        let keys = (0..num_keys)
+            .map(|i| self.stack[self.sp - num_keys + i].from_ref())
+            .collect::<Vec<_>>();
+        self.sp -= num_keys;
+
+        if !operator.is(&Opcode::OpNoop) {
+            let current = root.get_path(&keys)?.from_ref();
+
+            self.push(current)?;
+            self.push(value)?;
+            self.exec_binary_op(operator)?;
+            value = self.pop();
+        }
+
+        root.set_path(&keys, value.from_ref())?;
+
+        self.push(value)
+    }
+
     fn push_closure(&mut self, cost_index: usize, num_free: usize) -> R {
-        let Object::CompiledFunction(ins, a, b) = &self.constants[cost_index] else {
-            return Err(format!("Not a function {}", self.constants[cost_index]));
+        let Object::CompiledFunction(function) = &self.constants[cost_index] else {
+            return Err(format!("Not a function {}", self.constants[cost_index]).into());
         };
 
         let free = (0..num_free)
@@ -350,7 +638,7 @@ impl Vm {
             .collect::<Vec<_>>();
         self.sp -= num_free;
 
-        let closure = Object::Closure(Instructions(ins.0.to_vec()), *a, *b, free);
+        let closure = Object::Closure(function.clone(), free);
         self.push(closure)?;
 
         Ok(())
@@ -359,14 +647,34 @@ impl Vm {
     fn exec_call(&mut self, num_args: usize) -> R {
         let item = self.stack[self.sp - 1 - num_args].from_ref();
         match item {
-            Object::Closure(instructions, num_locals, num_parameters, free) => {
-                self.call_closure(&instructions, num_locals, num_parameters, num_args, free)
-            }
+            Object::Closure(function, free) => self.call_closure(function, num_args, free),
             Object::Builtin(builtin) => self.exec_builtin(builtin, num_args),
-            _ => Err("calling non-function and non-built-in".into()),
+            other => Err(RuntimeErrorKind::NotCallable(other.type_str())),
         }
     }
 
+    // moves the callee and its arguments down over the frame making the call
+    // and drops that frame, so deep recursion runs in constant stack space.
+    // the arguments are checked first so a failing call still shows the caller
+    fn exec_tail_call(&mut self, num_args: usize) -> R {
+        let callee = self.sp - 1 - num_args;
+
+        // builtins push their result, which the OpReturnValue after the call
+        // then returns as usual
+        let Object::Closure(function, free) = self.stack[callee].from_ref() else {
+            return self.exec_call(num_args);
+        };
+        let (num_args, rest) = self.take_arguments(&function, num_args)?;
+
+        let base = self.pop_frame().base_poiner - 1;
+        for i in 0..=num_args {
+            self.stack[base + i] = mem::replace(&mut self.stack[callee + i], Object::Null);
+        }
+        self.sp = base + 1 + num_args;
+
+        self.enter_closure(function, num_args, rest, free)
+    }
+
     fn exec_builtin(&mut self, builtin: BuiltinFunction, num_args: usize) -> R {
         let args = self.stack[self.sp - num_args..self.sp].to_vec();
 
@@ -378,62 +686,71 @@ impl Vm {
 
     fn call_closure(
         &mut self,
-        instructions: &Instructions,
-        num_locals: usize,
-        num_parameters: usize,
+        function: Rc<CompiledFunction>,
         num_args: usize,
         free: Vec<Object>,
     ) -> R {
-        if num_args != num_parameters {
-            return Err(format!(
-                "wrong number of arguments: want={}, got={}",
-                num_parameters, num_args
-            ));
-        }
+        let (num_args, rest) = self.take_arguments(&function, num_args)?;
 
-        //let frame = Frame::new(instructions.clone(), self.sp - num_args, free);
-        let frame = Frame {
-            instructions: instructions.clone(),
-            base_poiner: self.sp - num_args,
-            ip: usize::MAX,
-            free,
-            num_locals,
-            num_parameters,
-        };
+        self.enter_closure(function, num_args, rest, free)
+    }
+
+    // checks the number of arguments on top of the stack and takes the ones
+    // past the parameters off it as the rest array
+    fn take_arguments(
+        &mut self,
+        function: &CompiledFunction,
+        num_args: usize,
+    ) -> Result<(usize, Object), RuntimeErrorKind> {
+        check_arity(function.min_arity(), function.max_arity(), num_args)?;
 
-        self.sp = frame.base_poiner + num_locals;
+        let extra = num_args.saturating_sub(function.num_parameters);
+        let rest = self.build_array(self.sp - extra, self.sp);
+        self.sp -= extra;
 
-        self.push_frame(frame);
-        Ok(())
+        Ok((num_args - extra, rest))
     }
 
-    fn exec_array_index(&mut self, left: Vec<Object>, i: i64) -> R {
-        if i < 0 || i > (left.len() as i64) - 1 {
-            return self.push(Object::Null);
+    // starts running `function` on the `num_args` arguments on top of the stack
+    fn enter_closure(
+        &mut self,
+        function: Rc<CompiledFunction>,
+        num_args: usize,
+        rest: Object,
+        free: Vec<Object>,
+    ) -> R {
+        let base_poiner = self.sp - num_args;
+        if base_poiner + function.num_locals >= STACK_SIZE {
+            return Err(RuntimeErrorKind::StackOverflow);
         }
 
-        self.push(left[i as usize].from_ref())
-    }
+        // locals must not pick up cells left behind by an earlier call
+        for slot in &mut self.stack[base_poiner + num_args..base_poiner + function.num_locals] {
+            *slot = Object::Null;
+        }
 
-    fn exec_hash_index(&mut self, left: HashMap<Object, Object>, i: Object) -> R {
-        if !i.hashable() {
-            return Err(format!("unusable as hash key: {}", i.type_str()));
+        // the rest parameter takes the slot after the others
+        if function.rest {
+            self.stack[base_poiner + function.num_parameters] = rest;
         }
 
-        let val = match left.get(&i) {
-            Some(i) => i.from_ref(),
-            None => Object::Null,
+        // calls leaving out parameters start at the code filling in their
+        // defaults
+        let frame = Frame {
+            ip: function.entry(num_args).wrapping_sub(1),
+            base_poiner,
+            free,
+            function,
         };
 
-        self.push(val)
+        self.sp = frame.base_poiner + frame.function.num_locals;
+
+        self.push_frame(frame);
+        Ok(())
     }
 
     fn exec_index(&mut self, left: Object, right: Object) -> R {
-        match (left, right) {
-            (Object::Array(left), Object::Integer(i)) => self.exec_array_index(left, i),
-            (Object::Hash(left), i) => self.exec_hash_index(left, i),
-            (left, _) => Err(format!("index operator not supported: {}", left.type_str())),
-        }
+        self.push(left.index(&right)?)
     }
 
     fn build_hash(&mut self, start: usize, end: usize) -> Result<Object, String> {
@@ -444,13 +761,13 @@ impl Vm {
             let value = self.stack[i + 1].from_ref();
 
             if !key.hashable() {
-                return Err(format!("unsable as hash key: {}", key.type_str()));
+                return Err(format!("unusable as hash key: {}", key.type_str()));
             }
 
             hm.insert(key, value);
         }
 
-        Ok(Object::Hash(hm))
+        Ok(hm.into())
     }
 
     fn build_array(&mut self, start: usize, end: usize) -> Object {
@@ -460,7 +777,7 @@ impl Vm {
             elements.push(self.stack[i].from_ref());
         }
 
-        Object::Array(elements)
+        elements.into()
     }
 
     fn pop(&mut self) -> Object {
@@ -474,8 +791,8 @@ impl Vm {
     }
 
     fn push(&mut self, object: Object) -> R {
-        if self.sp > STACK_SIZE {
-            return Err("stack overflow".into());
+        if self.sp >= STACK_SIZE {
+            return Err(RuntimeErrorKind::StackOverflow);
         }
 
         self.stack[self.sp] = object;
@@ -502,8 +819,8 @@ mod test {
             test::{test_null, test_object},
             Object,
         },
-        parser::Parser,
-        vm::Vm,
+        parser::{parse_error::ParseError, Parser},
+        vm::{runtime_error::RuntimeErrorKind, verifier, Vm},
     };
 
     #[rstest]
@@ -514,6 +831,9 @@ mod test {
     #[case("1 * 2", 2)]
     #[case("4 / 2", 2)]
     #[case("50 / 2 * 2 + 10 - 5", 55)]
+    #[case("7 % 3", 1)]
+    #[case("-7 % 3", -1)]
+    #[case("1 + 10 % 4 * 2", 5)]
     #[case("5 + 5 + 5 + 5 - 10", 10)]
     #[case("2 * 2 * 2 * 2 * 2", 32)]
     #[case("5 * 2 + 10", 20)]
@@ -528,6 +848,20 @@ mod test {
         test_object(&element, &expected)
     }
 
+    #[rstest]
+    #[case("1.5", 1.5)]
+    #[case("-2.5", -2.5)]
+    #[case("1.5 + 1", 2.5)]
+    #[case("10 / 4.0", 2.5)]
+    #[case("2 * 0.25 - 1", -0.5)]
+    #[case("1e3 / 8", 125.0)]
+    #[case("0.1 + 0.2", 0.30000000000000004)]
+    #[case("5.5 % 2", 1.5)]
+    fn test_float_arithmetic(#[case] input: &str, #[case] expected: f64) {
+        let element = test_vm(input);
+        test_object(&element, &expected)
+    }
+
     #[rstest]
     #[case("true", true)]
     #[case("false", false)]
@@ -555,6 +889,27 @@ mod test {
     #[case("!!false", false)]
     #[case("!!5", true)]
     #[case("!(if (false) { 5; })", true)]
+    #[case("1.5 > 1", true)]
+    #[case("2 < 1.5", false)]
+    #[case("1 <= 1", true)]
+    #[case("2 <= 1", false)]
+    #[case("1 >= 1", true)]
+    #[case("1 >= 2", false)]
+    #[case("1.5 >= 1", true)]
+    #[case("1 <= 0.5", false)]
+    #[case("true && true", true)]
+    #[case("true && false", false)]
+    #[case("false || true", true)]
+    #[case("false || false", false)]
+    #[case("1 && \"a\"", true)]
+    #[case("1 < 2 && 2 < 3 || false", true)]
+    #[case("false && 1 / 0", false)]
+    #[case("true || 1 / 0", true)]
+    #[case("let f = fn(n) { n > 0 && f(n - 1) }; f(3)", false)]
+    #[case("let f = fn(n) { n == 0 || f(n - 1) }; f(3)", true)]
+    #[case("1 == 1.0", true)]
+    #[case("1.0 != 1", false)]
+    #[case("0.5 == 0.5", true)]
     fn test_boolean_expression(#[case] input: &str, #[case] expected: bool) {
         let element = test_vm(input);
         test_object(&element, &expected)
@@ -777,7 +1132,7 @@ noReturnTwo();"
     #[rstest]
     #[case("[][0]")]
     #[case("[1, 2, 3][99]")]
-    #[case("[1][-1]")]
+    #[case("[1][-2]")]
     #[case("{1: 1}[0]")]
     #[case("{}[0]")]
     fn test_index_expression_null(#[case] input: &str) {
@@ -835,14 +1190,226 @@ outer();
     }
 
     #[rstest]
-    #[case("fn() { 1; }(1);", "wrong number of arguments: want=0, got=1")]
-    #[case("fn(a) { a; }();", "wrong number of arguments: want=1, got=0")]
-    #[case("fn(a, b) { a + b; }(1);", "wrong number of arguments: want=2, got=1")]
-    fn test_invalid_argument_count(#[case] input: &str, #[case] expected_error: &str) {
+    #[case("fn() { 1; }(1);", 0, Some(0), 1)]
+    #[case("fn(a) { a; }();", 1, Some(1), 0)]
+    #[case("fn(a, b) { a + b; }(1);", 2, Some(2), 1)]
+    #[case("fn(a, ...b) { a }();", 1, None, 0)]
+    fn test_invalid_argument_count(
+        #[case] input: &str,
+        #[case] min: usize,
+        #[case] max: Option<usize>,
+        #[case] got: usize,
+    ) {
+        let result = test_vm_result(input);
+        assert_eq!(
+            result,
+            Err(RuntimeErrorKind::WrongArgumentCount { min, max, got })
+        );
+    }
+
+    #[rstest]
+    #[case("let x = 1; x = 2; x", 2)]
+    #[case("let x = 1; x = x + 1", 2)]
+    #[case("let x = 5; x += 2; x -= 1; x *= 3; x /= 2; x %= 5; x", 4)]
+    #[case("let a = 1; let b = 2; a = b = 7; a + b", 14)]
+    #[case("let x = 1; let f = fn() { x = 10 }; f(); x", 10)]
+    #[case("let x = 1; let f = fn() { let x = 2; x = 3 }; f(); x", 1)]
+    #[case(
+        "let f = fn(n) { let i = 0; let s = 0; s += n; i = s * 2; i }; f(4)",
+        8
+    )]
+    #[case("let f = fn(n) { fn() { n += 1; n * 10 } }; f(1)()", 20)]
+    #[case("let a = [1, 2, 3]; a[1] = 5; a[1] + a[2]", 8)]
+    #[case("let a = [[1, 2], [3]]; a[0][1] += 40; a[0][1]", 42)]
+    #[case("let h = {\"a\": 1}; h[\"b\"] = 2; h[\"a\"] + h[\"b\"]", 3)]
+    #[case("let a = [1]; let b = a; b[0] = 2; a[0]", 1)]
+    #[case("let f = fn() { let a = [0, 0]; a[1] = 3; a[1] }; f()", 3)]
+    #[case("let a = [0]; a[0] = 9", 9)]
+    fn test_assignment(#[case] input: &str, #[case] expected: i64) {
+        let element = test_vm(input);
+        test_object(&element, &expected)
+    }
+
+    #[rstest]
+    #[case(
+        "let s = \"ab\"; s[0] = 1",
+        RuntimeErrorKind::NotIndexable { container: "STRING", key: "INTEGER", assign: true }
+    )]
+    #[case("let a = [1]; a[1] = 2", RuntimeErrorKind::IndexOutOfBounds(1))]
+    #[case("let h = {}; h[\"a\"][0] = 2", RuntimeErrorKind::KeyNotFound("a".into()))]
+    #[case("let h = {}; h[[]] = 2", RuntimeErrorKind::UnusableHashKey("ARRAY"))]
+    fn test_assignment_errors(#[case] input: &str, #[case] expected_error: RuntimeErrorKind) {
+        let result = test_vm_result(input);
+        assert_eq!(result, Err(expected_error));
+    }
+
+    #[rstest]
+    #[case("let i = 0; while (i < 10) { i += 1 }; i", 10)]
+    #[case(
+        "let i = 0; let s = 0; while (i < 10) { i += 1; if (i % 2 == 0) { continue } s += i }; s",
+        25
+    )]
+    #[case("let i = 0; while (true) { if (i == 7) { break; } i += 1 }; i", 7)]
+    #[case("let i = 0; let n = 0; while (i < 3) { i += 1; let j = 0; while (true) { j += 1; if (j > 4) { break } n += 1 } }; n", 12)]
+    #[case(
+        "let f = fn(n) { let i = 0; while (true) { if (i == n) { return i * 2 } i += 1 } }; f(4)",
+        8
+    )]
+    #[case("let f = fn() { let s = 0; let i = 0; while (i < 5) { i += 1; if (i == 2) { continue } s += i }; s }; f()", 13)]
+    #[case("let i = 0; while (i < 100000) { if (i > 1) { } i += 1 }; i", 100000)]
+    #[case("while (false) { 1 }; 5", 5)]
+    fn test_while(#[case] input: &str, #[case] expected: i64) {
+        let element = test_vm(input);
+        test_object(&element, &expected)
+    }
+
+    #[rstest]
+    #[case("let s = 0; for (x in [1, 2, 3]) { s += x }; s", 6)]
+    #[case("let s = 0; for (i, x in [5, 6, 7]) { s += i * x }; s", 20)]
+    #[case("let s = 0; for (k in {1: 10, 2: 20}) { s += k }; s", 3)]
+    #[case("let s = 0; for (k, v in {1: 10, 2: 20}) { s += k * v }; s", 50)]
+    #[case("let n = 0; for (c in \"héllo\") { n += 1 }; n", 5)]
+    #[case(
+        "let s = \"\"; for (k in {\"b\": 1, \"a\": 2}) { s = s + k }; len(s)",
+        2
+    )]
+    #[case(
+        "let s = 0; for (i in 0..10) { if (i % 2 == 0) { continue } s += i }; s",
+        25
+    )]
+    #[case(
+        "let s = 0; for (i in 2..100000) { if (i > 5) { break } s += i }; s",
+        14
+    )]
+    #[case("let n = 0; for (i in 0..3) { for (j in 0..4) { n += 1 } }; n", 12)]
+    #[case(
+        "let f = fn(xs) { for (x in xs) { if (x > 2) { return x } } }; f([1, 3, 5])",
+        3
+    )]
+    #[case(
+        "let f = fn(xs) { let s = 0; for (x in xs) { s += x }; s }; f(0..5)",
+        10
+    )]
+    #[case("let x = 10; let s = 0; for (x in [1, 2]) { s += x }; s + x", 5)]
+    fn test_for(#[case] input: &str, #[case] expected: i64) {
+        let element = test_vm(input);
+        test_object(&element, &expected)
+    }
+
+    #[rstest]
+    #[case("len(0..5)", 5)]
+    #[case("len(0..=5)", 6)]
+    #[case("len(5..0)", 0)]
+    #[case("len(-3..3)", 6)]
+    #[case("(2..10)[3]", 5)]
+    #[case("(1..=3)[2]", 3)]
+    #[case("let n = 4; (0..n * 2)[7]", 7)]
+    #[case("let s = 0; for (i in 1..=100) { s += i }; s", 5050)]
+    #[case("let s = 0; for (i, x in 10..13) { s += i * x }; s", 35)]
+    #[case("let n = 0; for (i in 3..3) { n += 1 }; n", 0)]
+    fn test_range(#[case] input: &str, #[case] expected: i64) {
+        let element = test_vm(input);
+        test_object(&element, &expected)
+    }
+
+    #[rstest]
+    #[case("1..4", Object::Range(1, 4))]
+    #[case("let a = 1; a..=a + 2", Object::Range(1, 4))]
+    #[case("(0..3)[3]", Object::Null)]
+    #[case("(0..3)[-1]", Object::Integer(2))]
+    fn test_range_object(#[case] input: &str, #[case] expected: Object) {
+        assert_eq!(test_vm(input).to_string(), expected.to_string());
+    }
+
+    #[rstest]
+    #[case("[1, 2, 3, 4][1:3]", "[2, 3]")]
+    #[case("[1, 2, 3, 4][:2]", "[1, 2]")]
+    #[case("[1, 2, 3, 4][2:]", "[3, 4]")]
+    #[case("[1, 2, 3, 4][:]", "[1, 2, 3, 4]")]
+    #[case("[1, 2, 3, 4][-3:-1]", "[2, 3]")]
+    #[case("[1, 2, 3, 4][-10:10]", "[1, 2, 3, 4]")]
+    #[case("[1, 2, 3, 4][3:1]", "[]")]
+    #[case("let a = [1, 2, 3]; let n = 1; a[n + 1:][0]", "3")]
+    #[case("\"héllo\"[1:4]", "éll")]
+    #[case("\"hello\"[-3:]", "llo")]
+    #[case("\"hello\"[5:]", "")]
+    #[case("(10..20)[2:5]", "12..15")]
+    #[case("(0..=9)[-2:]", "8..10")]
+    #[case("let a = [1, 2]; let b = a[:]; b[0] = 9; a[0]", "1")]
+    fn test_slice(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(test_vm(input).to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("let x = 5; if (x < 3) { 1 } else if (x < 6) { 2 } else { 3 }", "2")]
+    #[case("if (false) { 1 } else if (false) { 2 }", "null")]
+    #[case("match (2) { 1 => \"one\", 2 => \"two\", _ => \"many\" }", "two")]
+    #[case("match (2.0) { 2 => \"int\", _ => \"other\" }", "int")]
+    #[case("match (-1) { -1 => true, _ => false }", "true")]
+    #[case("match (\"a\") { 1 => 1 }", "null")]
+    #[case("match ([1, [2, 3]]) { [a, [b, c]] => a + b + c }", "6")]
+    #[case("match ([1, 2]) { [a] => a, [a, b, c] => c, [_, b] => b }", "2")]
+    #[case(
+        "match ({\"x\": 1, \"y\": 2}) { {\"z\": z} => z, {\"y\": y} => y }",
+        "2"
+    )]
+    #[case("match ({1: [true]}) { {1: [false]} => 0, {1: [true]} => 1 }", "1")]
+    #[case("match ([]) { {} => \"hash\", [] => \"array\" }", "array")]
+    #[case("match (5) { n => { let m = n * 2; m } }", "10")]
+    #[case("match (1) { _ => {} }", "null")]
+    #[case(
+        "let f = fn(x) { match (x) { [h, t] => h + f(t), _ => 0 } }; f([1, [2, [3, 0]]])",
+        "6"
+    )]
+    #[case("let s = 0; for (p in [[1, 2], 3, [4]]) { s += match (p) { [a, b] => a * b, [a] => a, _ => 0 } }; s", "6")]
+    // failed arms must not leave anything behind on the stack
+    #[case("let n = 0; for (i in 0..1000) { n += match ({\"a\": [i]}) { {\"a\": [0, 0]} => 0, {\"a\": [1]} => 1, _ => 2 } }; n", "1999")]
+    fn test_match(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(test_vm(input).to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("for (x in 1) { x }", "not iterable: INTEGER")]
+    #[case(
+        "let f = fn() { for (x in fn() {}) { x } }; f()",
+        "not iterable: CLOSURE"
+    )]
+    #[case("1.5..2", "range bounds must be INTEGER, got FLOAT and INTEGER")]
+    #[case("1[0:1]", "slice operator not supported: INTEGER")]
+    #[case("{}[0:1]", "slice operator not supported: HASH")]
+    #[case("[1][\"a\":]", "slice bounds must be INTEGER, got STRING")]
+    #[case("[1][:1.5]", "slice bounds must be INTEGER, got FLOAT")]
+    fn test_for_errors(#[case] input: &str, #[case] expected_error: &str) {
         let result = test_vm_result(input);
         assert_eq!(result, Err(expected_error.into()));
     }
 
+    #[rstest]
+    #[case("1 / 0", RuntimeErrorKind::DivisionByZero)]
+    #[case("1 % 0", RuntimeErrorKind::ModuloByZero)]
+    #[case("true && 1 / 0", RuntimeErrorKind::DivisionByZero)]
+    #[case("let f = fn(x) { 10 / x }; f(0)", RuntimeErrorKind::DivisionByZero)]
+    #[case("9223372036854775807 + 1", overflow("9223372036854775807 + 1"))]
+    #[case("-9223372036854775807 - 2", overflow("-9223372036854775807 - 2"))]
+    #[case("4611686018427387904 * 2", overflow("4611686018427387904 * 2"))]
+    #[case(
+        "let min = -9223372036854775807 - 1; min / -1",
+        overflow("-9223372036854775808 / -1")
+    )]
+    #[case(
+        "let min = -9223372036854775807 - 1; -min",
+        overflow("-(-9223372036854775808)")
+    )]
+    #[case("0..=9223372036854775807", overflow("0..=9223372036854775807"))]
+    fn test_arithmetic_errors(#[case] input: &str, #[case] expected_error: RuntimeErrorKind) {
+        let result = test_vm_result(input);
+        assert_eq!(result, Err(expected_error));
+    }
+
+    fn overflow(operation: &str) -> RuntimeErrorKind {
+        RuntimeErrorKind::IntegerOverflow(operation.into())
+    }
+
     #[rstest]
     #[case("len(\"\")", 0)]
     #[case("len(\"four\")", 4)]
@@ -929,6 +1496,30 @@ closure();
 ",
         99
     )]
+    #[case(
+        "
+let newCounter = fn() {
+    let count = 0;
+    fn() { count += 1; count };
+};
+let counter = newCounter();
+counter();
+counter();
+",
+        2
+    )]
+    #[case(
+        "
+let f = fn() {
+    let x = 1;
+    let get = fn() { x };
+    x = 7;
+    get();
+};
+f();
+",
+        7
+    )]
     fn test_closure(#[case] input: &str, #[case] expected: i64) {
         let element = test_vm(input);
         test_object(&element, &expected);
@@ -997,8 +1588,60 @@ wrapper();
     }
 
     #[rstest]
-    #[case("rest([1, 2, 3])", Object::Array(vec![Object::Integer(2), Object::Integer(3)]))]
-    #[case("push([], 1)", Object::Array(vec![Object::Integer(1)]))]
+    #[case(
+        "
+let countDown = fn(x) { if (x == 0) { 0 } else { countDown(x - 1) } };
+countDown(100000);
+",
+        0
+    )]
+    #[case(
+        "
+let wrapper = fn() {
+    let sum = fn(x, acc) { if (x == 0) { return acc; } sum(x - 1, acc + x) };
+    sum(10000, 0);
+};
+wrapper();
+",
+        50005000
+    )]
+    #[case(
+        "
+let odd = 0;
+let even = fn(x) { if (x == 0) { true } else { odd(x - 1) } };
+odd = fn(x) { if (x == 0) { false } else { even(x - 1) } };
+if (even(10001)) { 1 } else { 0 };
+",
+        0
+    )]
+    #[case(
+        "
+let reduce = fn(arr, initial, f) {
+  let iter = fn(arr, result) {
+    if (len(arr) == 0) {
+      result
+    } else {
+      iter(rest(arr), f(result, first(arr)));
+    }
+  };
+
+  iter(arr, initial);
+};
+let xs = [];
+for (i in 1..=3000) { xs = push(xs, i) };
+reduce(xs, 0, fn(acc, x) { acc + x });
+",
+        4501500
+    )]
+    #[case("let f = fn(x) { len(x) }; f([1, 2]) + 1", 3)]
+    fn test_tail_calls(#[case] input: &str, #[case] expected: i64) {
+        let element = test_vm(input);
+        test_object(&element, &expected);
+    }
+
+    #[rstest]
+    #[case("rest([1, 2, 3])", Object::from(vec![Object::Integer(2), Object::Integer(3)]))]
+    #[case("push([], 1)", Object::from(vec![Object::Integer(1)]))]
     #[case(
         "len(1)",
         Object::Error("arguments to `len` not supported, got INTEGER".into())
@@ -1029,15 +1672,18 @@ wrapper();
         let mut parser = Parser::new(input.into());
         let (program, errors) = parser.parse_program();
 
-        assert_eq!(errors, Vec::<String>::new());
+        assert_eq!(errors, Vec::<ParseError>::new());
 
         let mut compiler = Compiler::new();
         compiler
             .compile((&program).into())
             .expect("Failed to compile program");
 
+        let bytecode = compiler.bytecode();
+        verifier::verify(&bytecode).expect("compiler made invalid bytecode");
+
         let mut vm = Vm::new();
-        vm.with_bytecode(compiler.bytecode());
+        vm.with_bytecode(bytecode);
         vm.run().expect("vm failed to run");
 
         let element = vm.last_popped();
@@ -1047,20 +1693,23 @@ wrapper();
         element.from_ref()
     }
 
-    fn test_vm_result(input: &str) -> Result<Object, String> {
+    fn test_vm_result(input: &str) -> Result<Object, RuntimeErrorKind> {
         let mut parser = Parser::new(input.into());
         let (program, errors) = parser.parse_program();
 
-        assert_eq!(errors, Vec::<String>::new());
+        assert_eq!(errors, Vec::<ParseError>::new());
 
         let mut compiler = Compiler::new();
         compiler
             .compile((&program).into())
             .expect("Failed to compile program");
 
+        let bytecode = compiler.bytecode();
+        verifier::verify(&bytecode).expect("compiler made invalid bytecode");
+
         let mut vm = Vm::new();
-        vm.with_bytecode(compiler.bytecode());
-        vm.run()?;
+        vm.with_bytecode(bytecode);
+        vm.run().map_err(|e| e.kind)?;
 
         let element = vm.last_popped();
 
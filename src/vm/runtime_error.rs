@@ -0,0 +1,260 @@
+use std::fmt::Display;
+
+// failures of the operations both engines share report the message the
+// evaluator uses for them as well
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeErrorKind {
+    StackOverflow,
+    NotCallable(&'static str),
+    // rejected by the verifier before running
+    InvalidBytecode(String),
+    DivisionByZero,
+    ModuloByZero,
+    // the operation that overflowed, written the way the source would
+    IntegerOverflow(String),
+    // the types of the operands of a binary operator they do not support
+    TypeMismatch(&'static str, &'static str),
+    // a function taking `min` to `max` arguments, or any number from `min`
+    // up, called with `got`
+    WrongArgumentCount {
+        min: usize,
+        max: Option<usize>,
+        got: usize,
+    },
+    IndexOutOfBounds(i64),
+    // reading or assigning `container[key]` for types that have no such
+    // elements
+    NotIndexable {
+        container: &'static str,
+        key: &'static str,
+        assign: bool,
+    },
+    UnusableHashKey(&'static str),
+    KeyNotFound(String),
+    Operation(String),
+}
+
+impl From<String> for RuntimeErrorKind {
+    fn from(value: String) -> Self {
+        RuntimeErrorKind::Operation(value)
+    }
+}
+
+impl From<&str> for RuntimeErrorKind {
+    fn from(value: &str) -> Self {
+        RuntimeErrorKind::Operation(value.to_string())
+    }
+}
+
+impl Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeErrorKind::StackOverflow => write!(f, "stack overflow"),
+            RuntimeErrorKind::NotCallable(type_str) => write!(f, "not a function: {}", type_str),
+            RuntimeErrorKind::InvalidBytecode(message) => write!(f, "{}", message),
+            RuntimeErrorKind::DivisionByZero => write!(f, "division by zero"),
+            RuntimeErrorKind::ModuloByZero => write!(f, "modulo by zero"),
+            RuntimeErrorKind::IntegerOverflow(operation) => {
+                write!(f, "integer overflow: {}", operation)
+            }
+            RuntimeErrorKind::TypeMismatch(left, right) => {
+                write!(f, "unsupported types for binary op {} {}", left, right)
+            }
+            RuntimeErrorKind::WrongArgumentCount { min, max, got } => {
+                let want = match max {
+                    Some(max) if max == min => max.to_string(),
+                    Some(max) => format!("{} to {}", min, max),
+                    None => format!("at least {}", min),
+                };
+
+                write!(f, "wrong number of arguments: want={}, got={}", want, got)
+            }
+            RuntimeErrorKind::IndexOutOfBounds(index) => {
+                write!(f, "index out of bounds: {}", index)
+            }
+            RuntimeErrorKind::NotIndexable {
+                container,
+                key,
+                assign,
+            } => write!(
+                f,
+                "index {} not supported: {}[{}]",
+                if *assign { "assignment" } else { "operator" },
+                container,
+                key
+            ),
+            RuntimeErrorKind::UnusableHashKey(key) => write!(f, "unusable as hash key: {}", key),
+            RuntimeErrorKind::KeyNotFound(key) => write!(f, "key not found: {}", key),
+            RuntimeErrorKind::Operation(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+// a function that was running when the error happened and the offset and
+// source line of the instruction it was executing
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceFrame {
+    pub function: String,
+    pub offset: usize,
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    // outermost call first
+    pub frames: Vec<TraceFrame>,
+}
+
+impl RuntimeError {
+    pub fn render(&self) -> String {
+        if self.frames.is_empty() {
+            return format!("error: {}", self.kind);
+        }
+
+        let mut lines = vec!["traceback (most recent call last):".to_string()];
+
+        // deep recursion shows up once with a count instead of every frame
+        let mut frames = self.frames.iter().peekable();
+        while let Some(frame) = frames.next() {
+            let mut repeated = 0;
+            while frames.next_if_eq(&frame).is_some() {
+                repeated += 1;
+            }
+
+            lines.push(match frame.line {
+                Some(line) => format!(
+                    "  in {} at {:04}, line {}",
+                    frame.function, frame.offset, line
+                ),
+                None => format!("  in {} at {:04}", frame.function, frame.offset),
+            });
+            if repeated > 0 {
+                lines.push(format!("  ... repeated {} more times", repeated));
+            }
+        }
+
+        lines.push(format!("error: {}", self.kind));
+        lines.join("\n")
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::{compile, parse, run};
+
+    use super::{RuntimeError, RuntimeErrorKind, TraceFrame};
+
+    fn run_error(input: &str) -> RuntimeError {
+        let program = parse(input).expect("failed to parse");
+        let bytecode = compile(&program).expect("failed to compile");
+
+        run(bytecode).expect_err("expected a runtime error")
+    }
+
+    #[rstest]
+    #[case("1 + true", RuntimeErrorKind::TypeMismatch("INTEGER", "BOOLEAN"))]
+    #[case("1 < true", RuntimeErrorKind::TypeMismatch("INTEGER", "BOOLEAN"))]
+    #[case("\"a\" - \"b\"", RuntimeErrorKind::TypeMismatch("STRING", "STRING"))]
+    #[case("-true", RuntimeErrorKind::Operation("unsupported type for negation: BOOLEAN".into()))]
+    #[case("1 % 0", RuntimeErrorKind::ModuloByZero)]
+    #[case(
+        "fn(a, b = 1) { a }()",
+        RuntimeErrorKind::WrongArgumentCount { min: 1, max: Some(2), got: 0 }
+    )]
+    #[case("let a = [1]; a[2] = 0", RuntimeErrorKind::IndexOutOfBounds(2))]
+    #[case(
+        "1[0]",
+        RuntimeErrorKind::NotIndexable { container: "INTEGER", key: "INTEGER", assign: false }
+    )]
+    #[case("{}[[]]", RuntimeErrorKind::UnusableHashKey("ARRAY"))]
+    #[case("let x = 1; x()", RuntimeErrorKind::NotCallable("INTEGER"))]
+    #[case(
+        "let f = fn(n) { 1 + f(n + 1) }; f(0)",
+        RuntimeErrorKind::StackOverflow
+    )]
+    fn test_error_kind(#[case] input: &str, #[case] expected: RuntimeErrorKind) {
+        assert_eq!(run_error(input).kind, expected);
+    }
+
+    #[test]
+    fn test_error_frames() {
+        let error = run_error(
+            "
+let inner = fn(x) { x / 0 };
+let outer = fn() { 1 + inner(2) };
+outer();
+",
+        );
+
+        assert_eq!(
+            error.frames,
+            vec![
+                TraceFrame {
+                    function: "<main>".into(),
+                    offset: 17,
+                    line: Some(4),
+                },
+                TraceFrame {
+                    function: "outer".into(),
+                    offset: 9,
+                    line: Some(3),
+                },
+                TraceFrame {
+                    function: "inner".into(),
+                    offset: 5,
+                    line: Some(2),
+                },
+            ]
+        );
+        assert_eq!(
+            error.render(),
+            "traceback (most recent call last):
+  in <main> at 0017, line 4
+  in outer at 0009, line 3
+  in inner at 0005, line 2
+error: division by zero"
+        );
+    }
+
+    #[test]
+    fn test_tail_call_error_keeps_caller() {
+        let error = run_error(
+            "
+let inner = fn(a, b = 1) { a + b };
+let outer = fn() { inner(1, 2, 3) };
+outer();
+",
+        );
+
+        assert_eq!(
+            error.render(),
+            "traceback (most recent call last):
+  in <main> at 0017, line 4
+  in outer at 0012, line 3
+error: wrong number of arguments: want=1 to 2, got=3"
+        );
+    }
+
+    #[test]
+    fn test_render_repeated_frames() {
+        let error = run_error("let f = fn(n) { 1 + f(n + 1) }; f(0)");
+        let rendered = error.render();
+
+        assert!(rendered.starts_with(
+            "traceback (most recent call last):
+  in <main> at 0013, line 1
+  in f at 0010, line 1
+  ... repeated "
+        ));
+        assert!(rendered.ends_with("more times\n  in f at 0006, line 1\nerror: stack overflow"));
+    }
+}
@@ -0,0 +1,504 @@
+use std::collections::HashMap;
+
+use crate::{
+    builtin::BUILTINS,
+    code::{read_operands::read_operands, Opcode},
+    compiler::Bytecode,
+    object::Object,
+};
+
+use super::GLOBALS_SIZE;
+
+// a decoded instruction and its offset
+struct Instruction {
+    offset: usize,
+    op: Opcode,
+    operands: Vec<usize>,
+}
+
+// the main program or a function from the constant pool
+struct Code<'a> {
+    name: String,
+    instructions: &'a [u8],
+    num_locals: usize,
+    num_free: usize,
+    entries: Vec<usize>,
+    function: bool,
+}
+
+// checks bytecode the vm would otherwise trust blindly, so running it can
+// fail with a runtime error but never read out of bounds or wander off into
+// the middle of an instruction
+pub fn verify(bytecode: &Bytecode) -> Result<(), String> {
+    let mut codes = vec![Code {
+        name: "<main>".to_string(),
+        instructions: &bytecode.instructions.0,
+        num_locals: 0,
+        num_free: 0,
+        entries: vec![0],
+        function: false,
+    }];
+
+    for (index, constant) in bytecode.constants.iter().enumerate() {
+        if let Object::CompiledFunction(function) = constant {
+            let name = match &function.name {
+                Some(name) => format!("{} (constant {})", name, index),
+                None => format!("constant {}", index),
+            };
+
+            // calls store the arguments in the first locals and pick an entry
+            // by the number of missing defaults
+            let parameters = function.num_parameters + function.rest as usize;
+            if function.num_locals < parameters {
+                return Err(format!(
+                    "invalid bytecode in {}: {} locals cannot hold {} parameters",
+                    name, function.num_locals, parameters
+                ));
+            }
+            if function.entries.is_empty() || function.entries.len() > function.num_parameters + 1 {
+                return Err(format!(
+                    "invalid bytecode in {}: {} entries for {} parameters",
+                    name,
+                    function.entries.len(),
+                    function.num_parameters
+                ));
+            }
+
+            codes.push(Code {
+                name,
+                instructions: &function.instructions.0,
+                num_locals: function.num_locals,
+                num_free: 0,
+                entries: function.entries.clone(),
+                function: true,
+            });
+        }
+    }
+
+    let decoded = codes
+        .iter()
+        .map(|code| decode(code).map_err(|e| format!("invalid bytecode in {} at {}", code.name, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // a function only learns how many values it closes over from the
+    // OpClosure instructions creating it
+    let mut frees = HashMap::new();
+    for (code, instructions) in codes.iter().zip(&decoded) {
+        for instruction in instructions {
+            if !instruction.op.is(&Opcode::OpClosure) {
+                continue;
+            }
+
+            let (index, count) = (instruction.operands[0], instruction.operands[1]);
+            if let Some(previous) = frees.insert(index, count).filter(|p| *p != count) {
+                return Err(format!(
+                    "invalid bytecode in {} at {:04}: closure over constant {} with {} free values, elsewhere {}",
+                    code.name, instruction.offset, index, count, previous
+                ));
+            }
+        }
+    }
+
+    let mut function_index = 0;
+    for (index, constant) in bytecode.constants.iter().enumerate() {
+        if matches!(constant, Object::CompiledFunction(_)) {
+            function_index += 1;
+            codes[function_index].num_free = frees.get(&index).copied().unwrap_or(0);
+        }
+    }
+
+    for (code, instructions) in codes.iter().zip(&decoded) {
+        check_operands(code, instructions, &bytecode.constants)
+            .and_then(|_| check_stack(code, instructions))
+            .map_err(|e| format!("invalid bytecode in {} at {}", code.name, e))?;
+    }
+
+    Ok(())
+}
+
+fn decode(code: &Code) -> Result<Vec<Instruction>, String> {
+    let bytes = code.instructions;
+    let mut instructions = vec![];
+
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let op = Opcode::try_from(bytes[offset]).map_err(|e| format!("{:04}: {}", offset, e))?;
+        let def = op.definition();
+
+        let width = def.operand_widths.iter().sum::<usize>();
+        if offset + width >= bytes.len() {
+            return Err(format!(
+                "{:04}: {} runs past the end of the instructions",
+                offset, def.name
+            ));
+        }
+
+        let (operands, read) = read_operands(&def, &bytes[offset + 1..]);
+        instructions.push(Instruction {
+            offset,
+            op,
+            operands,
+        });
+
+        offset += 1 + read;
+    }
+
+    Ok(instructions)
+}
+
+fn check_operands(
+    code: &Code,
+    instructions: &[Instruction],
+    constants: &[Object],
+) -> Result<(), String> {
+    let len = code.instructions.len();
+    let starts = instructions
+        .iter()
+        .map(|instruction| instruction.offset)
+        .collect::<Vec<_>>();
+    // jumping to the very end finishes the main program
+    let is_target = |target: usize| starts.binary_search(&target).is_ok() || target == len;
+
+    for entry in &code.entries {
+        if !is_target(*entry) {
+            return Err(format!("{:04}: entry is not an instruction", entry));
+        }
+    }
+
+    for Instruction {
+        offset,
+        op,
+        operands,
+    } in instructions
+    {
+        let at = |problem: String| Err(format!("{:04}: {}", offset, problem));
+
+        match op {
+            Opcode::OpJump | Opcode::OpJumpNotTruthy | Opcode::OpIterNext
+                if !is_target(operands[0]) =>
+            {
+                return at(format!("jump target {} is not an instruction", operands[0]));
+            }
+            // an iterator step binds a value or a key and a value
+            Opcode::OpIterNext if !(1..=2).contains(&operands[1]) => {
+                return at(format!(
+                    "iterator step binding {} values, expected 1 or 2",
+                    operands[1]
+                ));
+            }
+            Opcode::OpConstant if operands[0] >= constants.len() => {
+                return at(format!("constant {} does not exist", operands[0]));
+            }
+            Opcode::OpClosure
+                if !matches!(
+                    constants.get(operands[0]),
+                    Some(Object::CompiledFunction(_))
+                ) =>
+            {
+                return at(format!("constant {} is not a function", operands[0]));
+            }
+            Opcode::OpGetGlobal | Opcode::OpSetGlobal | Opcode::OpSetIndexGlobal
+                if operands[0] >= GLOBALS_SIZE =>
+            {
+                return at(format!("global {} out of range", operands[0]));
+            }
+            Opcode::OpGetBuiltin if operands[0] >= BUILTINS.len() => {
+                return at(format!("builtin {} does not exist", operands[0]));
+            }
+            Opcode::OpGetLocal
+            | Opcode::OpSetLocal
+            | Opcode::OpSetIndexLocal
+            | Opcode::OpCaptureLocal
+                if operands[0] >= code.num_locals =>
+            {
+                return at(format!(
+                    "local {} out of range, the function has {}",
+                    operands[0], code.num_locals
+                ));
+            }
+            Opcode::OpGetFree
+            | Opcode::OpSetFree
+            | Opcode::OpSetIndexFree
+            | Opcode::OpCaptureFree
+                if operands[0] >= code.num_free =>
+            {
+                return at(format!(
+                    "free value {} out of range, the function has {}",
+                    operands[0], code.num_free
+                ));
+            }
+            Opcode::OpSetIndexGlobal | Opcode::OpSetIndexLocal | Opcode::OpSetIndexFree => {
+                if let Err(e) = Opcode::try_from(operands[2] as u8) {
+                    return at(e);
+                }
+            }
+            Opcode::OpReturnValue
+            | Opcode::OpReturn
+            | Opcode::OpTailCall
+            | Opcode::OpCurrentClosure
+                if !code.function =>
+            {
+                return at(format!("{:?} outside of a function", op));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// every path to an instruction has to arrive with the same number of values
+// on the stack, and none may take more values than there are
+fn check_stack(code: &Code, instructions: &[Instruction]) -> Result<(), String> {
+    let index_of = |offset: usize| instructions.binary_search_by_key(&offset, |i| i.offset);
+
+    let mut depths: Vec<Option<usize>> = vec![None; instructions.len()];
+    let mut pending = code
+        .entries
+        .iter()
+        .map(|entry| (*entry, 0))
+        .collect::<Vec<_>>();
+
+    while let Some((offset, depth)) = pending.pop() {
+        let Ok(index) = index_of(offset) else {
+            // only the main program may run off the end
+            if code.function {
+                return Err(format!("{:04}: function ends without returning", offset));
+            }
+            continue;
+        };
+
+        match depths[index] {
+            Some(known) if known == depth => continue,
+            Some(known) => {
+                return Err(format!(
+                    "{:04}: reached with {} and {} values on the stack",
+                    offset, known, depth
+                ))
+            }
+            None => depths[index] = Some(depth),
+        }
+
+        let Instruction { op, operands, .. } = &instructions[index];
+        let (pops, pushes) = op.stack_effect(operands);
+        let Some(after) = depth.checked_sub(pops).map(|d| d + pushes) else {
+            return Err(format!(
+                "{:04}: {:?} takes {} values from a stack holding {}",
+                offset, op, pops, depth
+            ));
+        };
+
+        let next = offset + 1 + op.definition().operand_widths.iter().sum::<usize>();
+        match op {
+            Opcode::OpReturn | Opcode::OpReturnValue => {}
+            Opcode::OpJump => pending.push((operands[0], after)),
+            Opcode::OpJumpNotTruthy => {
+                pending.push((operands[0], after));
+                pending.push((next, after));
+            }
+            // an exhausted iterator jumps without pushing anything
+            Opcode::OpIterNext => {
+                pending.push((operands[0], depth));
+                pending.push((next, after));
+            }
+            _ => pending.push((next, after)),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use rstest::rstest;
+
+    use crate::{
+        code::{make::make, Instructions, Opcode},
+        compile,
+        compiler::Bytecode,
+        object::{CompiledFunction, Object},
+        parse, run,
+        vm::runtime_error::RuntimeErrorKind,
+    };
+
+    use super::verify;
+
+    fn instructions(instructions: Vec<Vec<u8>>) -> Instructions {
+        Instructions(instructions.concat())
+    }
+
+    fn function(body: Vec<Vec<u8>>, num_locals: usize) -> Object {
+        Object::CompiledFunction(Rc::new(CompiledFunction::new(
+            instructions(body),
+            num_locals,
+            0,
+        )))
+    }
+
+    fn bytecode(main: Vec<Vec<u8>>, constants: Vec<Object>) -> Bytecode {
+        Bytecode {
+            instructions: instructions(main),
+            constants,
+            lines: vec![],
+            globals: vec![],
+        }
+    }
+
+    #[rstest]
+    #[case("1 + 2 * 3")]
+    #[case("let a = [1, 2, 3]; a[1] += 2; a[0:2]")]
+    #[case("if (true) { 1 } else { 2 }; for (x in [1, 2]) { if (x > 1) { break } }")]
+    #[case("let f = fn(a, b = 1, ...rest) { let c = a + b; fn() { c += 1 } }; f(1)()")]
+    #[case("let fib = fn(n) { if (n < 2) { return n; } fib(n - 1) + fib(n - 2) }; fib(10)")]
+    fn test_compiled_programs_verify(#[case] input: &str) {
+        let program = parse(input).expect("failed to parse");
+        let bytecode = compile(&program).expect("failed to compile");
+
+        assert_eq!(verify(&bytecode), Ok(()));
+    }
+
+    #[rstest]
+    #[case(
+        bytecode(vec![vec![255]], vec![]),
+        "invalid bytecode in <main> at 0000: unknown opcode 255"
+    )]
+    #[case(
+        bytecode(vec![make(Opcode::OpConstant, &[0])[..2].to_vec()], vec![Object::Integer(1)]),
+        "invalid bytecode in <main> at 0000: OpConstant runs past the end of the instructions"
+    )]
+    #[case(
+        bytecode(
+            vec![make(Opcode::OpConstant, &[0]), make(Opcode::OpJump, &[1])],
+            vec![Object::Integer(1)]
+        ),
+        "invalid bytecode in <main> at 0003: jump target 1 is not an instruction"
+    )]
+    #[case(
+        bytecode(vec![make(Opcode::OpConstant, &[3]), make(Opcode::OpPop, &[])], vec![]),
+        "invalid bytecode in <main> at 0000: constant 3 does not exist"
+    )]
+    #[case(
+        bytecode(
+            vec![make(Opcode::OpClosure, &[0, 0]), make(Opcode::OpPop, &[])],
+            vec![Object::Integer(1)]
+        ),
+        "invalid bytecode in <main> at 0000: constant 0 is not a function"
+    )]
+    #[case(
+        bytecode(vec![make(Opcode::OpGetBuiltin, &[200]), make(Opcode::OpPop, &[])], vec![]),
+        "invalid bytecode in <main> at 0000: builtin 200 does not exist"
+    )]
+    #[case(
+        bytecode(
+            vec![make(Opcode::OpClosure, &[0, 0]), make(Opcode::OpPop, &[])],
+            vec![function(
+                vec![make(Opcode::OpGetLocal, &[2]), make(Opcode::OpReturnValue, &[])],
+                1
+            )]
+        ),
+        "invalid bytecode in constant 0 at 0000: local 2 out of range, the function has 1"
+    )]
+    #[case(
+        bytecode(
+            vec![make(Opcode::OpClosure, &[0, 0]), make(Opcode::OpPop, &[])],
+            vec![function(
+                vec![make(Opcode::OpGetFree, &[0]), make(Opcode::OpReturnValue, &[])],
+                0
+            )]
+        ),
+        "invalid bytecode in constant 0 at 0000: free value 0 out of range, the function has 0"
+    )]
+    #[case(
+        bytecode(vec![make(Opcode::OpReturn, &[])], vec![]),
+        "invalid bytecode in <main> at 0000: OpReturn outside of a function"
+    )]
+    #[case(
+        bytecode(vec![make(Opcode::OpAdd, &[])], vec![]),
+        "invalid bytecode in <main> at 0000: OpAdd takes 2 values from a stack holding 0"
+    )]
+    #[case(
+        bytecode(
+            vec![
+                make(Opcode::OpTrue, &[]),
+                make(Opcode::OpJumpNotTruthy, &[7]),
+                make(Opcode::OpConstant, &[0]),
+                make(Opcode::OpPop, &[]),
+            ],
+            vec![Object::Integer(1)]
+        ),
+        "invalid bytecode in <main> at 0007: reached with 1 and 0 values on the stack"
+    )]
+    #[case(
+        bytecode(
+            vec![make(Opcode::OpClosure, &[1, 0]), make(Opcode::OpPop, &[])],
+            vec![
+                Object::Integer(1),
+                function(vec![make(Opcode::OpConstant, &[0])], 0)
+            ]
+        ),
+        "invalid bytecode in constant 1 at 0003: function ends without returning"
+    )]
+    #[case(
+        bytecode(vec![make(Opcode::OpTailCall, &[0])], vec![]),
+        "invalid bytecode in <main> at 0000: OpTailCall outside of a function"
+    )]
+    #[case(
+        bytecode(vec![make(Opcode::OpIterNext, &[4, 3])], vec![]),
+        "invalid bytecode in <main> at 0000: iterator step binding 3 values, expected 1 or 2"
+    )]
+    #[case(
+        bytecode(
+            vec![],
+            vec![Object::CompiledFunction(Rc::new(CompiledFunction::new(
+                instructions(vec![make(Opcode::OpReturn, &[])]),
+                1,
+                2
+            )))]
+        ),
+        "invalid bytecode in constant 0: 1 locals cannot hold 2 parameters"
+    )]
+    #[case(
+        bytecode(
+            vec![],
+            vec![Object::CompiledFunction(Rc::new(CompiledFunction {
+                entries: vec![0, 0, 0],
+                ..CompiledFunction::new(instructions(vec![make(Opcode::OpReturn, &[])]), 1, 1)
+            }))]
+        ),
+        "invalid bytecode in constant 0: 3 entries for 1 parameters"
+    )]
+    #[case(
+        bytecode(
+            vec![],
+            vec![Object::CompiledFunction(Rc::new(CompiledFunction {
+                entries: vec![],
+                ..CompiledFunction::new(instructions(vec![make(Opcode::OpReturn, &[])]), 0, 0)
+            }))]
+        ),
+        "invalid bytecode in constant 0: 0 entries for 0 parameters"
+    )]
+    fn test_invalid_bytecode(#[case] bytecode: Bytecode, #[case] expected: &str) {
+        assert_eq!(verify(&bytecode), Err(expected.to_string()));
+    }
+
+    #[test]
+    fn test_run_rejects_invalid_bytecode() {
+        let error = run(bytecode(vec![make(Opcode::OpAdd, &[])], vec![]))
+            .expect_err("expected invalid bytecode to be rejected");
+
+        assert_eq!(
+            error.kind,
+            RuntimeErrorKind::InvalidBytecode(
+                "invalid bytecode in <main> at 0000: OpAdd takes 2 values from a stack holding 0"
+                    .into()
+            )
+        );
+        assert!(error.frames.is_empty());
+        assert_eq!(
+            error.render(),
+            "error: invalid bytecode in <main> at 0000: OpAdd takes 2 values from a stack holding 0"
+        );
+    }
+}